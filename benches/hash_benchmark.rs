@@ -0,0 +1,261 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use blitzforge::core::crypt::crypt;
+use blitzforge::core::hasher::create_hasher;
+use blitzforge::core::{CandidateBatch, CandidateSource, DigestTable};
+use hashbrown::HashMap;
+use blitzforge::{Algorithm, Engine, Generator, MaskGenerator, Target};
+
+const ALGORITHMS: [Algorithm; 4] = [
+    Algorithm::BlitzHash,
+    Algorithm::Md5,
+    Algorithm::Sha1,
+    Algorithm::Sha256,
+];
+
+fn bench_hashers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hash");
+    group.throughput(Throughput::Elements(1));
+
+    for algo in ALGORITHMS {
+        let hasher = create_hasher(algo);
+        group.bench_function(algo.to_string(), |b| {
+            b.iter(|| hasher.hash(black_box(b"password123")))
+        });
+    }
+
+    group.finish();
+}
+
+// unreachable targets across every algorithm, a few sharing a salt, so the
+// engine exhausts the full keyspace hashing each candidate per (algorithm, salt)
+fn mixed_targets() -> Vec<Target> {
+    let mut targets = Vec::new();
+
+    for (idx, algo) in ALGORITHMS.iter().enumerate() {
+        for n in 0..4 {
+            let salt = if n % 2 == 0 { String::new() } else { "salt".to_string() };
+            let hasher = create_hasher(*algo);
+            targets.push(Target {
+                id: format!("bench{}_{}", idx, n),
+                username: format!("user{}", n),
+                algorithm: *algo,
+                hash: hex::encode(hasher.hash(format!("not-in-keyspace-{}", n).as_bytes())),
+                salt,
+                ..Default::default()
+            });
+        }
+    }
+
+    targets
+}
+
+fn bench_engine_mixed(c: &mut Criterion) {
+    let targets = mixed_targets();
+    let mask = "?l?l?d";
+    let keyspace = 26 * 26 * 10;
+
+    let mut group = c.benchmark_group("engine");
+    group.throughput(Throughput::Elements(keyspace));
+    group.sample_size(20);
+
+    group.bench_function("mixed_targets", |b| {
+        b.iter(|| {
+            let generator = MaskGenerator::new(mask).unwrap();
+            let mut engine = Engine::new(targets.clone(), Box::new(generator), 4, 4096);
+            engine.run(|_| {}).unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+// the headline case: many unsalted targets of one algorithm, none reachable
+fn md5_targets(count: usize) -> Vec<Target> {
+    let hasher = create_hasher(Algorithm::Md5);
+    (0..count)
+        .map(|n| Target {
+            id: format!("md5_{}", n),
+            username: format!("user{}", n),
+            algorithm: Algorithm::Md5,
+            hash: hex::encode(hasher.hash(format!("not-in-keyspace-{}", n).as_bytes())),
+            ..Default::default()
+        })
+        .collect()
+}
+
+// the same run through the single-group digest lookup and the general
+// per-target path, so the difference is what the fast path buys
+fn bench_engine_unsalted(c: &mut Criterion) {
+    let targets = md5_targets(1000);
+    let mask = "?l?l?d";
+    let keyspace = 26 * 26 * 10;
+
+    let mut group = c.benchmark_group("engine_unsalted_md5");
+    group.throughput(Throughput::Elements(keyspace));
+    group.sample_size(20);
+
+    for fast in [true, false] {
+        let name = if fast { "fast_path" } else { "general_path" };
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let generator = MaskGenerator::new(mask).unwrap();
+                let mut engine = Engine::new(targets.clone(), Box::new(generator), 4, 4096);
+                if !fast {
+                    engine = engine.without_fast_path();
+                }
+                engine.run(|_| {}).unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+// one fast-path probe per candidate, nearly all misses: the hashmap the
+// engine used to build, a binary search and a linear scan over sorted
+// digests (the scan compiles to wide compares), and `DigestTable`
+fn bench_digest_lookup(c: &mut Criterion) {
+    let hasher = create_hasher(Algorithm::Md5);
+    let probes: Vec<Vec<u8>> = (0..4096).map(|n| hasher.hash(format!("probe{}", n).as_bytes())).collect();
+
+    let mut group = c.benchmark_group("digest_lookup");
+    group.throughput(Throughput::Elements(probes.len() as u64));
+
+    for count in [16, 1000, 100_000] {
+        let digests: Vec<[u8; 16]> = md5_targets(count).iter()
+            .map(|t| hex::decode(&t.hash).unwrap().try_into().unwrap())
+            .collect();
+
+        let mut map: HashMap<Vec<u8>, Vec<usize>> = HashMap::new();
+        for (i, digest) in digests.iter().enumerate() {
+            map.entry(digest.to_vec()).or_default().push(i);
+        }
+        group.bench_function(format!("hashmap/{}", count), |b| {
+            b.iter(|| probes.iter().filter(|p| map.get(p.as_slice()).is_some()).count())
+        });
+
+        let mut sorted = digests.clone();
+        sorted.sort_unstable();
+        group.bench_function(format!("binary_search/{}", count), |b| {
+            b.iter(|| probes.iter().filter(|p| sorted.binary_search_by(|d| d.as_slice().cmp(p)).is_ok()).count())
+        });
+        if count <= 1000 {
+            group.bench_function(format!("linear_scan/{}", count), |b| {
+                b.iter(|| probes.iter().filter(|p| sorted.iter().any(|d| d == p.as_slice())).count())
+            });
+        }
+
+        let table = DigestTable::new(digests.iter().enumerate().map(|(i, d)| (d.to_vec(), i)));
+        group.bench_function(format!("digest_table/{}", count), |b| {
+            b.iter(|| probes.iter().filter(|p| table.get(p).next().is_some()).count())
+        });
+    }
+
+    group.finish();
+}
+
+// a cheap hash where generating candidates is a real share of the work:
+// workers generating their own keyspace ranges against the generator
+// filling every batch on one thread
+fn bench_engine_ranges(c: &mut Criterion) {
+    let targets = md5_targets(16);
+    let mask = "?l?l?l?d";
+    let keyspace = 26 * 26 * 26 * 10;
+
+    let mut group = c.benchmark_group("engine_ranges_md5");
+    group.throughput(Throughput::Elements(keyspace));
+    group.sample_size(10);
+
+    for ranges in [true, false] {
+        let name = if ranges { "parallel_ranges" } else { "batch_path" };
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let generator = MaskGenerator::new(mask).unwrap();
+                let mut engine = Engine::new(targets.clone(), Box::new(generator), 4, 16384);
+                if !ranges {
+                    engine = engine.without_parallel_ranges();
+                }
+                engine.run(|_| {}).unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+// many md5-crypt targets under two salts, none reachable: with
+// `with_hash_once` each candidate is crypted twice, otherwise once per target
+fn bench_engine_shared_salts(c: &mut Criterion) {
+    let targets: Vec<Target> = (0..32)
+        .map(|n| {
+            let setting = if n % 2 == 0 { "$1$pepper" } else { "$1$nacl" };
+            Target {
+                id: format!("md5crypt_{}", n),
+                username: format!("user{}", n),
+                algorithm: Algorithm::Md5Crypt,
+                hash: crypt(Algorithm::Md5Crypt, setting, format!("not-in-keyspace-{}", n).as_bytes()).unwrap(),
+                ..Default::default()
+            }
+        })
+        .collect();
+    let mask = "?d?d";
+
+    let mut group = c.benchmark_group("engine_shared_salts");
+    group.throughput(Throughput::Elements(100));
+    group.sample_size(10);
+
+    for hash_once in [true, false] {
+        let name = if hash_once { "hash_once" } else { "per_target" };
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let generator = MaskGenerator::new(mask).unwrap();
+                let mut engine = Engine::new(targets.clone(), Box::new(generator), 4, 4096);
+                if hash_once {
+                    engine = engine.with_hash_once();
+                }
+                engine.run(|_| {}).unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+// candidate generation alone: a fresh vec per candidate from `next_batch`
+// against refilling one packed buffer with `next_into`
+fn bench_candidate_sources(c: &mut Criterion) {
+    let mask = "?l?l?l?d";
+    let keyspace = 26 * 26 * 26 * 10;
+
+    let mut group = c.benchmark_group("candidates");
+    group.throughput(Throughput::Elements(keyspace));
+
+    group.bench_function("next_batch", |b| {
+        b.iter(|| {
+            let mut generator = MaskGenerator::new(mask).unwrap();
+            let mut bytes = 0;
+            while let Some(batch) = generator.next_batch(4096) {
+                bytes += batch.iter().map(Vec::len).sum::<usize>();
+            }
+            black_box(bytes)
+        })
+    });
+
+    group.bench_function("next_into", |b| {
+        let mut buf = CandidateBatch::new(4096);
+        b.iter(|| {
+            let mut generator = MaskGenerator::new(mask).unwrap();
+            let mut bytes = 0;
+            while generator.next_into(&mut buf) {
+                bytes += buf.iter().map(<[u8]>::len).sum::<usize>();
+            }
+            black_box(bytes)
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_hashers, bench_engine_mixed, bench_engine_unsalted, bench_digest_lookup, bench_engine_ranges, bench_engine_shared_salts, bench_candidate_sources);
+criterion_main!(benches);
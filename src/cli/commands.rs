@@ -1,196 +1,2904 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::path::PathBuf;
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader, Write};
-use clap::ValueEnum;
+use clap::{Args, ValueEnum};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
 
 use crate::core::*;
-use crate::cli::{TerminalUI, BenchmarkLogger};
+use crate::core::blitzhash::{self, Endian};
+use crate::core::run_report::{self, RunConfig, RunReport};
+use crate::cli::{ui, TerminalUI, BenchmarkLogger};
+use crate::cli::ui::{ui_println, UiStream};
+use crate::cli::dashboard::Dashboard;
+use crate::cli::exit_code::usage_error;
+use crate::cli::interrupt;
+use crate::cli::progress_file::ProgressFile;
 use crate::tools;
 
-#[derive(Debug, Clone, Copy, ValueEnum)]
+/// default brute force charset
+pub(crate) const DEFAULT_CHARSET: &str = "abcdefghijklmnopqrstuvwxyz0123456789";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
 pub enum Strategy {
+    #[default]
     Dictionary,
     Mask,
     Brute,
     Hybrid,
+    /// candidates from an external command's stdout (`--generator-cmd`)
+    Command,
+}
+
+/// on-disk layout for generated targets
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum TargetFormat {
+    /// pretty-printed json, easy to edit by hand
+    #[default]
+    Json,
+    /// compact length-prefixed `.bft` layout for very large target sets
+    Binary,
+}
+
+/// order to attack a wordlist in
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum WordlistSort {
+    /// stream the file as-is
+    #[default]
+    None,
+    /// shortest candidates first
+    Length,
+    /// byte-wise lexicographic order
+    Lex,
+    /// heaviest first, by each line's `word,weight` column (`--weighted`)
+    Weight,
+}
+
+/// `--batch-size`: candidates per batch, with optional per-algorithm
+/// sizes, e.g. "4096,bcrypt=64,blitzhash=65536"
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BatchSizes {
+    /// size for algorithms without their own entry
+    pub default: Option<usize>,
+    pub per_algorithm: Vec<(Algorithm, usize)>,
+}
+
+impl BatchSizes {
+    pub fn uniform(size: usize) -> Self {
+        Self { default: Some(size), per_algorithm: Vec::new() }
+    }
+}
+
+impl std::str::FromStr for BatchSizes {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let size = |n: &str| match n.trim().parse::<usize>() {
+            Ok(0) | Err(_) => Err(format!("invalid batch size '{}' (expected a positive number)", n.trim())),
+            Ok(n) => Ok(n),
+        };
+        
+        let mut sizes = BatchSizes::default();
+        for part in s.split(',') {
+            match part.split_once('=') {
+                Some((algo, n)) => {
+                    let algorithm: Algorithm = algo.trim().parse()?;
+                    if sizes.per_algorithm.iter().any(|(a, _)| *a == algorithm) {
+                        return Err(format!("batch size for {} given twice", algorithm));
+                    }
+                    sizes.per_algorithm.push((algorithm, size(n)?));
+                }
+                None if sizes.default.is_some() => return Err("more than one default batch size".to_string()),
+                None => sizes.default = Some(size(part)?),
+            }
+        }
+        Ok(sizes)
+    }
+}
+
+/// how several wordlists are combined into one keyspace
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum WordlistStrategy {
+    /// exhaust each list before starting the next
+    #[default]
+    Sequential,
+    /// one batch from each list in turn
+    Interleaved,
+}
+
+/// options for the `generate-targets` command
+#[derive(Debug, Clone, Args)]
+pub struct GenerateTargetsArgs {
+    /// output json file path
+    #[arg(short, long)]
+    pub out: PathBuf,
+    
+    /// input passwords file (one per line), or - for stdin
+    #[arg(short, long, required_unless_present = "seed_passwords")]
+    pub passwords: Option<PathBuf>,
+    
+    /// make up this many random passwords instead of reading --passwords,
+    /// and write them to an answer key alongside the targets
+    #[arg(long)]
+    pub seed_passwords: Option<usize>,
+    
+    /// characters synthetic passwords are drawn from
+    #[arg(long, default_value = DEFAULT_CHARSET, requires = "seed_passwords")]
+    pub charset: String,
+    
+    /// shortest synthetic password
+    #[arg(long, default_value = "6", requires = "seed_passwords")]
+    pub min_len: usize,
+    
+    /// longest synthetic password; lengths are spread evenly in between
+    #[arg(long, default_value = "8", requires = "seed_passwords")]
+    pub max_len: usize,
+    
+    /// rng seed for synthetic passwords, to regenerate the same set
+    /// [default: random, printed]
+    #[arg(long, requires = "seed_passwords")]
+    pub seed: Option<u64>,
+    
+    /// where the `id<TAB>password` answer key goes [default: next to --out,
+    /// e.g. targets.answers.txt]
+    #[arg(long, requires = "seed_passwords")]
+    pub answer_key: Option<PathBuf>,
+    
+    /// comma-separated algorithms (md5,sha1,sha256,md4,scrypt,argon2,bcrypt,md5crypt,sha256crypt,sha512crypt)
+    #[arg(short, long, default_value = "md5,sha256")]
+    pub algorithms: String,
+    
+    /// output format; `run` detects either automatically
+    #[arg(short, long, value_enum, default_value_t = TargetFormat::Json)]
+    pub format: TargetFormat,
+    
+    /// byte order of blitzhash digests (le is canonical)
+    #[arg(long, default_value = "le")]
+    pub blitzhash_endian: Endian,
+    
+    /// bytes each password is hashed as (utf8, utf16le, latin1); recorded
+    /// on the targets so `run` encodes candidates the same way
+    #[arg(long, default_value = "utf8")]
+    pub password_encoding: PasswordEncoding,
+    
+    /// secret appended to every password before hashing and not stored on
+    /// the targets (default: $BLITZFORGE_PEPPER); `run` needs the same one
+    #[arg(long)]
+    pub pepper: Option<String>,
+}
+
+/// --pepper, or $BLITZFORGE_PEPPER when it isn't given
+fn resolve_pepper(pepper: Option<String>) -> Option<String> {
+    pepper.or_else(|| std::env::var("BLITZFORGE_PEPPER").ok()).filter(|p| !p.is_empty())
+}
+
+/// `--algorithms md5,sha1`, rejecting the whole list if any name is
+/// unknown - dropping it would leave targets quietly missing
+fn parse_algorithm_list(list: &str) -> Result<Vec<Algorithm>> {
+    let mut algorithms = Vec::new();
+    let mut unknown = Vec::new();
+    for name in list.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        match name.parse() {
+            Ok(algorithm) => algorithms.push(algorithm),
+            Err(_) => unknown.push(name),
+        }
+    }
+    
+    if !unknown.is_empty() {
+        return Err(usage_error(format!(
+            "unknown algorithm{} {} (expected {})",
+            if unknown.len() == 1 { "" } else { "s" },
+            unknown.join(", "),
+            Algorithm::ALL.iter().map(|a| a.to_string()).collect::<Vec<_>>().join(", "))));
+    }
+    if algorithms.is_empty() {
+        return Err(usage_error("no algorithms specified"));
+    }
+    Ok(algorithms)
 }
 
-pub fn generate_targets(
-    out: PathBuf,
-    passwords: PathBuf,
-    algorithms: String,
-) -> Result<()> {
+pub fn generate_targets(args: GenerateTargetsArgs) -> Result<()> {
     println!("🔧 Generating demo targets...");
     
-    let algos: Vec<Algorithm> = algorithms
-        .split(',')
-        .filter_map(|s| s.trim().parse().ok())
-        .collect();
+    let GenerateTargetsArgs { out, algorithms, format, password_encoding: encoding, .. } = args;
     
-    if algos.is_empty() {
-        anyhow::bail!("no valid algorithms specified");
+    if format == TargetFormat::Binary && encoding != PasswordEncoding::Utf8 {
+        return Err(usage_error(".bft files don't record a password encoding; use --format json"));
     }
     
-    // read passwords
-    let file = File::open(&passwords)?;
-    let reader = BufReader::new(file);
-    let passwords: Vec<String> = reader
-        .lines()
-        .filter_map(|l| l.ok())
-        .filter(|l| !l.trim().is_empty())
-        .collect();
+    let algos = parse_algorithm_list(&algorithms)?;
+    
+    let mut seed = None;
+    let passwords = match (&args.passwords, args.seed_passwords) {
+        // checked here: a clap conflict would also waive the `requires` on
+        // --seed and friends whenever --passwords is given
+        (Some(_), Some(_)) => return Err(usage_error("--passwords and --seed-passwords can't be combined")),
+        (None, Some(count)) => {
+            if args.charset.is_empty() {
+                return Err(usage_error("--charset must not be empty"));
+            }
+            if args.min_len == 0 || args.min_len > args.max_len {
+                return Err(usage_error("need 1 <= --min-len <= --max-len"));
+            }
+            let chosen = args.seed.unwrap_or_else(rand::random);
+            seed = Some(chosen);
+            tools::synthetic_passwords(count, &args.charset, args.min_len, args.max_len, chosen)
+        }
+        // read passwords ("-" reads stdin)
+        (Some(path), None) if path.as_os_str() == "-" => read_passwords(std::io::stdin().lock(), "stdin")?,
+        (Some(path), None) => read_passwords(BufReader::new(File::open(path)?), &path.display().to_string())?,
+        (None, None) => unreachable!("clap requires --passwords or --seed-passwords"),
+    };
     
     if passwords.is_empty() {
         anyhow::bail!("no passwords found in file");
     }
     
     // generate targets
-    let targets = tools::generate_demo_targets(&passwords, &algos);
+    let pepper = resolve_pepper(args.pepper);
+    let mut targets = tools::generate_peppered_targets(&passwords, &algos, encoding, pepper.as_deref().unwrap_or_default().as_bytes())?;
+    convert_blitz_endian(&mut targets, args.blitzhash_endian);
     
-    // write to json
-    let json = serde_json::to_string_pretty(&targets)?;
-    fs::write(&out, json)?;
+    match format {
+        TargetFormat::Json => fs::write(&out, serde_json::to_string_pretty(&targets)?)?,
+        TargetFormat::Binary => fs::write(&out, targets_file::encode_binary(&targets))?,
+    }
     
     println!("✅ Generated {} targets → {}", targets.len(), out.display());
     println!("   Algorithms: {}", algorithms);
     println!("   Passwords: {}", passwords.len());
+    if encoding != PasswordEncoding::Utf8 {
+        println!("   Encoding: {}", encoding);
+    }
+    
+    // synthetic passwords exist nowhere else, so they get written down
+    if let Some(seed) = seed {
+        let key_path = args.answer_key.unwrap_or_else(|| out.with_extension("answers.txt"));
+        fs::write(&key_path, tools::answer_key(&passwords, &algos))?;
+        println!("   Seed: {} (pass --seed {} to regenerate these passwords)", seed, seed);
+        println!("🔑 Answer key → {}", key_path.display());
+    }
     
     Ok(())
 }
 
-pub fn run_cracking(
-    targets_path: PathBuf,
-    strategy: Strategy,
-    wordlist: Option<PathBuf>,
-    mask: Option<String>,
-    charset: Option<String>,
-    min_len: usize,
-    max_len: usize,
-    workers: Option<usize>,
-    batch_size: usize,
-    repeat: usize,
-    log: Option<PathBuf>,
-) -> Result<()> {
-    // load targets
-    let json = fs::read_to_string(&targets_path)?;
-    let targets: Vec<Target> = serde_json::from_str(&json)?;
+/// one password per line, skipping blank lines. a line that can't be read
+/// (or isn't utf-8) is an error naming `source` and the line
+fn read_passwords(reader: impl BufRead, source: &str) -> Result<Vec<String>> {
+    let mut passwords = Vec::new();
+    for (n, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| anyhow::anyhow!("{}:{}: {}", source, n + 1, e))?;
+        if !line.trim().is_empty() {
+            passwords.push(line);
+        }
+    }
+    Ok(passwords)
+}
+
+/// named attack configurations for new users
+///
+/// | preset         | expands to                                        |
+/// |----------------|---------------------------------------------------|
+/// | `pin4`         | `--strategy brute --charset 0123456789 --min-len 4 --max-len 4` |
+/// | `pin6`         | `--strategy brute --charset 0123456789 --min-len 6 --max-len 6` |
+/// | `fast-dict`    | `--strategy dictionary --batch-size 16384`        |
+/// | `lower6`       | `--strategy brute --charset a-z --min-len 1 --max-len 6` |
+/// | `word-2digits` | `--strategy mask --mask ?l?l?l?l?d?d`             |
+/// | `ntlm-common`  | `--strategy dictionary --common-rules`            |
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Preset {
+    Pin4,
+    Pin6,
+    FastDict,
+    Lower6,
+    #[value(name = "word-2digits")]
+    Word2Digits,
+    NtlmCommon,
+}
+
+/// the flag values a preset expands into
+#[derive(Debug, Clone, Default)]
+pub struct PresetConfig {
+    pub strategy: Option<Strategy>,
+    pub mask: Option<&'static str>,
+    pub charset: Option<&'static str>,
+    pub min_len: Option<usize>,
+    pub max_len: Option<usize>,
+    pub batch_size: Option<usize>,
+    pub common_rules: bool,
+}
+
+impl Preset {
+    pub fn config(self) -> PresetConfig {
+        match self {
+            Preset::Pin4 => PresetConfig {
+                strategy: Some(Strategy::Brute),
+                charset: Some("0123456789"),
+                min_len: Some(4),
+                max_len: Some(4),
+                ..Default::default()
+            },
+            Preset::Pin6 => PresetConfig {
+                strategy: Some(Strategy::Brute),
+                charset: Some("0123456789"),
+                min_len: Some(6),
+                max_len: Some(6),
+                ..Default::default()
+            },
+            Preset::FastDict => PresetConfig {
+                strategy: Some(Strategy::Dictionary),
+                batch_size: Some(16384),
+                ..Default::default()
+            },
+            Preset::Lower6 => PresetConfig {
+                strategy: Some(Strategy::Brute),
+                charset: Some("abcdefghijklmnopqrstuvwxyz"),
+                min_len: Some(1),
+                max_len: Some(6),
+                ..Default::default()
+            },
+            Preset::Word2Digits => PresetConfig {
+                strategy: Some(Strategy::Mask),
+                mask: Some("?l?l?l?l?d?d"),
+                ..Default::default()
+            },
+            Preset::NtlmCommon => PresetConfig {
+                strategy: Some(Strategy::Dictionary),
+                common_rules: true,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// candidate generator parameters shared by every run of a job. the
+/// commands that only preview or size a keyspace fill in what they use and
+/// take `Default` for the rest
+#[derive(Debug, Clone, Default)]
+pub struct GeneratorOptions {
+    pub strategy: Strategy,
+    pub wordlists: Vec<PathBuf>,
+    pub no_trim: bool,
+    /// skip wordlist lines longer than this many bytes
+    pub max_word_len: Option<usize>,
+    /// follow every wordlist word with its common variants (`run --common-rules`)
+    pub common_rules: bool,
+    pub mask: Option<String>,
+    /// length `mask` must give, checked when the generator is built
+    pub mask_len: Option<usize>,
+    pub charset: Option<String>,
+    pub min_len: usize,
+    pub max_len: usize,
+    /// candidates to skip from the start of the keyspace
+    pub skip: u64,
+    /// stop after this many candidates
+    pub limit: Option<u64>,
+    /// longest candidate worth generating, when every target truncates its input
+    pub max_candidate_len: Option<usize>,
+    /// shell command whose stdout lines are the candidates (command strategy)
+    pub generator_cmd: Option<String>,
+    /// load the wordlist into memory and sort it before attacking
+    pub sort_wordlist: WordlistSort,
+    /// order to read several wordlists in
+    pub wordlist_strategy: WordlistStrategy,
+    /// keep reading lines appended to the (single) wordlist until it's
+    /// been idle this long
+    pub follow_wordlist: Option<std::time::Duration>,
+    /// memory the sorted wordlist may take (default: what's available)
+    pub max_memory: Option<u64>,
+    /// the dictionary stage's candidates, read once and shared by every
+    /// generator made from these options (`run --dictionary-cache`)
+    pub wordlist_cache: Option<MemoryGenerator>,
+    /// brute force only the `.` positions of this template (brute strategy)
+    pub template: Option<String>,
+    /// try only this many candidates, drawn at random from the keyspace
+    /// with this seed (`run --sample-keyspace`)
+    pub sample: Option<(u64, u64)>,
+}
+
+/// expand wordlist arguments, replacing directories with their files (sorted
+/// by name). a file in a directory that can't be read - no permission, a
+/// dangling link - is skipped with a warning; one named outright still
+/// fails the run when it's opened
+fn expand_wordlists(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut expanded = Vec::new();
     
-    if targets.is_empty() {
-        anyhow::bail!("no targets found in file");
+    for path in paths {
+        if !path.is_dir() {
+            expanded.push(path.clone());
+            continue;
+        }
+        
+        let mut files = Vec::new();
+        for entry in fs::read_dir(path).with_context(|| format!("cannot list wordlist directory {}", path.display()))? {
+            let file = match entry {
+                Ok(entry) => entry.path(),
+                Err(e) => {
+                    eprintln!("⚠️  skipping an entry of {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            // subdirectories and the like aren't lists
+            let readable = fs::metadata(&file).and_then(|meta| match meta.is_file() {
+                true => File::open(&file).map(|_| true),
+                false => Ok(false),
+            });
+            match readable {
+                Ok(true) => files.push(file),
+                Ok(false) => {}
+                Err(e) => eprintln!("⚠️  skipping unreadable wordlist {}: {}", file.display(), e),
+            }
+        }
+        files.sort();
+        expanded.extend(files);
+    }
+    
+    Ok(expanded)
+}
+
+/// the wordlists as one generator, read from disk as it goes
+fn open_dictionary(options: &GeneratorOptions) -> Result<Box<dyn Generator>> {
+    let wordlists = expand_wordlists(&options.wordlists)?;
+    Ok(match wordlists.as_slice() {
+        [] => return Err(usage_error("--wordlist required for dictionary strategy")),
+        [single] => {
+            let dictionary = DictionaryGenerator::new(single)?
+                .with_trim(!options.no_trim)
+                .with_max_word_len(options.max_word_len)
+                .with_weights(options.sort_wordlist == WordlistSort::Weight);
+            match options.follow_wordlist {
+                Some(idle) => Box::new(dictionary.with_follow(idle)),
+                None => Box::new(dictionary),
+            }
+        }
+        _ if options.follow_wordlist.is_some() => {
+            return Err(usage_error("--follow-wordlist needs a single wordlist file"));
+        }
+        many => Box::new(MultiDictionaryGenerator::new(many)?
+            .with_trim(!options.no_trim)
+            .with_max_word_len(options.max_word_len)
+            .with_weights(options.sort_wordlist == WordlistSort::Weight)
+            .with_interleaved(options.wordlist_strategy == WordlistStrategy::Interleaved)),
+    })
+}
+
+/// the wordlists read into memory (within --max-memory) and sorted as
+/// --sort-wordlist asks
+pub fn dictionary_in_memory(options: &GeneratorOptions) -> Result<MemoryGenerator> {
+    if options.follow_wordlist.is_some() {
+        return Err(usage_error("--follow-wordlist can't be combined with --sort-wordlist or --dictionary-cache"));
+    }
+    
+    let mut dictionary = open_dictionary(options)?;
+    let budget = options.max_memory.or_else(available_memory);
+    let loaded = match options.sort_wordlist {
+        WordlistSort::Weight => MemoryGenerator::load_by_weight(dictionary.as_mut(), budget),
+        _ => MemoryGenerator::load(dictionary.as_mut(), budget),
+    };
+    let mut loaded = loaded
+        .map_err(|e| anyhow::anyhow!("can't hold wordlist in memory: {} - raise --max-memory", e))?;
+    match options.sort_wordlist {
+        WordlistSort::Length => loaded.sort_by_length(),
+        WordlistSort::Lex => loaded.sort_lex(),
+        WordlistSort::Weight | WordlistSort::None => {}
+    }
+    Ok(loaded)
+}
+
+/// parse `--mask`, failing unless it gives candidates of `--mask-len`
+/// characters - a dropped `?l` errors here rather than after a long run
+fn mask_of_len(pattern: &str, expected_len: Option<usize>) -> Result<MaskGenerator> {
+    let mask = MaskGenerator::new(pattern)?;
+    if let Some(len) = expected_len.filter(|&len| len != mask.length()) {
+        return Err(usage_error(format!("mask {} gives {}-character candidates, expected {}", pattern, mask.length(), len)));
+    }
+    Ok(mask)
+}
+
+/// `--explain-keyspace` for one stage: where a mask or brute-force
+/// keyspace's size comes from, built the way `create_generator` builds it
+fn explain_keyspace_of(options: &GeneratorOptions, strategy: Strategy) -> Result<Vec<String>> {
+    let cap = options.max_candidate_len.unwrap_or(usize::MAX);
+    let charset = options.charset.as_deref().unwrap_or(DEFAULT_CHARSET);
+    let labelled = |mask: &MaskGenerator| -> Vec<(String, usize)> {
+        mask.charsets().iter().map(|cs| (charset_label(&cs.chars, charset), cs.chars.len())).collect()
+    };
+    
+    let (title, positions, min_len) = match (strategy, &options.mask, &options.template) {
+        (Strategy::Mask, Some(pattern), _) => {
+            let mask = mask_of_len(pattern, options.mask_len)?.truncated(cap);
+            (format!("mask {}", pattern), labelled(&mask), mask.positions())
+        }
+        (Strategy::Brute, _, Some(template)) => {
+            let template_mask = MaskGenerator::from_template(template, charset)?.truncated(cap);
+            (format!("template {}", template), labelled(&template_mask), template_mask.positions())
+        }
+        (Strategy::Brute, _, None) => {
+            let positions = vec![("charset".to_string(), charset.len()); options.max_len.min(cap)];
+            (format!("brute force over {} characters", charset.len()), positions, options.min_len.min(cap))
+        }
+        _ => return Ok(vec![format!("📐 {}: candidates come from a list, not positions", stage_names(&[strategy])[0])]),
+    };
+    
+    let mut lines = vec![format!("📐 Keyspace of {}:", title)];
+    lines.extend(keyspace_breakdown(&positions, min_len));
+    Ok(lines)
+}
+
+/// how a mask position reads back: its `?x` class, the literal byte, or
+/// `charset` for the brute-force charset
+fn charset_label(chars: &[u8], brute_charset: &str) -> String {
+    let classes = [
+        ("?l", CharSet::lowercase()),
+        ("?u", CharSet::uppercase()),
+        ("?d", CharSet::digits()),
+        ("?s", CharSet::special()),
+    ];
+    if let Some((label, _)) = classes.iter().find(|(_, cs)| cs.chars == chars) {
+        return label.to_string();
     }
+    match chars {
+        [byte] => format!("'{}'", byte.escape_ascii()),
+        _ if chars == brute_charset.as_bytes() => "charset".to_string(),
+        _ => format!("{} chars", chars.len()),
+    }
+}
+
+/// one row per position, `(label, charset size)`, with the running product
+/// of the sizes - the keyspace of candidates that long. candidates from
+/// `min_len` positions up all count, so a range of lengths adds a total
+fn keyspace_breakdown(positions: &[(String, usize)], min_len: usize) -> Vec<String> {
+    let mut lines = vec![format!("   {:>4}  {:<8} {:>5}  {}", "pos", "charset", "size", "keyspace")];
+    let mut running = 1u128;
+    let mut total = 0u128;
     
-    // create generator based on strategy
-    let generator: Box<dyn Generator> = match strategy {
+    for (i, (label, size)) in positions.iter().enumerate() {
+        running = running.saturating_mul(*size as u128);
+        if i + 1 >= min_len {
+            total = total.saturating_add(running);
+        }
+        lines.push(format!("   {:>4}  {:<8} {:>5}  {}", i + 1, label, size, running));
+    }
+    if min_len < positions.len() {
+        lines.push(format!("   lengths {} to {} together: {}", min_len, positions.len(), total));
+    }
+    lines
+}
+
+/// create generator based on strategy
+pub fn create_generator(options: &GeneratorOptions) -> Result<Box<dyn Generator>> {
+    let generator: Box<dyn Generator> = match options.strategy {
         Strategy::Dictionary => {
-            let wordlist_path = wordlist.ok_or_else(|| anyhow::anyhow!("--wordlist required for dictionary strategy"))?;
-            Box::new(DictionaryGenerator::new(wordlist_path)?)
+            let dictionary: Box<dyn Generator> = match &options.wordlist_cache {
+                Some(cache) => {
+                    let mut cached = cache.clone();
+                    cached.reset();
+                    Box::new(cached)
+                }
+                None if options.sort_wordlist == WordlistSort::None => open_dictionary(options)?,
+                None => Box::new(dictionary_in_memory(options)?),
+            };
+            match options.common_rules {
+                true => Box::new(RulesGenerator::new(dictionary)),
+                false => dictionary,
+            }
         }
         
         Strategy::Mask => {
-            let mask_pattern = mask.ok_or_else(|| anyhow::anyhow!("--mask required for mask strategy"))?;
-            Box::new(MaskGenerator::new(&mask_pattern)?)
+            let mask_pattern = options.mask.as_ref()
+                .ok_or_else(|| usage_error("--mask required for mask strategy"))?;
+            let mask = mask_of_len(mask_pattern, options.mask_len)?;
+            match options.max_candidate_len {
+                Some(len) => Box::new(mask.truncated(len)),
+                None => Box::new(mask),
+            }
         }
         
         Strategy::Brute => {
-            let charset_str = charset.unwrap_or_else(|| "abcdefghijklmnopqrstuvwxyz0123456789".to_string());
-            Box::new(BruteForceGenerator::new(&charset_str, min_len, max_len))
+            let charset_str = options.charset.as_deref().unwrap_or(DEFAULT_CHARSET);
+            match &options.template {
+                Some(template) => {
+                    let template = MaskGenerator::from_template(template, charset_str)?;
+                    match options.max_candidate_len {
+                        Some(len) => Box::new(template.truncated(len)),
+                        None => Box::new(template),
+                    }
+                }
+                None => {
+                    // longer candidates only repeat the length-`cap` ones
+                    let cap = options.max_candidate_len.unwrap_or(usize::MAX);
+                    let min_len = options.min_len.min(cap);
+                    let max_len = options.max_len.min(cap);
+                    Box::new(BruteForceGenerator::new(charset_str, min_len, max_len))
+                }
+            }
         }
         
         Strategy::Hybrid => {
-            anyhow::bail!("hybrid strategy not yet implemented");
+            return Err(usage_error("hybrid strategy not yet implemented"));
+        }
+        
+        Strategy::Command => {
+            let command = options.generator_cmd.as_ref()
+                .ok_or_else(|| usage_error("--generator-cmd required for command strategy"))?;
+            Box::new(ProcessGenerator::new(command)?)
         }
     };
     
-    let workers_count = workers.unwrap_or_else(num_cpus::get);
+    if options.skip > 0 || options.limit.is_some() {
+        return Ok(Box::new(RangeGenerator::new(generator, options.skip, options.limit)));
+    }
     
-    // create terminal ui
-    let mut ui = TerminalUI::new();
+    if let Some((count, seed)) = options.sample {
+        let sample = SampleGenerator::new(generator, count, seed)
+            .map_err(|e| usage_error(format!("--sample-keyspace: {}", e)))?;
+        return Ok(Box::new(sample));
+    }
     
-    // print warning banner
-    ui.print_warning();
+    Ok(generator)
+}
+
+/// options for the `run` command
+#[derive(Debug, Clone, Args)]
+pub struct RunArgs {
+    /// targets json file
+    #[arg(short, long, required_unless_present = "inline_targets")]
+    pub targets: Option<PathBuf>,
     
-    // print configuration
-    println!("\n📋 Configuration:");
-    println!("   Targets:    {}", targets.len());
-    println!("   Strategy:   {:?}", strategy);
-    println!("   Workers:    {}", workers_count);
-    println!("   Batch size: {}", batch_size);
-    println!("   Repeats:    {}", repeat);
+    /// a target given inline as id:algo:hash[:salt], instead of a file;
+    /// repeat for several. the salt is the rest of the spec, colons and all
+    #[arg(long = "target", value_name = "SPEC", conflicts_with = "targets",
+        value_parser = targets_file::parse_inline_target)]
+    pub inline_targets: Vec<Target>,
+    
+    /// named attack preset (pin4, pin6, fast-dict, lower6, word-2digits);
+    /// explicit flags override the preset's values
+    #[arg(long, value_enum)]
+    pub preset: Option<Preset>,
+    
+    /// replay the options saved by --save-config (or a --report-out
+    /// report); explicit flags and --preset override them
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+    
+    /// write the run's resolved options to this json file before it starts
+    #[arg(long)]
+    pub save_config: Option<PathBuf>,
+    
+    /// attack strategy (required unless --preset is given)
+    #[arg(short, long, value_enum)]
+    pub strategy: Option<Strategy>,
+    
+    /// run several strategies in order, e.g. "dictionary,mask,brute"; each
+    /// stage attacks only the targets earlier stages left uncracked
+    #[arg(long, value_enum, value_delimiter = ',', conflicts_with = "strategy")]
+    pub strategy_pipeline: Vec<Strategy>,
+    
+    /// wordlist path (for dictionary/hybrid modes); repeat the flag,
+    /// comma-separate paths, or pass a directory to chain several lists
+    #[arg(short, long, value_delimiter = ',')]
+    pub wordlist: Vec<PathBuf>,
+    
+    /// keep surrounding whitespace on wordlist lines (exact bytes)
+    #[arg(long)]
+    pub no_trim: bool,
     
-    if let Some(est) = generator.estimated_size() {
-        println!("   Keyspace:   {}", format_number(est));
+    /// skip wordlist lines longer than this many bytes, such as pasted
+    /// blobs (default: no limit)
+    #[arg(long)]
+    pub max_word_len: Option<usize>,
+    
+    /// also try every wordlist word lowercased, capitalized, uppercased, and
+    /// with a digit or ! appended, right after the word itself
+    #[arg(long)]
+    pub common_rules: bool,
+    
+    /// load the wordlist into memory and sort it before attacking (bounded
+    /// by --max-memory); changes the order passwords are discovered in
+    #[arg(long, value_enum, default_value_t = WordlistSort::None)]
+    pub sort_wordlist: WordlistSort,
+    
+    /// the wordlist has `word,weight` lines: strip the weights and try the
+    /// heaviest words first (--sort-wordlist weight)
+    #[arg(long, conflicts_with = "sort_wordlist")]
+    pub weighted: bool,
+    
+    /// read the wordlist into memory once (bounded by --max-memory) and
+    /// reuse it for every --repeat run, keeping disk reads out of the timings
+    #[arg(long)]
+    pub dictionary_cache: bool,
+    
+    /// with several wordlists: read them one after another, or round-robin
+    /// a batch from each for early wins from every list
+    #[arg(long, value_enum, default_value_t = WordlistStrategy::Sequential)]
+    pub wordlist_strategy: WordlistStrategy,
+    
+    /// at the end of the wordlist, wait for lines appended to it instead of
+    /// stopping (tail -f), so guesses can be added during a run
+    #[arg(long)]
+    pub follow_wordlist: bool,
+    
+    /// with --follow-wordlist, stop once the wordlist hasn't grown for this
+    /// many seconds
+    #[arg(long, value_name = "SECONDS", default_value_t = 30.0, requires = "follow_wordlist")]
+    pub follow_idle: f64,
+    
+    /// read candidates from this command's stdout, one per line (implies
+    /// --strategy command), e.g. "crunch 6 6 abc123"
+    #[arg(long)]
+    pub generator_cmd: Option<String>,
+    
+    /// mask pattern (for mask/hybrid modes) e.g. ?l?l?l?d?d
+    #[arg(short, long)]
+    pub mask: Option<String>,
+    
+    /// fail unless --mask gives candidates of exactly this many characters,
+    /// to catch a mistyped mask before a long run
+    #[arg(long)]
+    pub mask_len: Option<usize>,
+    
+    /// charset (for brute force mode) e.g. "abc123"
+    #[arg(short, long)]
+    pub charset: Option<String>,
+    
+    /// minimum length (for brute force) [default: 1]
+    #[arg(long)]
+    pub min_len: Option<usize>,
+    
+    /// maximum length (for brute force) [default: 8]
+    #[arg(long)]
+    pub max_len: Option<usize>,
+    
+    /// brute force only part of a password: each `.` runs through the
+    /// charset, other characters are fixed, e.g. "admin.." (replaces
+    /// --min-len/--max-len)
+    #[arg(long)]
+    pub template: Option<String>,
+    
+    /// brute force passwords shaped like this one: the character classes
+    /// it uses, at its length (e.g. Abc123 is mixed-case alnum, length 6)
+    #[arg(long, value_name = "PASSWORD", conflicts_with_all = ["preset", "strategy_pipeline"])]
+    pub like: Option<String>,
+    
+    /// skip this many candidates from the start of the keyspace (for sharding) [default: 0]
+    #[arg(long)]
+    pub skip: Option<u64>,
+    
+    /// stop after this many candidates (for sharding)
+    #[arg(long)]
+    pub limit: Option<u64>,
+    
+    /// try only this many distinct candidates, drawn at random from the
+    /// mask or brute-force keyspace, and report the hit rate
+    #[arg(long, value_name = "K", conflicts_with_all = ["skip", "limit"])]
+    pub sample_keyspace: Option<u64>,
+    
+    /// seed for --sample-keyspace, to draw the same sample again [default: random]
+    #[arg(long, requires = "sample_keyspace")]
+    pub sample_seed: Option<u64>,
+    
+    /// before the run, break a mask or brute-force keyspace down position
+    /// by position: each one's charset size and the running product
+    #[arg(long)]
+    pub explain_keyspace: bool,
+    
+    /// number of worker threads (default: cpu count)
+    #[arg(long)]
+    pub workers: Option<usize>,
+    
+    /// batch size for candidate processing [default: 4096]; add
+    /// algo=size entries to size batches for costly or cheap algorithms,
+    /// e.g. "4096,bcrypt=64"
+    #[arg(long)]
+    pub batch_size: Option<BatchSizes>,
+    
+    /// soft per-batch time limit in seconds; slower batches shrink the batch size
+    #[arg(long)]
+    pub batch_timeout: Option<f64>,
+    
+    /// stop each run after this many seconds, whatever's left of the keyspace
+    #[arg(long)]
+    pub max_time: Option<f64>,
+    
+    /// give each algorithm and salt this many seconds of hashing per stage,
+    /// then give up on its targets still uncracked, reporting them apart
+    /// from the unfound
+    #[arg(long, value_name = "SECONDS")]
+    pub target_timeout: Option<f64>,
+    
+    /// stop the run once this many targets are cracked
+    #[arg(long, value_name = "N", conflicts_with = "stop_after_percent")]
+    pub stop_after_count: Option<usize>,
+    
+    /// stop the run once this percentage of the targets is cracked
+    #[arg(long, value_name = "PERCENT")]
+    pub stop_after_percent: Option<f64>,
+    
+    /// memory the run may use, e.g. 512M or 4G [default: what's available].
+    /// caps workers for memory-hard kdfs unless --workers is given
+    #[arg(long, value_parser = parse_size)]
+    pub max_memory: Option<u64>,
+    
+    /// number of repeat runs for benchmarking [default: 1]
+    #[arg(short, long)]
+    pub repeat: Option<usize>,
+    
+    /// keep repeating runs until the 95% confidence interval of the mean
+    /// H/s is narrower than this percentage of the mean; --repeat becomes
+    /// the most runs to try [default cap: 30]
+    #[arg(long, value_name = "PERCENT")]
+    pub ci_width: Option<f64>,
+    
+    /// attack targets in a random order, reshuffled for each --repeat run,
+    /// so timings don't depend on which targets happen to come first
+    #[arg(long)]
+    pub shuffle_targets: bool,
+    
+    /// seed for --shuffle-targets, to replay the same orderings [default: random]
+    #[arg(long, requires = "shuffle_targets")]
+    pub seed: Option<u64>,
+    
+    /// csv log file for benchmark results
+    #[arg(short, long)]
+    pub log: Option<PathBuf>,
+    
+    /// read --targets as a bare hash list (one hash per line) of this algorithm
+    #[arg(long, requires = "targets")]
+    pub hash_algo: Option<Algorithm>,
+    
+    /// read --targets as a hash list whose lines carry their scheme, e.g.
+    /// {SHA}<base64> or sha256:<hex>, each line taking its algorithm from it
+    #[arg(long, requires = "targets", conflicts_with = "hash_algo")]
+    pub hash_prefixes: bool,
+    
+    /// split each hash-list line into hash and salt at this character
+    /// (e.g. ':' for `hash:salt`); default: the whole line is the hash
+    #[arg(long, requires = "hash_algo")]
+    pub hash_salt_separator: Option<char>,
+    
+    /// byte order of blitzhash digests in the targets file (le is canonical)
+    #[arg(long, default_value = "le")]
+    pub blitzhash_endian: Endian,
+    
+    /// targets with an empty salt are salted with the empty string rather
+    /// than unsalted, unless their `salted` field says otherwise
+    #[arg(long)]
+    pub no_salt_fallback: bool,
+    
+    /// write cracked credentials as json (combine shards with `merge`)
+    #[arg(long)]
+    pub results: Option<PathBuf>,
+    
+    /// results json or potfile from an earlier run: targets it already
+    /// cracked count as found from the start, without guessing
+    #[arg(long, value_name = "PATH")]
+    pub potfile: Option<PathBuf>,
+    
+    /// insert cracked credentials into a sqlite database as they're found
+    /// (one row per target id, kept across runs)
+    #[arg(long, value_name = "PATH")]
+    pub sqlite: Option<PathBuf>,
+    
+    /// write a complete json report of the run: configuration, every
+    /// target's outcome, statistics and the targets file's sha-256
+    #[arg(long)]
+    pub report_out: Option<PathBuf>,
+    
+    /// print the run report (as --report-out writes it) to stdout when
+    /// done; implies --progress-to-stderr so stdout is only the json
+    #[arg(long)]
+    pub json: bool,
+    
+    /// write progress, banners and results text to stderr instead of stdout
+    #[arg(long)]
+    pub progress_to_stderr: bool,
+    
+    /// keep a json snapshot of the run's statistics in this file, replaced
+    /// about twice a second, for dashboards to poll
+    #[arg(long)]
+    pub progress_json: Option<PathBuf>,
+    
+    /// show only the overall progress rows, not one row per target (the
+    /// default above 50 targets)
+    #[arg(long)]
+    pub summary_only: bool,
+    
+    /// show cracked passwords as p******d on screen, for screen-shared
+    /// demos; results, potfiles and reports still get the full value
+    #[arg(long)]
+    pub mask_output: bool,
+    
+    /// list targets that were not cracked (printed, or written to the given file)
+    #[arg(long, num_args = 0..=1)]
+    pub output_unfound: Option<Option<PathBuf>>,
+    
+    /// warn about and skip malformed targets instead of refusing to run
+    #[arg(long)]
+    pub skip_invalid: bool,
+    
+    /// secret appended to every candidate before hashing, for targets
+    /// generated with one (default: $BLITZFORGE_PEPPER)
+    #[arg(long)]
+    pub pepper: Option<String>,
+    
+    /// fold every candidate to this case before hashing (none, upper,
+    /// lower), for schemes that normalise passwords - e.g. lm hashes
+    #[arg(long, default_value = "none")]
+    pub case_fold: CaseFold,
+    
+    /// confirm you're authorized to attack these hashes; required unless
+    /// every target came from generate-targets (or BLITZFORGE_ACK=1 is set)
+    #[arg(long)]
+    pub i_understand: bool,
+    
+    /// pin each worker thread to its own cpu core for steadier benchmarks
+    #[arg(long)]
+    pub pin_threads: bool,
+    
+    /// experimental: keep adjusting how many workers hash, settling on the
+    /// count that measures fastest (helps on thermally throttled laptops)
+    #[arg(long)]
+    pub adaptive_workers: bool,
+    
+    /// after each crack, try variants of the cracked password (its digits
+    /// counted up and down, its case changed) ahead of the rest
+    #[arg(long)]
+    pub adaptive_hints: bool,
+    
+    /// compare identical targets (same algorithm, salt and digest) once
+    /// and report a match against every id sharing it
+    #[arg(long)]
+    pub duplicate_target_collapse: bool,
+    
+    /// crypt each candidate once for all md5/sha256/sha512-crypt targets
+    /// sharing a salt and rounds, instead of once per target
+    #[arg(long)]
+    pub hash_once: bool,
+    
+    /// blitzhash only: list the candidates whose digests shared the most
+    /// leading bytes with a target's (default: the closest 10)
+    #[arg(long, num_args = 0..=1, default_missing_value = "10")]
+    pub track_near_misses: Option<usize>,
+    
+    /// full-screen dashboard instead of inline progress bars (q to quit)
+    #[arg(long)]
+    pub tui: bool,
+}
+
+impl RunArgs {
+    /// fill in the brute force flags the user didn't set from --like, if given
+    pub fn apply_like(&mut self) -> Result<()> {
+        let Some(sample) = &self.like else {
+            return Ok(());
+        };
+        let (charset, min_len, max_len) = tools::infer_charset(sample)
+            .map_err(|e| usage_error(format!("--like: {}", e)))?;
+        
+        self.strategy = self.strategy.or(Some(Strategy::Brute));
+        self.charset = self.charset.take().or(Some(charset));
+        self.min_len = self.min_len.or(Some(min_len));
+        self.max_len = self.max_len.or(Some(max_len));
+        Ok(())
+    }
+    
+    /// fill in flags the user didn't set from the preset, if any
+    pub fn apply_preset(&mut self) {
+        let Some(preset) = self.preset else {
+            return;
+        };
+        let config = preset.config();
+        
+        self.strategy = self.strategy.or(config.strategy);
+        self.mask = self.mask.take().or_else(|| config.mask.map(str::to_string));
+        self.charset = self.charset.take().or_else(|| config.charset.map(str::to_string));
+        self.min_len = self.min_len.or(config.min_len);
+        self.max_len = self.max_len.or(config.max_len);
+        self.common_rules |= config.common_rules;
+        self.fill_batch_size(config.batch_size);
     }
     
-    println!("\n⚡ Starting cracking engine...\n");
+    /// take `size` as the default batch size unless one was given
+    fn fill_batch_size(&mut self, size: Option<usize>) {
+        match &mut self.batch_size {
+            Some(sizes) => sizes.default = sizes.default.or(size),
+            None => self.batch_size = size.map(BatchSizes::uniform),
+        }
+    }
     
-    // create benchmark logger if needed
-    let mut logger = if let Some(log_path) = log {
-        Some(BenchmarkLogger::new(log_path)?)
+    /// fill in flags still unset from a saved config
+    pub fn apply_config(&mut self, config: &RunConfig) -> Result<()> {
+        let stages = config.strategy.split(',')
+            .map(|name| Strategy::from_str(name.trim(), true)
+                .map_err(|_| usage_error(format!("unknown strategy '{}' in config", name))))
+            .collect::<Result<Vec<_>>>()?;
+        
+        // an explicit --generator-cmd picks its own strategy
+        if self.strategy.is_none() && self.strategy_pipeline.is_empty() && self.generator_cmd.is_none() {
+            match stages.as_slice() {
+                [strategy] => self.strategy = Some(*strategy),
+                _ => self.strategy_pipeline = stages,
+            }
+        }
+        
+        if self.wordlist.is_empty() {
+            self.wordlist = config.wordlists.iter().map(PathBuf::from).collect();
+        }
+        self.no_trim |= config.no_trim;
+        self.generator_cmd = self.generator_cmd.take().or_else(|| config.generator_cmd.clone());
+        self.mask = self.mask.take().or_else(|| config.mask.clone());
+        self.charset = self.charset.take().or_else(|| config.charset.clone());
+        self.template = self.template.take().or_else(|| config.template.clone());
+        self.min_len = self.min_len.or(Some(config.min_len));
+        self.max_len = self.max_len.or(Some(config.max_len));
+        self.skip = self.skip.or(Some(config.skip));
+        self.limit = self.limit.or(config.limit);
+        self.workers = self.workers.or(Some(config.workers));
+        self.fill_batch_size(Some(config.batch_size));
+        if let Some(sizes) = self.batch_size.as_mut().filter(|s| s.per_algorithm.is_empty()) {
+            sizes.per_algorithm = config.algorithm_batch_sizes.clone();
+        }
+        self.repeat = self.repeat.or(Some(config.repeat));
+        Ok(())
+    }
+}
+
+/// the targets' algorithms in first-seen order
+fn target_algorithms(targets: &[Target]) -> Vec<Algorithm> {
+    let mut algorithms = Vec::new();
+    for target in targets {
+        if !algorithms.contains(&target.algorithm) {
+            algorithms.push(target.algorithm);
+        }
+    }
+    algorithms
+}
+
+/// permute `targets` before the engine groups them. the same seed always
+/// gives the same order
+fn shuffle_target_order(targets: &mut [Target], seed: u64) {
+    targets.shuffle(&mut StdRng::seed_from_u64(seed));
+}
+
+/// convert blitzhash digests between the canonical little-endian layout and
+/// `endian` (the same swap in both directions). other algorithms are untouched
+fn convert_blitz_endian(targets: &mut [Target], endian: Endian) {
+    if endian == Endian::Little {
+        return;
+    }
+    
+    let convert = |hash: &mut String| {
+        // malformed hex is left for validate_targets to report
+        if let Ok(bytes) = hex::decode(&*hash) {
+            *hash = hex::encode(blitzhash::reorder_lanes(&bytes, endian));
+        }
+    };
+    
+    for target in targets {
+        if target.algorithm == Algorithm::BlitzHash {
+            convert(&mut target.hash);
+        }
+        if target.verify_algo == Some(Algorithm::BlitzHash) {
+            if let Some(hash) = target.verify_hash.as_mut() {
+                convert(hash);
+            }
+        }
+    }
+}
+
+/// candidate length past which no target's hash changes - `Some` only when
+/// every target truncates its input (see `hasher::effective_max_len`)
+fn truncation_len(targets: &[Target]) -> Option<usize> {
+    targets.iter()
+        .map(|t| hasher::effective_max_len(t.algorithm))
+        .try_fold(0, |longest, len| len.map(|len| longest.max(len)))
+}
+
+/// sort each target's check (`Target::checked`, or a failed parse) into
+/// the targets to attack and the errored ones, before a long run. errors
+/// on the first bad target unless `skip_invalid`, which sets them aside
+/// with a warning
+fn validate_targets(
+    checked: Vec<Result<Target, TargetError>>,
+    skip_invalid: bool,
+    ui: &TerminalUI,
+) -> Result<(Vec<Target>, Vec<TargetError>)> {
+    let mut valid = Vec::with_capacity(checked.len());
+    let mut errored = Vec::new();
+    
+    for check in checked {
+        match check {
+            Ok(target) => valid.push(target),
+            Err(error) if skip_invalid => {
+                ui_println!(ui, "⚠️  Skipping target {}", error);
+                errored.push(error);
+            }
+            Err(error) => {
+                anyhow::bail!("invalid target {} (use --skip-invalid to ignore)", error);
+            }
+        }
+    }
+    
+    Ok((valid, errored))
+}
+
+/// memory budget for concurrent kdf hashes when there's no --max-memory
+/// and the os doesn't report what's available
+const DEFAULT_KDF_MEMORY_BUDGET: u64 = 1024 * 1024 * 1024;
+
+/// memory one hash of the costliest memory-hard target (scrypt, argon2)
+/// allocates, read from its phc parameters
+fn kdf_memory_per_hash(targets: &[Target]) -> Option<u64> {
+    targets.iter()
+        .filter(|t| matches!(t.algorithm, Algorithm::Scrypt | Algorithm::Argon2))
+        .filter_map(|t| hasher::phc_memory_bytes(t.algorithm, &t.hash))
+        .max()
+}
+
+/// most workers whose concurrent hashes fit in `budget` - always at least one
+fn kdf_worker_cap(per_hash: u64, budget: u64) -> usize {
+    match per_hash {
+        0 => usize::MAX,
+        bytes => (budget / bytes).clamp(1, usize::MAX as u64) as usize,
+    }
+}
+
+/// memory the os says is available right now, if it says
+fn available_memory() -> Option<u64> {
+    let mut system = sysinfo::System::new();
+    system.refresh_memory();
+    Some(system.available_memory()).filter(|&bytes| bytes > 0)
+}
+
+/// returns the last run's result, which decides the exit code
+pub fn run_cracking(mut args: RunArgs) -> Result<CrackingResult> {
+    // resolve --like or the preset, then a saved config, before constructing the generator
+    if args.like.as_deref() == Some("") {
+        return Err(usage_error("--like needs a non-empty sample password"));
+    }
+    args.apply_like()?;
+    args.apply_preset();
+    let saved_config = match &args.config {
+        Some(path) => {
+            let config = RunConfig::load(path)?;
+            args.apply_config(&config)?;
+            Some(config)
+        }
+        None => None,
+    };
+    
+    let stages = if args.strategy_pipeline.is_empty() {
+        let strategy = args.strategy
+            .or(args.generator_cmd.as_ref().map(|_| Strategy::Command))
+            .ok_or_else(|| usage_error("--strategy, --preset or --generator-cmd required"))?;
+        vec![strategy]
     } else {
-        None
+        args.strategy_pipeline.clone()
+    };
+    let min_len = args.min_len.unwrap_or(1);
+    let max_len = args.max_len.unwrap_or(8);
+    let batch_sizes = args.batch_size.clone().unwrap_or_default();
+    let batch_size = batch_sizes.default.unwrap_or(4096);
+    let skip = args.skip.unwrap_or(0);
+    let repeat = args.repeat.unwrap_or(if args.ci_width.is_some() { DEFAULT_CI_MAX_RUNS } else { 1 });
+    
+    let RunArgs {
+        targets: targets_path,
+        inline_targets,
+        wordlist: wordlists,
+        no_trim,
+        max_word_len,
+        common_rules,
+        sort_wordlist,
+        weighted,
+        dictionary_cache,
+        wordlist_strategy,
+        follow_wordlist,
+        follow_idle,
+        generator_cmd,
+        sample_keyspace,
+        sample_seed,
+        explain_keyspace,
+        mask,
+        mask_len,
+        template,
+        charset,
+        limit,
+        workers,
+        batch_timeout,
+        max_time,
+        target_timeout,
+        stop_after_count,
+        stop_after_percent,
+        max_memory,
+        ci_width,
+        shuffle_targets,
+        seed,
+        log,
+        results: results_path,
+        potfile,
+        sqlite,
+        report_out,
+        json,
+        progress_to_stderr,
+        progress_json,
+        summary_only,
+        mask_output,
+        blitzhash_endian,
+        no_salt_fallback,
+        hash_algo,
+        hash_prefixes,
+        hash_salt_separator,
+        output_unfound,
+        skip_invalid,
+        i_understand,
+        pepper,
+        case_fold,
+        save_config,
+        pin_threads,
+        adaptive_workers,
+        adaptive_hints,
+        duplicate_target_collapse,
+        hash_once,
+        track_near_misses,
+        tui,
+        ..
+    } = args;
+    
+    if repeat == 0 {
+        return Err(usage_error("--repeat must be at least 1"));
+    }
+    if let Some(width) = ci_width {
+        if !(width > 0.0 && width.is_finite()) {
+            return Err(usage_error("--ci-width must be a positive percentage"));
+        }
+        if repeat < MIN_CI_RUNS {
+            return Err(usage_error(format!("--ci-width needs --repeat of at least {}", MIN_CI_RUNS)));
+        }
+    }
+    let max_time = match max_time {
+        Some(secs) if !(secs > 0.0 && secs.is_finite()) => {
+            return Err(usage_error("--max-time must be a positive number of seconds"));
+        }
+        secs => secs.map(std::time::Duration::from_secs_f64),
+    };
+    let batch_timeout = match batch_timeout {
+        Some(secs) if !(secs > 0.0 && secs.is_finite()) => {
+            return Err(usage_error("--batch-timeout must be a positive number of seconds"));
+        }
+        secs => secs.map(std::time::Duration::from_secs_f64),
+    };
+    if follow_wordlist && !(follow_idle > 0.0 && follow_idle.is_finite()) {
+        return Err(usage_error("--follow-idle must be a positive number of seconds"));
+    }
+    let target_timeout = match target_timeout {
+        Some(secs) if !(secs > 0.0 && secs.is_finite()) => {
+            return Err(usage_error("--target-timeout must be a positive number of seconds"));
+        }
+        secs => secs.map(std::time::Duration::from_secs_f64),
     };
     
-    // run for each repeat
-    for run in 1..=repeat {
-        if repeat > 1 {
-            println!("\n🔄 Run {}/{}", run, repeat);
-        }
-        
-        // create engine
-        let mut engine = Engine::new(
-            targets.clone(),
-            generator,
-            workers_count,
-            batch_size,
-        );
-        
-        // run with ui callback
-        ui.start_display(&targets);
-        
-        let result = engine.run(|stats| {
-            ui.update(stats);
-        })?;
-        
-        ui.stop_display();
-        
-        // print results
-        ui.print_results(&result);
+    let stream = if json || progress_to_stderr { UiStream::Stderr } else { UiStream::Stdout };
+    let mut ui = TerminalUI::new()
+        .with_stream(stream)
+        .with_summary_only(summary_only)
+        .with_masked_passwords(mask_output);
+    
+    // load targets (json or .bft, detected from the file contents). with
+    // --skip-invalid a json entry that doesn't parse is set aside on its own
+    // the file is read once: the bytes parsed are the bytes the report hashes
+    let loaded = match &targets_path {
+        Some(path) => fs::read(path)
+            .with_context(|| format!("failed to read targets file {}", path.display()))?,
+        None => Vec::new(),
+    };
+    let hash_list = |path: &PathBuf| std::str::from_utf8(&loaded)
+        .with_context(|| format!("failed to read hash list {}", path.display()));
+    let mut unparsed = Vec::new();
+    let mut targets = match (&targets_path, hash_algo) {
+        (Some(path), Some(algorithm)) => targets_file::parse_hash_list(hash_list(path)?, algorithm, hash_salt_separator)
+            .with_context(|| format!("in hash list {}", path.display()))?,
+        (Some(path), None) if hash_prefixes => targets_file::parse_prefixed_list(hash_list(path)?)
+            .with_context(|| format!("in hash list {}", path.display()))?,
+        (Some(_), None) if skip_invalid => targets_file::parse_targets_each(&loaded)?
+            .into_iter()
+            .filter_map(|entry| entry.map_err(|e| unparsed.push(e)).ok())
+            .collect(),
+        (Some(_), None) => targets_file::parse_targets(&loaded)?,
+        (None, _) => inline_targets,
+    };
+    // inline targets are hashed as the json file they'd have come from
+    let targets_sha256 = match (report_out.is_some() || json, &targets_path) {
+        (false, _) => None,
+        (true, Some(_)) => Some(run_report::sha256_bytes(&loaded)),
+        (true, None) => Some(run_report::sha256_bytes(&serde_json::to_vec(&targets)?)),
+    };
+    let targets_source = targets_path.unwrap_or_else(|| PathBuf::from("(inline)"));
+    convert_blitz_endian(&mut targets, blitzhash_endian);
+    if no_salt_fallback {
+        for target in targets.iter_mut().filter(|t| !t.algorithm.is_kdf()) {
+            target.salted.get_or_insert(true);
+        }
+    }
+    let checked = unparsed.into_iter().map(Err)
+        .chain(targets.into_iter().map(Target::checked))
+        .collect();
+    let (targets, errored) = validate_targets(checked, skip_invalid, &ui)?;
+    
+    if targets.is_empty() {
+        anyhow::bail!("no targets found in file");
+    }
+    
+    let pepper = resolve_pepper(pepper);
+    let known = match &potfile {
+        Some(path) => results::known_matches(&results::load_entries(path)?, &targets, pepper.as_deref().unwrap_or_default().as_bytes()),
+        None => Vec::new(),
+    };
+    
+    // --stop-after-*: cracked targets that end the run early
+    let stop_after = match (stop_after_count, stop_after_percent) {
+        (Some(0), _) => return Err(usage_error("--stop-after-count must be at least 1")),
+        (_, Some(p)) if !(p > 0.0 && p <= 100.0) => {
+            return Err(usage_error("--stop-after-percent must be above 0 and at most 100"));
+        }
+        (Some(count), _) => Some(count),
+        (_, Some(p)) => Some(((p / 100.0 * targets.len() as f64).ceil() as usize).max(1)),
+        (None, None) => None,
+    };
+    
+    let shuffle_seed = shuffle_targets.then(|| seed.unwrap_or_else(rand::random));
+    // one sample for every run, so --repeat times the same guesses
+    let sample = match sample_keyspace {
+        Some(0) => return Err(usage_error("--sample-keyspace must be at least 1")),
+        Some(count) => Some((count, sample_seed.unwrap_or_else(rand::random))),
+        None => None,
+    };
+    
+    let algorithms = target_algorithms(&targets);
+    match track_near_misses {
+        Some(0) => return Err(usage_error("--track-near-misses must keep at least 1 miss")),
+        Some(_) if !algorithms.contains(&Algorithm::BlitzHash) => {
+            return Err(usage_error("--track-near-misses only works on blitzhash targets"));
+        }
+        _ => {}
+    }
+    if let Some(saved) = saved_config.filter(|c| !c.algorithms.is_empty() && c.algorithms != algorithms) {
+        let names = |algos: &[Algorithm]| algos.iter().map(|a| a.to_string()).collect::<Vec<_>>().join(",");
+        ui_println!(ui, "⚠️  config was saved for {} targets, these are {}", names(&saved.algorithms), names(&algorithms));
+    }
+    
+    // the banner alone is easy to scroll past; real hashes need an explicit yes
+    let acknowledged = i_understand || std::env::var("BLITZFORGE_ACK").is_ok_and(|v| v == "1");
+    if !acknowledged && !targets.iter().all(tools::is_demo_target) {
+        ui.print_warning();
+        return Err(usage_error(
+            "these targets weren't made by generate-targets. blitzforge is for education and \
+            authorized testing only: pass --i-understand (or set BLITZFORGE_ACK=1) to confirm \
+            you're allowed to attack these hashes"));
+    }
+    
+    let mut options = GeneratorOptions {
+        strategy: stages[0],
+        // once, so a skipped list is only warned about once
+        wordlists: expand_wordlists(&wordlists)?,
+        no_trim,
+        max_word_len,
+        common_rules,
+        mask,
+        mask_len,
+        charset,
+        min_len,
+        max_len,
+        skip,
+        limit,
+        max_candidate_len: truncation_len(&targets),
+        generator_cmd,
+        sort_wordlist: if weighted { WordlistSort::Weight } else { sort_wordlist },
+        wordlist_strategy,
+        follow_wordlist: follow_wordlist.then(|| std::time::Duration::from_secs_f64(follow_idle)),
+        max_memory,
+        wordlist_cache: None,
+        template,
+        sample,
+    };
+    
+    // every run and repeat gets the same in-memory wordlist
+    if dictionary_cache && stages.contains(&Strategy::Dictionary) {
+        options.wordlist_cache = Some(dictionary_in_memory(&options)?);
+    }
+    
+    if let Some(len) = options.max_candidate_len {
+        let longer = stages.iter().any(|strategy| match strategy {
+            Strategy::Brute => options.template.as_ref().map_or(options.max_len, String::len) > len,
+            Strategy::Mask => options.mask.as_deref()
+                .and_then(|m| MaskGenerator::new(m).ok())
+                .is_some_and(|m| m.positions() > len),
+            Strategy::Dictionary | Strategy::Hybrid | Strategy::Command => false,
+        });
+        if longer {
+            ui_println!(ui, "✂️  every target truncates input at {} bytes: longer candidates are not generated", len);
+        }
+    }
+    
+    // build the first run's generators up front: this checks every stage's
+    // options before anything runs, and gives the keyspace estimate
+    let mut first_generators = stages.iter()
+        .map(|&strategy| create_generator(&GeneratorOptions { strategy, ..options.clone() }))
+        .collect::<Result<Vec<_>>>()?;
+    let keyspace = first_generators.iter()
+        .try_fold(0u128, |total, g| g.estimated_size().map(|size| total.saturating_add(size)));
+    // the whole keyspace a --sample-keyspace sample is drawn from
+    let sampled_from = match sample {
+        Some(_) => stages.iter()
+            .map(|&strategy| create_generator(&GeneratorOptions { strategy, sample: None, ..options.clone() }))
+            .collect::<Result<Vec<_>>>()?
+            .iter()
+            .try_fold(0u128, |total, g| g.estimated_size().map(|size| total.saturating_add(size))),
+        None => None,
+    };
+    
+    let mut workers_count = workers.unwrap_or_else(num_cpus);
+    
+    // scrypt and argon2 allocate their full memory cost per concurrent hash;
+    // unless the user chose a worker count, keep the total within budget
+    if let Some(per_hash) = kdf_memory_per_hash(&targets) {
+        let budget = max_memory
+            .or_else(available_memory)
+            .unwrap_or(DEFAULT_KDF_MEMORY_BUDGET);
+        let cap = kdf_worker_cap(per_hash, budget);
+        
+        if cap < workers_count {
+            match workers {
+                None => {
+                    ui_println!(ui, "⚠️  memory-hard targets need {} per hash: limiting workers {} → {} to stay under {}",
+                        format_bytes(per_hash), workers_count, cap, format_bytes(budget));
+                    workers_count = cap;
+                }
+                Some(_) => ui_println!(ui, "⚠️  {} workers × {} per hash may exceed {} of memory",
+                    workers_count, format_bytes(per_hash), format_bytes(budget)),
+            }
+        }
+    }
+    
+    let names = stage_names(&stages);
+    let config = RunConfig {
+        strategy: names.join(","),
+        algorithms,
+        // as given, so a replay picks up lists added to a directory since
+        wordlists: wordlists.iter().map(|w| w.display().to_string()).collect(),
+        no_trim: options.no_trim,
+        mask: options.mask.clone(),
+        charset: options.charset.clone(),
+        template: options.template.clone(),
+        min_len,
+        max_len,
+        skip,
+        limit,
+        generator_cmd: options.generator_cmd.clone(),
+        workers: workers_count,
+        batch_size,
+        algorithm_batch_sizes: batch_sizes.per_algorithm.clone(),
+        repeat,
+    };
+    
+    // print warning banner
+    ui.print_warning();
+    
+    // print configuration
+    ui_println!(ui, "\n📋 Configuration:");
+    ui_println!(ui, "   Targets:    {}", targets.len());
+    if let Some(path) = &potfile {
+        ui_println!(ui, "   Potfile:    {} already cracked in {}", known.len(), path.display());
+    }
+    ui_println!(ui, "   Strategy:   {}", names.join(" → "));
+    ui_println!(ui, "   Workers:    {}{}", workers_count, if pin_threads { " (pinned)" } else { "" });
+    ui_println!(ui, "   Batch size: {}", batch_size);
+    for (algorithm, size) in &batch_sizes.per_algorithm {
+        ui_println!(ui, "               {} for {}", size, algorithm);
+    }
+    match ci_width {
+        Some(width) => ui_println!(ui, "   Repeats:    until the 95% CI is within {}% of the mean, at most {}", width, repeat),
+        None => ui_println!(ui, "   Repeats:    {}", repeat),
+    }
+    if let Some(seed) = shuffle_seed {
+        ui_println!(ui, "   Shuffle:    seed {} (pass --seed {} to replay these orders)", seed, seed);
+    }
+    if let Some(pepper) = &pepper {
+        ui_println!(ui, "   Pepper:     {} bytes appended to every candidate", pepper.len());
+    }
+    if case_fold != CaseFold::None {
+        ui_println!(ui, "   Case fold:  {}case before hashing", case_fold);
+    }
+    if common_rules {
+        ui_println!(ui, "   Rules:      common variants of every wordlist word");
+    }
+    if adaptive_hints {
+        ui_println!(ui, "   Hints:      variants of each cracked password go first");
+    }
+    
+    if let Some(est) = keyspace {
+        ui_println!(ui, "   Keyspace:   {}", format_number(est));
+    }
+    if explain_keyspace {
+        for &strategy in &stages {
+            for line in explain_keyspace_of(&options, strategy)? {
+                ui_println!(ui, "{}", line);
+            }
+        }
+    }
+    if let (Some((_, seed)), Some(full)) = (sample, sampled_from) {
+        ui_println!(ui, "   Sample:     drawn at random from {} (pass --sample-seed {} to draw it again)", format_number(full), seed);
+    }
+    
+    // saved before the run, so even an interrupted run can be replayed
+    if let Some(path) = &save_config {
+        config.write(path)?;
+        ui_println!(ui, "🧾 Config saved → {}", path.display());
+    }
+    
+    ui_println!(ui, "\n⚡ Starting cracking engine...\n");
+    
+    // create benchmark logger if needed
+    let mut logger = if let Some(log_path) = log {
+        Some(BenchmarkLogger::new(log_path)?)
+    } else {
+        None
+    };
+    
+    // cracked credentials are stored as each batch finds them, so a crash
+    // or ctrl-c part way loses nothing
+    let mut sink = match &sqlite {
+        Some(path) => Some(results::SqliteSink::open(path)?),
+        None => None,
+    };
+    let mut sink_error = None;
+    let mut sink_inserted = 0;
+    
+    // written once up front, so a bad path fails before the attack starts
+    let mut progress_file = match &progress_json {
+        Some(path) => {
+            let mut file = ProgressFile::new(path);
+            file.write(&Statistics::new(targets.len()))?;
+            Some(file)
+        }
+        None => None,
+    };
+    
+    let mut on_batch = |stats: &Statistics| {
+        if let Some(file) = progress_file.as_mut() {
+            file.update(stats);
+        }
+        let Some(sink) = sink.as_mut() else {
+            return;
+        };
+        match sink.record(&stats.new_matches) {
+            Ok(n) => sink_inserted += n,
+            Err(e) => {
+                sink_error.get_or_insert(e);
+            }
+        }
+    };
+    
+    // ctrl-c from here on stops the attack after its current batch
+    let stop = interrupt::stop_flag();
+    
+    // run for each repeat
+    let mut run_results = Vec::with_capacity(repeat);
+    for run in 1..=repeat {
+        // the first run and stage always start, so there's a result to report
+        if run > 1 && interrupt::stop_requested() {
+            break;
+        }
+        if repeat > 1 {
+            ui_println!(ui, "\n🔄 Run {}/{}", run, repeat);
+        }
+        let run_start = std::time::Instant::now();
+        
+        // first run uses the generators built above, later runs start fresh
+        let mut prebuilt = std::mem::take(&mut first_generators).into_iter();
+        
+        // each stage attacks whatever the earlier stages left uncracked
+        let mut remaining = targets.clone();
+        // each run gets its own order, still reproducible from the one seed
+        let run_seed = shuffle_seed.map(|seed| seed.wrapping_add(run as u64 - 1));
+        if let Some(seed) = run_seed {
+            shuffle_target_order(&mut remaining, seed);
+        }
+        let mut given_up = Vec::new();
+        let mut stage_results = Vec::with_capacity(stages.len());
+        
+        for (i, &strategy) in stages.iter().enumerate() {
+            if remaining.is_empty() || (i > 0 && interrupt::stop_requested()) {
+                break;
+            }
+            if stages.len() > 1 {
+                ui_println!(ui, "\n🧩 Stage {}/{}: {} ({} targets left)",
+                    i + 1, stages.len(), names[i], remaining.len());
+            }
+            
+            let generator = match prebuilt.next() {
+                Some(g) => g,
+                None => create_generator(&GeneratorOptions { strategy, ..options.clone() })?,
+            };
+            let generator: Box<dyn Generator> = match adaptive_hints {
+                true => Box::new(HintGenerator::new(generator)),
+                false => generator,
+            };
+            // --max-time covers the whole run, so later stages get what's left
+            let generator: Box<dyn Generator> = match max_time {
+                Some(limit) => Box::new(DeadlineGenerator::new(generator, limit.saturating_sub(run_start.elapsed()))),
+                None => generator,
+            };
+            
+            let stage_keyspace = generator.estimated_size();
+            
+            // create engine
+            let mut engine = Engine::new(
+                remaining.clone(),
+                generator,
+                workers_count,
+                batch_size,
+            ).with_stop_flag(stop.clone());
+            
+            if let Some(pepper) = &pepper {
+                engine = engine.with_pepper(pepper.as_bytes().to_vec());
+            }
+            
+            if let Some(fold) = case_fold.transform() {
+                engine = engine.with_transform(fold);
+            }
+            
+            if !batch_sizes.per_algorithm.is_empty() {
+                engine = engine.with_algorithm_batch_sizes(batch_sizes.per_algorithm.clone());
+            }
+            
+            if let Some(timeout) = batch_timeout {
+                engine = engine.with_batch_timeout(timeout);
+            }
+            
+            if pin_threads {
+                engine = engine.with_pinned_threads();
+            }
+            
+            if adaptive_workers {
+                engine = engine.with_adaptive_workers();
+            }
+            
+            if hash_once {
+                engine = engine.with_hash_once();
+            }
+            
+            // later stages only see what the first left, so it takes them all
+            if i == 0 && !known.is_empty() {
+                engine = engine.with_known_matches(known.clone());
+            }
+            
+            // each stage gives every group a fresh budget
+            if let Some(timeout) = target_timeout {
+                engine = engine.with_target_timeout(timeout);
+            }
+            
+            // the threshold counts targets cracked by earlier stages too
+            if let Some(count) = stop_after {
+                let cracked = targets.len() - remaining.len() - given_up.len();
+                engine = engine.with_success_threshold(count.saturating_sub(cracked));
+            }
+            
+            if let Some(cap) = track_near_misses {
+                engine = engine.with_near_misses(cap);
+            }
+            
+            if duplicate_target_collapse {
+                engine = engine.with_duplicate_collapse();
+                if i == 0 && run == 1 {
+                    ui_println!(ui, "🧬 Collapsed {} duplicate targets", engine.duplicates_collapsed());
+                }
+            }
+            
+            // run with ui callback
+            let result = if tui {
+                let mut dashboard = Dashboard::start(&remaining, stream)?.with_masked_passwords(mask_output);
+                let result = engine.run(|stats| {
+                    on_batch(stats);
+                    dashboard.update(stats);
+                });
+                drop(dashboard);
+                result?
+            } else {
+                ui.start_display(&remaining, stage_keyspace);
+                
+                let result = engine.run(|stats| {
+                    on_batch(stats);
+                    ui.update(stats);
+                })?;
+                
+                ui.stop_display();
+                result
+            };
+            
+            if stages.len() > 1 {
+                for m in &result.matches {
+                    ui_println!(ui, "   🔓 {} cracked by stage {} ({})", m.target_id, i + 1, names[i]);
+                }
+            }
+            
+            let cracked: std::collections::HashSet<&str> = result.matches.iter()
+                .map(|m| m.target_id.as_str())
+                .collect();
+            remaining.retain(|t| !cracked.contains(t.id.as_str()));
+            // targets given up on aren't passed to later stages either
+            let (retired, kept) = remaining.into_iter().partition(|t| result.given_up.contains(&t.id));
+            given_up.extend::<Vec<Target>>(retired);
+            remaining = kept;
+            // a time limit or success threshold covers the whole run
+            let run_over = matches!(result.termination, TerminationReason::TimeLimit | TerminationReason::ThresholdReached);
+            stage_results.push(result);
+            if run_over {
+                break;
+            }
+        }
+        
+        let result = combine_stage_results(stage_results, targets.len());
+        
+        // print results
+        let unfound: Vec<&Target> = remaining.iter().collect();
+        ui.print_results(&result, &unfound);
+        if !errored.is_empty() {
+            ui.print_errored(&errored);
+        }
+        if pin_threads && !result.threads_pinned {
+            eprintln!("⚠️  cpu affinity isn't available on this platform; ran unpinned");
+        }
+        
+        // what random guessing buys against the whole keyspace
+        if let Some(full) = sampled_from.filter(|&full| full > 0) {
+            let (found, total) = (result.matches.len(), result.statistics.targets_total);
+            let guesses = result.statistics.guesses_tried;
+            ui_println!(ui, "🎲 Hit rate: {}/{} targets ({:.1}%) from {} random guesses, {} of the keyspace",
+                found, total, 100.0 * found as f64 / total.max(1) as f64,
+                format_number(guesses), format_share(guesses as f64 / full as f64));
+        }
+        
+        if let Some(cap) = track_near_misses {
+            ui.print_near_misses(&result.near_misses[..cap.min(result.near_misses.len())]);
+        }
+        
+        if result.final_batch_size < batch_size {
+            ui_println!(ui, "⏱️  Batch size adapted to slow hashing: {} → {}", batch_size, result.final_batch_size);
+        }
+        
+        if let Some(active) = result.adaptive_workers {
+            ui_println!(ui, "🌡️  Adaptive workers settled on {} of {}", active, workers_count);
+        }
+        
+        // report what survived the attack, given up on or not
+        let uncracked: Vec<&Target> = remaining.iter().chain(&given_up).collect();
+        match &output_unfound {
+            Some(Some(path)) => {
+                let lines: Vec<String> = uncracked.iter().map(|t| ui::unfound_line(t)).collect();
+                results::write_atomic(path, lines.join("\n") + "\n")?;
+                ui_println!(ui, "🔒 {} uncracked targets → {}", uncracked.len(), path.display());
+            }
+            Some(None) => ui.print_unfound(&uncracked),
+            None => {}
+        }
+        
+        if let Some(path) = &results_path {
+            let entries = results::entries_from_matches(&result.matches, &targets);
+            results::write_atomic(path, serde_json::to_string_pretty(&entries)?)?;
+            ui_println!(ui, "💾 {} cracked → {}", entries.len(), path.display());
+        }
+        
+        // log to csv if enabled
+        if let Some(ref mut log) = logger {
+            log.log_result(&result, &targets, &names.join(","), workers_count, keyspace, run_seed)?;
+        }
+        
+        run_results.push(result);
+        
+        if let Some(width) = ci_width {
+            let throughputs: Vec<f64> = run_results.iter().map(|r| r.statistics.hashes_per_second).collect();
+            if ci_converged(&throughputs, width) {
+                break;
+            }
+        }
+    }
+    
+    if repeat > 1 {
+        print_repeat_summary(&ui, &summarize_repeats(&run_results));
+    }
+    if let Some(width) = ci_width {
+        let throughputs: Vec<f64> = run_results.iter().map(|r| r.statistics.hashes_per_second).collect();
+        match ConfidenceInterval::of(&throughputs) {
+            Some(ci) if ci_converged(&throughputs, width) => {
+                ui_println!(ui, "   Stable after:     {} runs (CI {:.1}% of the mean, target {}%)",
+                    throughputs.len(), ci.relative_width(), width);
+            }
+            Some(ci) => {
+                ui_println!(ui, "   Stable after:     ⚠️  CI still {:.1}% of the mean after {} runs (target {}%)",
+                    ci.relative_width(), throughputs.len(), width);
+            }
+            None => {}
+        }
+    }
+    
+    // the last refresh may have been skipped; leave the final figures
+    if let (Some(file), Some(last)) = (progress_file.as_mut(), run_results.last()) {
+        file.write(&last.statistics)?;
+    }
+    
+    if let Some(e) = sink_error {
+        return Err(e.context("failed to store cracked credentials"));
+    }
+    if let Some(path) = &sqlite {
+        ui_println!(ui, "🗄️  {} new cracked → {}", sink_inserted, path.display());
+    }
+    
+    if let (Some(sha), Some(last)) = (targets_sha256, run_results.last()) {
+        let report = RunReport::new(&targets_source, sha, &targets, config, last)
+            .with_errored(errored);
+        if let Some(path) = &report_out {
+            report.write(path)?;
+            ui_println!(ui, "📄 Run report → {}", path.display());
+        }
+        if json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+    }
+    
+    let last = run_results.pop().expect("repeat is at least 1");
+    match last.termination {
+        TerminationReason::Interrupted => ui_println!(ui, "\n⛔ Stopped early"),
+        _ => ui_println!(ui, "\n✅ All runs completed!"),
+    }
+    
+    Ok(last)
+}
+
+/// cli names of the stages, e.g. ["dictionary", "mask"]
+fn stage_names(stages: &[Strategy]) -> Vec<String> {
+    stages.iter()
+        .map(|s| s.to_possible_value().unwrap().get_name().to_string())
+        .collect()
+}
+
+/// fold a pipeline's per-stage results into one result for the whole run.
+/// guess counts and find times carry on from the earlier stages, so they
+/// read as if a single attack had run
+fn combine_stage_results(mut stage_results: Vec<CrackingResult>, targets_total: usize) -> CrackingResult {
+    if stage_results.len() == 1 {
+        return stage_results.pop().unwrap();
+    }
+    
+    let mut stage_results = stage_results.into_iter();
+    let mut combined = stage_results.next().expect("at least one stage runs");
+    combined.statistics.targets_total = targets_total;
+    
+    for mut stage in stage_results {
+        for m in &mut stage.matches {
+            m.guesses_tried += combined.statistics.guesses_tried;
+            m.time_seconds += combined.total_time;
+        }
+        combined.matches.append(&mut stage.matches);
+        combined.statistics.guesses_tried += stage.statistics.guesses_tried;
+        combined.statistics.hashes_computed += stage.statistics.hashes_computed;
+        combined.statistics.progress = stage.statistics.progress;
+        combined.total_time += stage.total_time;
+        combined.final_batch_size = stage.final_batch_size;
+        combined.threads_pinned &= stage.threads_pinned;
+        combined.termination = stage.termination;
+        combined.adaptive_workers = stage.adaptive_workers;
+        combined.given_up.append(&mut stage.given_up);
+        combined.near_misses.append(&mut stage.near_misses);
+    }
+    
+    // stable, so each stage's ties keep their order
+    combined.near_misses.sort_by_key(|m| std::cmp::Reverse(m.shared_bytes));
+    
+    combined.statistics.targets_found = combined.matches.len();
+    if combined.total_time > 0.0 {
+        combined.statistics.hashes_per_second = combined.statistics.hashes_computed as f64 / combined.total_time;
+    }
+    combined
+}
+
+/// cross-run summary for `--repeat`, so quick benchmarks don't need `report`
+#[derive(Debug)]
+struct RepeatSummary {
+    runs: usize,
+    median_hashes_per_s: f64,
+    peak_hashes_per_s: f64,
+    min_hashes_per_s: f64,
+    median_time: f64,
+    /// 95% confidence interval of the mean H/s
+    confidence: Option<ConfidenceInterval>,
+    /// fewest and most targets cracked by any single run
+    found_range: (usize, usize),
+    targets_total: usize,
+}
+
+fn summarize_repeats(results: &[CrackingResult]) -> RepeatSummary {
+    let throughputs: Vec<f64> = results.iter().map(|r| r.statistics.hashes_per_second).collect();
+    let times: Vec<f64> = results.iter().map(|r| r.total_time).collect();
+    let found = results.iter().map(|r| r.matches.len());
+    
+    RepeatSummary {
+        runs: results.len(),
+        median_hashes_per_s: median(&throughputs),
+        peak_hashes_per_s: throughputs.iter().copied().fold(0.0_f64, f64::max),
+        min_hashes_per_s: throughputs.iter().copied().fold(f64::INFINITY, f64::min),
+        median_time: median(&times),
+        confidence: ConfidenceInterval::of(&throughputs),
+        found_range: (found.clone().min().unwrap_or(0), found.max().unwrap_or(0)),
+        targets_total: results.first().map_or(0, |r| r.statistics.targets_total),
+    }
+}
+
+fn print_repeat_summary(ui: &TerminalUI, summary: &RepeatSummary) {
+    ui_println!(ui, "\n📈 Summary of {} runs:", summary.runs);
+    ui_println!(ui, "   Median H/s:       {}", format_hashes_per_sec(summary.median_hashes_per_s));
+    ui_println!(ui, "   Peak H/s:         {}", format_hashes_per_sec(summary.peak_hashes_per_s));
+    ui_println!(ui, "   Min H/s:          {}", format_hashes_per_sec(summary.min_hashes_per_s));
+    ui_println!(ui, "   Median time:      {:.2}s", summary.median_time);
+    if let Some(ci) = &summary.confidence {
+        ui_println!(ui, "   Mean H/s:         {} ± {} (95% CI)",
+            format_hashes_per_sec(ci.mean), format_hashes_per_sec(ci.half_width));
+    }
+    
+    match summary.found_range {
+        (min, max) if min == max => {
+            ui_println!(ui, "   Consistency:      every run cracked {}/{}", max, summary.targets_total);
+        }
+        (min, max) => {
+            ui_println!(ui, "   Consistency:      ⚠️  runs cracked between {} and {} of {}", min, max, summary.targets_total);
+        }
+    }
+}
+
+/// runs `--ci-width` tries when --repeat doesn't say
+const DEFAULT_CI_MAX_RUNS: usize = 30;
+
+/// fewer runs than this never count as stable - two close numbers can
+/// agree by luck
+const MIN_CI_RUNS: usize = 3;
+
+/// 95% confidence interval for the mean of a few measurements, using
+/// student's t since benchmark runs are far too few for the normal
+#[derive(Debug, Clone, PartialEq)]
+struct ConfidenceInterval {
+    mean: f64,
+    /// the interval is `mean ± half_width`
+    half_width: f64,
+}
+
+impl ConfidenceInterval {
+    /// `None` for fewer than two samples, which have no spread to go on
+    fn of(samples: &[f64]) -> Option<Self> {
+        let n = samples.len();
+        if n < 2 {
+            return None;
+        }
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+        Some(Self {
+            mean,
+            half_width: t_critical_95(n - 1) * (variance / n as f64).sqrt(),
+        })
+    }
+    
+    /// full width of the interval as a percentage of the mean
+    fn relative_width(&self) -> f64 {
+        if self.mean > 0.0 {
+            200.0 * self.half_width / self.mean
+        } else {
+            f64::INFINITY
+        }
+    }
+}
+
+/// two-sided 95% critical value of student's t for `df` degrees of freedom
+fn t_critical_95(df: usize) -> f64 {
+    const TABLE: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+        2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+        2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+    ];
+    match df {
+        0 => f64::INFINITY,
+        1..=30 => TABLE[df - 1],
+        31..=60 => 2.000,
+        61..=120 => 1.980,
+        _ => 1.960,
+    }
+}
+
+/// `--ci-width` stopping rule: enough runs, and an interval narrower than
+/// `width_percent` of the mean
+fn ci_converged(throughputs: &[f64], width_percent: f64) -> bool {
+    throughputs.len() >= MIN_CI_RUNS
+        && ConfidenceInterval::of(throughputs).is_some_and(|ci| ci.relative_width() <= width_percent)
+}
+
+/// options for the `preview` command
+#[derive(Debug, Clone, Args)]
+pub struct PreviewArgs {
+    /// attack strategy
+    #[arg(short, long, value_enum)]
+    pub strategy: Strategy,
+    
+    /// wordlist path(s) (for dictionary mode)
+    #[arg(short, long, value_delimiter = ',')]
+    pub wordlist: Vec<PathBuf>,
+    
+    /// keep surrounding whitespace on wordlist lines (exact bytes)
+    #[arg(long)]
+    pub no_trim: bool,
+    
+    /// mask pattern (for mask mode) e.g. ?l?l?l?d?d
+    #[arg(short, long)]
+    pub mask: Option<String>,
+    
+    /// charset (for brute force mode) e.g. "abc123"
+    #[arg(short, long)]
+    pub charset: Option<String>,
+    
+    /// minimum length (for brute force)
+    #[arg(long, default_value = "1")]
+    pub min_len: usize,
+    
+    /// maximum length (for brute force)
+    #[arg(long, default_value = "8")]
+    pub max_len: usize,
+    
+    /// skip this many candidates from the start of the keyspace
+    #[arg(long, default_value = "0")]
+    pub skip: u64,
+    
+    /// stop after this many candidates
+    #[arg(long)]
+    pub limit: Option<u64>,
+    
+    /// number of candidates to show from each end
+    #[arg(short = 'n', long, default_value = "5")]
+    pub count: usize,
+}
+
+/// options for the `generate-table` command
+#[derive(Debug, Clone, Args)]
+pub struct GenerateTableArgs {
+    /// output file, one `hash<TAB>password` line per candidate
+    #[arg(short, long)]
+    pub out: PathBuf,
+    
+    /// hash algorithm (unsalted digests only)
+    #[arg(short, long)]
+    pub algo: Algorithm,
+    
+    /// stop after this many entries
+    #[arg(long, default_value = "1000000")]
+    pub limit: u64,
+    
+    /// candidate strategy
+    #[arg(short, long, value_enum)]
+    pub strategy: Strategy,
+    
+    /// wordlist path(s) (for dictionary mode)
+    #[arg(short, long, value_delimiter = ',')]
+    pub wordlist: Vec<PathBuf>,
+    
+    /// keep surrounding whitespace on wordlist lines (exact bytes)
+    #[arg(long)]
+    pub no_trim: bool,
+    
+    /// mask pattern (for mask mode) e.g. ?l?l?l?d?d
+    #[arg(short, long)]
+    pub mask: Option<String>,
+    
+    /// charset (for brute force mode) e.g. "abc123"
+    #[arg(short, long)]
+    pub charset: Option<String>,
+    
+    /// minimum length (for brute force)
+    #[arg(long, default_value = "1")]
+    pub min_len: usize,
+    
+    /// maximum length (for brute force)
+    #[arg(long, default_value = "8")]
+    pub max_len: usize,
+}
+
+/// precompute hashes for a keyspace - the storage side of the space-time
+/// tradeoff that `run` makes in the other direction
+pub fn generate_table(args: GenerateTableArgs) -> Result<()> {
+    if args.limit == 0 {
+        return Err(usage_error("--limit must be at least 1"));
+    }
+    if args.algo.is_kdf() {
+        return Err(usage_error(format!(
+            "{} hashes are salted per target, so a precomputed table can't match them", args.algo)));
+    }
+    
+    let options = GeneratorOptions {
+        strategy: args.strategy,
+        wordlists: args.wordlist,
+        no_trim: args.no_trim,
+        mask: args.mask,
+        charset: args.charset,
+        min_len: args.min_len,
+        max_len: args.max_len,
+        limit: Some(args.limit),
+        ..Default::default()
+    };
+    let mut generator = create_generator(&options)?;
+    
+    println!("🗂️  Building {} lookup table ({:?})...", args.algo, args.strategy);
+    let start = std::time::Instant::now();
+    
+    let mut out = std::io::BufWriter::new(File::create(&args.out)?);
+    let entries = tools::write_lookup_table(generator.as_mut(), args.algo, &mut out)?;
+    let size = fs::metadata(&args.out).map(|m| m.len()).unwrap_or(0);
+    
+    println!("✅ {} entries → {} ({}, {:.2}s)",
+        format_number(entries), args.out.display(), format_bytes(size), start.elapsed().as_secs_f64());
+    if entries == args.limit {
+        println!("   stopped at --limit; the keyspace may have more candidates");
+    }
+    
+    Ok(())
+}
+
+/// options for the `compare-algorithms` command
+#[derive(Debug, Clone, Args)]
+pub struct CompareAlgorithmsArgs {
+    /// the known password, hashed once under every algorithm
+    #[arg(short, long)]
+    pub password: String,
+    
+    /// comma-separated algorithms to compare [default: all]
+    #[arg(short, long, value_delimiter = ',')]
+    pub algorithms: Vec<Algorithm>,
+    
+    /// candidate strategy
+    #[arg(short, long, value_enum)]
+    pub strategy: Strategy,
+    
+    /// wordlist path(s) (for dictionary mode)
+    #[arg(short, long, value_delimiter = ',')]
+    pub wordlist: Vec<PathBuf>,
+    
+    /// mask pattern (for mask mode) e.g. ?l?l?l?d?d
+    #[arg(short, long)]
+    pub mask: Option<String>,
+    
+    /// charset (for brute force mode) e.g. "abc123"
+    #[arg(short, long)]
+    pub charset: Option<String>,
+    
+    /// minimum length (for brute force)
+    #[arg(long, default_value = "1")]
+    pub min_len: usize,
+    
+    /// maximum length (for brute force)
+    #[arg(long, default_value = "8")]
+    pub max_len: usize,
+    
+    /// give up on an algorithm after this many seconds
+    #[arg(long, default_value = "10")]
+    pub time_limit: f64,
+    
+    /// number of worker threads (default: cpu count)
+    #[arg(long)]
+    pub workers: Option<usize>,
+}
+
+/// how one algorithm fared against the shared password
+#[derive(Debug, Clone)]
+pub struct AlgorithmTiming {
+    pub algorithm: Algorithm,
+    pub found: bool,
+    pub seconds: f64,
+    pub guesses: u64,
+    pub hashes_per_second: f64,
+}
+
+/// crack `password` hashed under each algorithm in turn with a fresh
+/// generator, so every algorithm searches the same keyspace in the same
+/// order and gets every worker. each run stops at `time_limit`
+pub fn compare_algorithms_runs(
+    password: &str,
+    algorithms: &[Algorithm],
+    options: &GeneratorOptions,
+    workers: usize,
+    time_limit: std::time::Duration,
+) -> Result<Vec<AlgorithmTiming>> {
+    let mut timings = Vec::with_capacity(algorithms.len());
+    
+    for &algorithm in algorithms {
+        let targets = tools::generate_demo_targets(&[password.to_string()], &[algorithm], PasswordEncoding::Utf8)?;
+        let generator = DeadlineGenerator::new(create_generator(options)?, time_limit);
+        
+        // the deadline is only checked between batches, and the batch
+        // timeout can only shrink a batch once one has overrun - so slow
+        // hashes start at a few candidates per worker
+        let batch_size = if algorithm.is_kdf() { workers * 4 } else { 4096 };
+        let engine = Engine::new(targets, Box::new(generator), workers, batch_size)
+            .with_batch_timeout(time_limit / 20);
+        // the one target's match, if any, then the run's totals once it ends
+        let mut stream = engine.matches();
+        let found = stream.next();
+        let result = stream.finish()?;
+        
+        timings.push(AlgorithmTiming {
+            algorithm,
+            found: found.is_some(),
+            seconds: found.as_ref().map_or(result.total_time, |m| m.time_seconds),
+            guesses: found.as_ref().map_or(result.statistics.guesses_tried, |m| m.guesses_tried),
+            hashes_per_second: result.statistics.hashes_per_second,
+        });
+    }
+    
+    Ok(timings)
+}
+
+pub fn compare_algorithms(args: CompareAlgorithmsArgs) -> Result<()> {
+    if !(args.time_limit > 0.0 && args.time_limit.is_finite()) {
+        return Err(usage_error("--time-limit must be a positive number of seconds"));
+    }
+    let algorithms = match args.algorithms.is_empty() {
+        true => Algorithm::ALL.to_vec(),
+        false => args.algorithms,
+    };
+    
+    let options = GeneratorOptions {
+        strategy: args.strategy,
+        wordlists: args.wordlist,
+        mask: args.mask,
+        charset: args.charset,
+        min_len: args.min_len,
+        max_len: args.max_len,
+        ..Default::default()
+    };
+    let workers = args.workers.unwrap_or_else(num_cpus);
+    let time_limit = std::time::Duration::from_secs_f64(args.time_limit);
+    
+    let name = stage_names(&[args.strategy]).remove(0);
+    println!("⚖️  Cracking the same password under {} algorithms ({}, {} workers, {}s limit each)\n",
+        algorithms.len(), name, workers, args.time_limit);
+    
+    let timings = compare_algorithms_runs(&args.password, &algorithms, &options, workers, time_limit)?;
+    print!("{}", comparison_table(&timings));
+    
+    Ok(())
+}
+
+/// the comparison as a text table. algorithms that ran out of time get an
+/// estimate: the password sits at the same keyspace position for all of
+/// them, so a found run's guess count over their rate says how long it'd take
+fn comparison_table(timings: &[AlgorithmTiming]) -> String {
+    let position = timings.iter().filter(|t| t.found).map(|t| t.guesses).max();
+    
+    let mut out = format!("   {:<12} {:<10} {:>10} {:>12} {:>12}\n", "Algorithm", "Result", "Time", "Guesses", "Rate");
+    for t in timings {
+        // plain words: emoji widths would throw the columns off
+        let result = if t.found { "cracked" } else { "time limit" };
+        out.push_str(&format!("   {:<12} {:<10} {:>10} {:>12} {:>12}",
+            t.algorithm.to_string(),
+            result,
+            format!("{:.2}s", t.seconds),
+            format_number(t.guesses),
+            format_hashes_per_sec(t.hashes_per_second)));
+        
+        if let (false, Some(position)) = (t.found, position) {
+            if t.hashes_per_second > 0.0 {
+                out.push_str(&format!("   ~{} to crack", format_seconds(position as f64 / t.hashes_per_second)));
+            }
+        }
+        out.push('\n');
+    }
+    
+    out
+}
+
+/// a duration in the largest unit that keeps it readable
+fn format_seconds(secs: f64) -> String {
+    const UNITS: [(f64, &str); 5] = [
+        (365.25 * 86400.0, "years"),
+        (86400.0, "days"),
+        (3600.0, "hours"),
+        (60.0, "min"),
+        (1.0, "s"),
+    ];
+    
+    UNITS.iter()
+        .find(|(size, _)| secs >= *size)
+        .map(|(size, unit)| format!("{:.1} {}", secs / size, unit))
+        .unwrap_or_else(|| format!("{:.2}s", secs))
+}
+
+/// largest keyspace walked to find the last candidates
+const PREVIEW_SCAN_LIMIT: u64 = 50_000_000;
+
+/// first and last candidates of a generator, plus its size
+#[derive(Debug)]
+pub struct CandidatePreview {
+    pub first: Vec<Vec<u8>>,
+    /// `None` when the keyspace is too large to walk to the end
+    pub last: Option<Vec<Vec<u8>>>,
+    pub total: Option<u128>,
+}
+
+/// collect the first and last `n` candidates without hashing anything.
+/// the tail needs a full pass, so it is skipped above `scan_limit`
+pub fn preview_candidates(generator: &mut dyn Generator, n: usize, scan_limit: u64) -> CandidatePreview {
+    let estimated = generator.estimated_size();
+    let walk = estimated.is_none_or(|size| size <= u128::from(scan_limit));
+    
+    let mut first = Vec::with_capacity(n);
+    let mut last = std::collections::VecDeque::with_capacity(n);
+    let mut seen = 0u64;
+    
+    while let Some(batch) = generator.next_batch(4096) {
+        for candidate in batch {
+            seen += 1;
+            if first.len() < n {
+                first.push(candidate.clone());
+            }
+            if n > 0 {
+                if last.len() == n {
+                    last.pop_front();
+                }
+                last.push_back(candidate);
+            }
+        }
+        
+        if !walk && first.len() >= n {
+            break;
+        }
+    }
+    
+    CandidatePreview {
+        first,
+        last: walk.then(|| last.into_iter().collect()),
+        total: if walk { Some(u128::from(seen)) } else { estimated },
+    }
+}
+
+pub fn preview(args: PreviewArgs) -> Result<()> {
+    let options = GeneratorOptions {
+        strategy: args.strategy,
+        wordlists: args.wordlist,
+        no_trim: args.no_trim,
+        mask: args.mask,
+        charset: args.charset,
+        min_len: args.min_len,
+        max_len: args.max_len,
+        skip: args.skip,
+        limit: args.limit,
+        ..Default::default()
+    };
+    
+    let mut generator = create_generator(&options)?;
+    let preview = preview_candidates(generator.as_mut(), args.count, PREVIEW_SCAN_LIMIT);
+    
+    println!("🔍 Candidate preview ({:?})", args.strategy);
+    
+    match preview.total {
+        Some(total) => println!("   Total:  {} candidates", format_number(total)),
+        None => println!("   Total:  unknown"),
+    }
+    
+    println!("\n   First {}:", preview.first.len());
+    for candidate in &preview.first {
+        println!("      {}", String::from_utf8_lossy(candidate));
+    }
+    
+    match &preview.last {
+        Some(last) => {
+            println!("\n   Last {}:", last.len());
+            for candidate in last {
+                println!("      {}", String::from_utf8_lossy(candidate));
+            }
+        }
+        None => println!("\n   Last: skipped (keyspace larger than {})", format_number(PREVIEW_SCAN_LIMIT)),
+    }
+    
+    Ok(())
+}
+
+/// options for the `mask-of` command
+#[derive(Debug, Clone, Args)]
+pub struct MaskOfArgs {
+    /// sample passwords to describe
+    #[arg(required = true)]
+    pub passwords: Vec<String>,
+}
+
+pub fn mask_of(args: MaskOfArgs) -> Result<()> {
+    println!("🎭 Masks that would crack each password\n");
+    
+    let width = args.passwords.iter().map(|p| p.chars().count()).max().unwrap_or(0);
+    for password in &args.passwords {
+        let mask = tools::mask_of(password);
+        let keyspace = MaskGenerator::new(&mask)?.estimated_size().unwrap_or(0);
+        println!("   {:<width$}  {}  ({} candidates)", password, mask, format_number(keyspace), width = width);
+    }
+    
+    Ok(())
+}
+
+/// options for the `hash` command
+#[derive(Debug, Clone, Args)]
+pub struct HashArgs {
+    /// hash algorithm
+    #[arg(short, long)]
+    pub algo: Algorithm,
+    
+    /// text to hash
+    #[arg(short, long)]
+    pub input: String,
+    
+    /// salt, prepended to the input as for a target's `salt` (the kdf salt
+    /// for scrypt, argon2, bcrypt and the crypt formats)
+    #[arg(long)]
+    pub salt: Option<String>,
+    
+    /// byte encoding of the input before hashing
+    #[arg(long, default_value = "utf8")]
+    pub password_encoding: PasswordEncoding,
+}
+
+pub fn hash(args: HashArgs) -> Result<()> {
+    let input = args.password_encoding.encode(&args.input)
+        .ok_or_else(|| usage_error(format!("input can't be encoded as {}", args.password_encoding)))?;
+    let digest = tools::hash_password(args.algo, &input, args.salt.as_deref().map(str::as_bytes))
+        .map_err(|e| usage_error(e.to_string()))?;
+    println!("{}", digest);
+    
+    Ok(())
+}
+
+/// options for the `split` command
+#[derive(Debug, Clone, Args)]
+pub struct SplitArgs {
+    /// number of shards (machines or terminals) to divide the keyspace across
+    #[arg(short = 'n', long)]
+    pub shards: u64,
+    
+    /// targets file named in the printed commands
+    #[arg(short, long, default_value = "targets.json")]
+    pub targets: PathBuf,
+    
+    /// attack strategy
+    #[arg(short, long, value_enum)]
+    pub strategy: Strategy,
+    
+    /// wordlist path(s) (for dictionary mode)
+    #[arg(short, long, value_delimiter = ',')]
+    pub wordlist: Vec<PathBuf>,
+    
+    /// keep surrounding whitespace on wordlist lines (exact bytes)
+    #[arg(long)]
+    pub no_trim: bool,
+    
+    /// mask pattern (for mask mode) e.g. ?l?l?l?d?d
+    #[arg(short, long)]
+    pub mask: Option<String>,
+    
+    /// charset (for brute force mode) e.g. "abc123"
+    #[arg(short, long)]
+    pub charset: Option<String>,
+    
+    /// minimum length (for brute force)
+    #[arg(long, default_value = "1")]
+    pub min_len: usize,
+    
+    /// maximum length (for brute force)
+    #[arg(long, default_value = "8")]
+    pub max_len: usize,
+}
+
+/// divide `total` candidates into `shards` contiguous (skip, limit) ranges.
+/// the first `total % shards` shards take one extra candidate
+pub fn shard_ranges(total: u64, shards: u64) -> Vec<(u64, u64)> {
+    let base = total / shards;
+    let remainder = total % shards;
+    
+    let mut ranges = Vec::with_capacity(shards as usize);
+    let mut start = 0;
+    for i in 0..shards {
+        let len = base + u64::from(i < remainder);
+        ranges.push((start, len));
+        start += len;
+    }
+    
+    ranges
+}
+
+/// how many candidates `split` divides up
+fn split_size(options: &GeneratorOptions) -> Result<u128> {
+    if let Some(total) = create_generator(options)?.estimated_size() {
+        return Ok(total);
+    }
+    if !matches!(options.strategy, Strategy::Dictionary) {
+        return Err(usage_error("keyspace size unknown: split supports the dictionary, mask and brute strategies"));
+    }
+    
+    // a lone wordlist isn't counted when it's opened. lines that trim to
+    // nothing overcount it, which only leaves the last shards short
+    let mut lines = 0u128;
+    for path in &options.wordlists {
+        lines += u128::from(DictionaryGenerator::count_lines(path)
+            .map_err(|e| anyhow::anyhow!("cannot read wordlist {}: {}", path.display(), e))?);
+    }
+    Ok(lines)
+}
+
+/// quote an argument for display if the shell would split or expand it
+pub(crate) fn shell_quote(arg: &str) -> String {
+    if arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./,=:".contains(c)) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+pub fn split(args: SplitArgs) -> Result<()> {
+    if args.shards == 0 {
+        return Err(usage_error("--shards must be at least 1"));
+    }
+    
+    let options = GeneratorOptions {
+        strategy: args.strategy,
+        wordlists: args.wordlist.clone(),
+        no_trim: args.no_trim,
+        mask: args.mask.clone(),
+        charset: args.charset.clone(),
+        min_len: args.min_len,
+        max_len: args.max_len,
+        ..Default::default()
+    };
+    
+    let total = split_size(&options)?;
+    // --skip and --limit are u64, so later shards couldn't say where to start
+    let total = u64::try_from(total)
+        .map_err(|_| anyhow::anyhow!("keyspace of {} candidates is too large to split (at most {})", total, u64::MAX))?;
+    
+    if args.shards > total {
+        anyhow::bail!("{} shards for only {} candidates", args.shards, total);
+    }
+    
+    // the strategy flags every shard shares
+    let strategy = args.strategy.to_possible_value().unwrap();
+    let mut common = vec![
+        "--targets".to_string(), args.targets.display().to_string(),
+        "--strategy".to_string(), strategy.get_name().to_string(),
+    ];
+    match args.strategy {
+        Strategy::Dictionary => {
+            let paths: Vec<String> = args.wordlist.iter().map(|p| p.display().to_string()).collect();
+            common.extend(["--wordlist".to_string(), paths.join(",")]);
+            if args.no_trim {
+                common.push("--no-trim".to_string());
+            }
+        }
+        Strategy::Mask | Strategy::Hybrid => {
+            common.extend(["--mask".to_string(), args.mask.unwrap_or_default()]);
+        }
+        Strategy::Command => unreachable!("command keyspaces have no known size"),
+        Strategy::Brute => {
+            common.extend([
+                "--charset".to_string(), args.charset.unwrap_or_else(|| DEFAULT_CHARSET.to_string()),
+                "--min-len".to_string(), args.min_len.to_string(),
+                "--max-len".to_string(), args.max_len.to_string(),
+            ]);
+        }
+    }
+    let common: Vec<String> = common.iter().map(|a| shell_quote(a)).collect();
+    
+    println!("🧩 {} candidates → {} shards of ~{}",
+        format_number(total), args.shards, format_number(total / args.shards));
+    println!();
+    
+    for (i, (skip, limit)) in shard_ranges(total, args.shards).into_iter().enumerate() {
+        println!("# shard {}/{}", i + 1, args.shards);
+        println!("blitzforge run {} --skip {} --limit {}", common.join(" "), skip, limit);
+    }
+    
+    Ok(())
+}
+
+/// options for the `merge` command
+#[derive(Debug, Clone, Args)]
+pub struct MergeArgs {
+    /// result json files (from `run --results`) and/or hash:password potfiles
+    #[arg(required = true)]
+    pub inputs: Vec<PathBuf>,
+    
+    /// deduplicated potfile to write
+    #[arg(short, long, default_value = "merged.pot")]
+    pub potfile: PathBuf,
+    
+    /// combined results json to write
+    #[arg(short, long, default_value = "merged.json")]
+    pub json: PathBuf,
+}
+
+pub fn merge(args: MergeArgs) -> Result<()> {
+    println!("🔗 Merging {} files...", args.inputs.len());
+    
+    let mut sources = Vec::with_capacity(args.inputs.len());
+    for path in &args.inputs {
+        let entries = results::load_entries(path)?;
+        println!("   {} entries ← {}", entries.len(), path.display());
+        sources.push(entries);
+    }
+    
+    let total: usize = sources.iter().map(Vec::len).sum();
+    let merged = results::merge_entries(sources);
+    
+    fs::write(&args.potfile, results::to_potfile(&merged))?;
+    fs::write(&args.json, serde_json::to_string_pretty(&merged)?)?;
+    
+    println!("✅ {} unique cracked hashes ({} duplicates dropped)", merged.len(), total - merged.len());
+    println!("   Potfile: {}", args.potfile.display());
+    println!("   Results: {}", args.json.display());
+    
+    Ok(())
+}
+
+/// options for the `diff-targets` command
+#[derive(Debug, Clone, Args)]
+pub struct DiffTargetsArgs {
+    /// targets file before the change (json or .bft)
+    pub old: PathBuf,
+    
+    /// targets file after the change
+    pub new: PathBuf,
+    
+    /// print the diff as json
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// one line per changed target - `+ id`, `- id` or `~ id: fields` - with
+/// the counts on stderr, so the output pipes cleanly
+pub fn diff_targets(args: DiffTargetsArgs) -> Result<()> {
+    let old = load_targets(&args.old)?;
+    let new = load_targets(&args.new)?;
+    let diff = targets_file::diff_targets(&old, &new);
+    
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+        return Ok(());
+    }
+    
+    for id in &diff.added {
+        println!("+ {}", id);
+    }
+    for id in &diff.removed {
+        println!("- {}", id);
+    }
+    for modified in &diff.modified {
+        println!("~ {}: {}", modified.id, modified.fields.join(", "));
+    }
+    eprintln!("{} added, {} removed, {} modified", diff.added.len(), diff.removed.len(), diff.modified.len());
+    
+    Ok(())
+}
+
+/// output layout for `report`
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum ReportFormat {
+    #[default]
+    Text,
+    Markdown,
+    Json,
+}
+
+/// options for the `report` command
+#[derive(Debug, Clone, Args)]
+pub struct ReportArgs {
+    /// csv file to analyze
+    #[arg(short, long)]
+    pub csv: PathBuf,
+    
+    /// add a relative cost per hash column, with this algorithm's median H/s as 1.0
+    #[arg(long, num_args = 0..=1, default_missing_value = "blitzhash")]
+    pub normalize_to: Option<String>,
+    
+    /// drop this fraction of the slowest and of the fastest runs per
+    /// algorithm (0.1 = 10% from each end) before computing statistics
+    #[arg(long, default_value_t = 0.0)]
+    pub trim: f64,
+    
+    /// output format
+    #[arg(short, long, value_enum, default_value_t = ReportFormat::Text)]
+    pub format: ReportFormat,
+    
+    /// also break the cracked passwords down by length, with the median
+    /// time each length took to find
+    #[arg(long)]
+    pub by_length: bool,
+}
+
+/// cracks of one password length
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct LengthSummary {
+    pub length: usize,
+    pub found: usize,
+    /// median seconds into the run the passwords were found (rows that
+    /// logged a time only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub median_found_in_s: Option<f64>,
+}
+
+/// per-algorithm benchmark summary
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AlgorithmSummary {
+    pub algorithm: String,
+    pub runs: usize,
+    pub median_hashes_per_s: f64,
+    pub peak_hashes_per_s: f64,
+    /// mean H/s over the runs left after trimming (only with --trim)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trimmed_mean_hashes_per_s: Option<f64>,
+    /// runs dropped as outliers, counting both ends
+    pub trimmed_runs: usize,
+    pub found: usize,
+    /// median share of the keyspace the runs searched, over the rows that
+    /// logged one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub median_keyspace_fraction: Option<f64>,
+    /// runs with every worker pinned to a core; absent when no row logged it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinned_runs: Option<usize>,
+    /// cost of one hash relative to the baseline (baseline median H/s over
+    /// this median): 4.0 means each hash takes four times as long
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relative_cost: Option<f64>,
+}
+
+/// fastest-to-slowest ratio within one algorithm beyond which the rows more
+/// likely mix up algorithms than come from differently loaded machines
+const SUSPICIOUS_SPREAD: f64 = 100.0;
+
+/// heuristic checks for logs that can't be right: the placeholder columns
+/// older loggers wrote, or one label across rows that clearly differ
+fn data_warnings(runs: &[BenchmarkRecord]) -> Vec<String> {
+    use std::collections::{BTreeMap, HashSet};
+    let mut warnings = Vec::new();
+    
+    if runs.len() < 2 {
+        return warnings;
+    }
+    
+    if runs.iter().all(|r| r.strategy == "dictionary" && r.workers == 8 && r.keyspace_size == "unknown") {
+        warnings.push("every row has strategy dictionary, 8 workers and an unknown keyspace - \
+            placeholders from an older logger, so those columns don't describe the runs".to_string());
+    } else {
+        let keyspaces: HashSet<&str> = runs.iter()
+            .map(|r| r.keyspace_size.as_str())
+            .filter(|k| k.parse::<u128>().is_ok())
+            .collect();
+        let strategies: HashSet<&str> = runs.iter().map(|r| r.strategy.as_str()).collect();
+        let workers: HashSet<usize> = runs.iter().map(|r| r.workers).collect();
+        
+        if keyspaces.len() > 1 && strategies.len() == 1 && workers.len() == 1 {
+            warnings.push(format!(
+                "{} different keyspace sizes, but every row has strategy {} and {} workers - check those columns were logged correctly",
+                keyspaces.len(), runs[0].strategy, runs[0].workers,
+            ));
+        }
+    }
+    
+    let mut ranges: BTreeMap<&str, (f64, f64)> = BTreeMap::new();
+    for r in runs.iter().filter(|r| r.hashes_per_s > 0.0) {
+        let range = ranges.entry(r.algorithm.as_str()).or_insert((f64::MAX, 0.0));
+        range.0 = range.0.min(r.hashes_per_s);
+        range.1 = range.1.max(r.hashes_per_s);
+    }
+    for (algorithm, (min, max)) in ranges {
+        if max / min > SUSPICIOUS_SPREAD {
+            warnings.push(format!(
+                "{} runs range from {} to {} - are some rows labelled with the wrong algorithm?",
+                algorithm, format_hashes_per_sec(min), format_hashes_per_sec(max),
+            ));
+        }
+    }
+    
+    warnings
+}
+
+/// summarize runs per algorithm, sorted by name. throughput statistics use
+/// the samples left after trimming `trim` from each end. with a baseline,
+/// each summary gets its relative cost from the ratio of medians
+fn summarize_runs(runs: &[BenchmarkRecord], baseline: Option<&str>, trim: f64) -> Result<Vec<AlgorithmSummary>> {
+    if !(0.0..0.5).contains(&trim) {
+        return Err(usage_error("--trim must be at least 0 and below 0.5"));
+    }
+    
+    use std::collections::BTreeMap;
+    let mut by_algo: BTreeMap<String, Vec<&BenchmarkRecord>> = BTreeMap::new();
+    
+    for run in runs {
+        by_algo.entry(run.algorithm.to_lowercase())
+            .or_default()
+            .push(run);
+    }
+    
+    let mut summaries: Vec<AlgorithmSummary> = by_algo.into_iter()
+        .map(|(algorithm, records)| {
+            let throughputs: Vec<f64> = records.iter().map(|r| r.hashes_per_s).collect();
+            let kept = trimmed(&throughputs, trim);
+            let fractions: Vec<f64> = records.iter().filter_map(|r| r.keyspace_fraction).collect();
+            AlgorithmSummary {
+                algorithm,
+                runs: records.len(),
+                median_hashes_per_s: median(&kept),
+                peak_hashes_per_s: kept.iter().copied().fold(0.0_f64, f64::max),
+                trimmed_mean_hashes_per_s: (trim > 0.0)
+                    .then(|| kept.iter().sum::<f64>() / kept.len() as f64),
+                trimmed_runs: throughputs.len() - kept.len(),
+                found: records.iter().filter(|r| r.found).count(),
+                median_keyspace_fraction: (!fractions.is_empty()).then(|| median(&fractions)),
+                pinned_runs: records.iter().any(|r| r.pinned_threads.is_some())
+                    .then(|| records.iter().filter(|r| r.pinned_threads == Some(true)).count()),
+                relative_cost: None,
+            }
+        })
+        .collect();
+    
+    if let Some(baseline) = baseline {
+        // accept aliases like "blitz" or "argon2id"
+        let name = baseline.parse::<Algorithm>()
+            .map(|a| a.to_string())
+            .unwrap_or_else(|_| baseline.to_lowercase());
         
-        // log to csv if enabled
-        if let Some(ref mut log) = logger {
-            log.log_result(&result, &targets)?;
-        }
+        let base_median = summaries.iter()
+            .find(|s| s.algorithm == name)
+            .map(|s| s.median_hashes_per_s)
+            .ok_or_else(|| anyhow::anyhow!("no {} runs in the report to normalize to", name))?;
         
-        // prepare for next run if needed
-        if run < repeat {
-            generator = match strategy {
-                Strategy::Dictionary => {
-                    let wordlist_path = wordlist.clone().unwrap();
-                    Box::new(DictionaryGenerator::new(wordlist_path)?)
-                }
-                Strategy::Mask => {
-                    let mask_pattern = mask.clone().unwrap();
-                    Box::new(MaskGenerator::new(&mask_pattern)?)
-                }
-                Strategy::Brute => {
-                    let charset_str = charset.clone().unwrap_or_else(|| "abcdefghijklmnopqrstuvwxyz0123456789".to_string());
-                    Box::new(BruteForceGenerator::new(&charset_str, min_len, max_len))
-                }
-                Strategy::Hybrid => unreachable!(),
-            };
+        for summary in &mut summaries {
+            if summary.median_hashes_per_s > 0.0 {
+                summary.relative_cost = Some(base_median / summary.median_hashes_per_s);
+            }
         }
     }
     
-    println!("\n✅ All runs completed!");
-    
-    Ok(())
+    Ok(summaries)
 }
 
-pub fn generate_report(csv_path: PathBuf) -> Result<()> {
-    println!("📊 Generating report from: {}", csv_path.display());
+/// found rows grouped by `password_length`, shortest first. rows whose
+/// length is blank or not a number are left out, as are unparsable times
+fn summarize_by_length(runs: &[BenchmarkRecord]) -> Vec<LengthSummary> {
+    use std::collections::BTreeMap;
+    let mut by_length: BTreeMap<usize, (usize, Vec<f64>)> = BTreeMap::new();
     
-    let mut reader = csv::Reader::from_path(&csv_path)?;
+    for run in runs.iter().filter(|r| r.found) {
+        let Ok(length) = run.password_length.trim().parse::<usize>() else {
+            continue;
+        };
+        let entry = by_length.entry(length).or_default();
+        entry.0 += 1;
+        if let Ok(secs) = run.found_in_s.trim().parse::<f64>() {
+            entry.1.push(secs);
+        }
+    }
+    
+    by_length.into_iter()
+        .map(|(length, (found, times))| LengthSummary {
+            length,
+            found,
+            median_found_in_s: (!times.is_empty()).then(|| median(&times)),
+        })
+        .collect()
+}
+
+pub fn generate_report(args: ReportArgs) -> Result<()> {
+    // flexible: older logs may have rows with and without the pinned_threads column
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_path(&args.csv)?;
     
     let mut runs = Vec::new();
     for result in reader.deserialize() {
@@ -198,45 +2906,145 @@ pub fn generate_report(csv_path: PathBuf) -> Result<()> {
         runs.push(record);
     }
     
-    if runs.is_empty() {
+    let summaries = summarize_runs(&runs, args.normalize_to.as_deref(), args.trim)?;
+    
+    // stderr, so json and markdown output stay clean
+    for warning in data_warnings(&runs) {
+        eprintln!("⚠️  {}", warning);
+    }
+    
+    let lengths = args.by_length.then(|| summarize_by_length(&runs));
+    
+    match (args.format, &lengths) {
+        (ReportFormat::Json, None) => println!("{}", serde_json::to_string_pretty(&summaries)?),
+        (ReportFormat::Json, Some(lengths)) => println!("{}", serde_json::to_string_pretty(&serde_json::json!({
+            "algorithms": summaries,
+            "by_length": lengths,
+        }))?),
+        (ReportFormat::Markdown, _) => {
+            print!("{}", markdown_report(&summaries, runs.len()));
+            if let Some(lengths) = &lengths {
+                print!("\n{}", markdown_length_table(lengths));
+            }
+        }
+        (ReportFormat::Text, _) => {
+            print_text_report(&summaries, runs.len(), &args.csv);
+            if let Some(lengths) = &lengths {
+                print_length_breakdown(lengths);
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+fn print_length_breakdown(lengths: &[LengthSummary]) {
+    println!("\n📏 Cracks by Password Length:");
+    if lengths.is_empty() {
+        println!("   no cracked rows with a password length");
+    }
+    for l in lengths {
+        let median = l.median_found_in_s.map_or_else(|| "-".to_string(), format_seconds);
+        println!("   {:>3} chars: {:>5} found, median {} to find", l.length, l.found, median);
+    }
+}
+
+fn markdown_length_table(lengths: &[LengthSummary]) -> String {
+    let mut out = String::from("| Length | Found | Median time to find |\n|---:|---:|---:|\n");
+    for l in lengths {
+        let median = l.median_found_in_s.map_or_else(|| "-".to_string(), format_seconds);
+        out.push_str(&format!("| {} | {} | {} |\n", l.length, l.found, median));
+    }
+    out
+}
+
+fn print_text_report(summaries: &[AlgorithmSummary], total_runs: usize, csv_path: &std::path::Path) {
+    println!("📊 Generating report from: {}", csv_path.display());
+    
+    if total_runs == 0 {
         println!("⚠️  No benchmark data found");
-        return Ok(());
+        return;
     }
     
     println!("\n📈 Summary:");
-    println!("   Total runs: {}", runs.len());
+    println!("   Total runs: {}", total_runs);
     
-    // group by algorithm
-    use std::collections::HashMap;
-    let mut by_algo: HashMap<String, Vec<&BenchmarkRecord>> = HashMap::new();
+    println!("\n🔐 Performance by Algorithm:");
+    for summary in summaries {
+        println!("\n   {}:", summary.algorithm.to_uppercase());
+        println!("      Runs:             {}", summary.runs);
+        println!("      Median H/s:       {}", format_hashes_per_sec(summary.median_hashes_per_s));
+        println!("      Peak H/s:         {}", format_hashes_per_sec(summary.peak_hashes_per_s));
+        if let Some(mean) = summary.trimmed_mean_hashes_per_s {
+            println!("      Trimmed mean H/s: {} ({} outlier runs dropped)",
+                format_hashes_per_sec(mean), summary.trimmed_runs);
+        }
+        match summary.median_keyspace_fraction {
+            Some(fraction) => println!("      Success rate:     {}/{} (median {} of keyspace searched)",
+                summary.found, summary.runs, format_share(fraction)),
+            None => println!("      Success rate:     {}/{}", summary.found, summary.runs),
+        }
+        if let Some(pinned) = summary.pinned_runs {
+            println!("      Pinned threads:   {}/{} runs", pinned, summary.runs);
+        }
+        if let Some(cost) = summary.relative_cost {
+            println!("      Relative cost:    {:.2}x per hash", cost);
+        }
+    }
+}
+
+fn markdown_report(summaries: &[AlgorithmSummary], total_runs: usize) -> String {
+    let normalized = summaries.iter().any(|s| s.relative_cost.is_some());
+    let trimmed = summaries.iter().any(|s| s.trimmed_mean_hashes_per_s.is_some());
     
-    for run in &runs {
-        by_algo.entry(run.algorithm.clone())
-            .or_insert_with(Vec::new)
-            .push(run);
+    let mut out = format!("## Benchmark Report\n\nTotal runs: {}\n\n", total_runs);
+    out.push_str("| Algorithm | Runs | Median H/s | Peak H/s | Found |");
+    if trimmed {
+        out.push_str(" Trimmed mean H/s |");
+    }
+    out.push_str(if normalized { " Relative cost |\n" } else { "\n" });
+    out.push_str("|---|---:|---:|---:|---:|");
+    if trimmed {
+        out.push_str("---:|");
     }
+    out.push_str(if normalized { "---:|\n" } else { "\n" });
     
-    println!("\n🔐 Performance by Algorithm:");
-    for (algo, records) in by_algo {
-        let throughputs: Vec<f64> = records.iter().map(|r| r.hashes_per_s).collect();
-        let median = median(&throughputs);
-        let max = throughputs.iter().copied().fold(0.0_f64, f64::max);
-        
-        println!("\n   {}:", algo.to_uppercase());
-        println!("      Runs:             {}", records.len());
-        println!("      Median H/s:       {}", format_hashes_per_sec(median));
-        println!("      Peak H/s:         {}", format_hashes_per_sec(max));
-        
-        let found_count = records.iter().filter(|r| r.found).count();
-        println!("      Success rate:     {}/{}", found_count, records.len());
+    for s in summaries {
+        // the median share of the keyspace searched, next to the finds
+        let searched = s.median_keyspace_fraction
+            .map_or_else(String::new, |fraction| format!(" ({})", format_share(fraction)));
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {}/{}{} |",
+            s.algorithm,
+            s.runs,
+            format_hashes_per_sec(s.median_hashes_per_s),
+            format_hashes_per_sec(s.peak_hashes_per_s),
+            s.found,
+            s.runs,
+            searched,
+        ));
+        if let Some(mean) = s.trimmed_mean_hashes_per_s {
+            out.push_str(&format!(" {} |", format_hashes_per_sec(mean)));
+        }
+        if normalized {
+            match s.relative_cost {
+                Some(cost) => out.push_str(&format!(" {:.2}x |", cost)),
+                None => out.push_str(" - |"),
+            }
+        }
+        out.push('\n');
     }
     
-    Ok(())
+    out
 }
 
 pub fn run_selftest() -> Result<()> {
     println!("🧪 Running BlitzForge self-tests...\n");
     
+    if cfg!(debug_assertions) {
+        println!("⚠️  debug build: expect rates far below a --release build\n");
+    }
+    
     // test 1: hash algorithms
     println!("Test 1: Hash Algorithms");
     test_hash_algorithms()?;
@@ -252,37 +3060,59 @@ pub fn run_selftest() -> Result<()> {
     test_simple_crack()?;
     println!("   ✅ Engine successfully cracked test password\n");
     
+    // test 4: memory-hard kdf crack
+    println!("Test 4: KDF Crack");
+    test_kdf_crack()?;
+    println!("   ✅ Engine successfully cracked scrypt and argon2 passwords\n");
+    
     println!("✅ All self-tests passed!");
     
     Ok(())
 }
 
+/// hashes per micro-benchmark in `selftest` - enough to time, quick in debug builds
+const SELFTEST_BENCH_ITERATIONS: u32 = 100_000;
+
+/// single-threaded H/s hashing a fixed password-sized buffer
+fn bench_hasher(hasher: &dyn Hasher, iterations: u32) -> f64 {
+    let input = *b"benchmark-input!";
+    let start = std::time::Instant::now();
+    
+    for _ in 0..iterations {
+        std::hint::black_box(hasher.hash(std::hint::black_box(&input)));
+    }
+    
+    iterations as f64 / start.elapsed().as_secs_f64().max(f64::EPSILON)
+}
+
 // helper: test hash algorithms
 fn test_hash_algorithms() -> Result<()> {
     use crate::core::hasher::*;
-    use crate::core::blitzhash;
     
     let test_input = b"password";
+    // single-threaded rates: a debug build or a regressed hasher stands out
+    let rate = |hasher: &dyn Hasher| format_hashes_per_sec(bench_hasher(hasher, SELFTEST_BENCH_ITERATIONS));
     
-    // test blitzhash
+    // test blitzhash - pinned bytes lock down the output format
     let hash = blitzhash::blitz_hash(0, test_input);
-    assert_eq!(hash.len(), 32);
-    println!("   BlitzHash: OK (custom algorithm)");
+    assert_eq!(hex::encode(hash), blitzhash::PINNED_PASSWORD_LE);
+    assert_eq!(hex::encode(blitzhash::reorder_lanes(&hash, Endian::Big)), blitzhash::PINNED_PASSWORD_BE);
+    println!("   BlitzHash: OK (custom algorithm, le/be output pinned, {})", rate(&BlitzHasher));
     
     let md5_hasher = Md5Hasher;
     let hash = md5_hasher.hash(test_input);
     assert_eq!(hex::encode(hash), "5f4dcc3b5aa765d61d8327deb882cf99");
-    println!("   MD5: OK");
+    println!("   MD5: OK ({})", rate(&md5_hasher));
     
     let sha1_hasher = Sha1Hasher;
     let hash = sha1_hasher.hash(test_input);
     assert_eq!(hex::encode(hash), "5baa61e4c9b93f3f0682250b6cf8331b7ee68fd8");
-    println!("   SHA1: OK");
+    println!("   SHA1: OK ({})", rate(&sha1_hasher));
     
     let sha256_hasher = Sha256Hasher;
     let hash = sha256_hasher.hash(test_input);
     assert_eq!(hex::encode(hash), "5e884898da28047151d0e56f8dc6292773603d0d6aabbdd62a11ef721d1542d8");
-    println!("   SHA256: OK");
+    println!("   SHA256: OK ({})", rate(&sha256_hasher));
     
     Ok(())
 }
@@ -320,7 +3150,7 @@ fn test_simple_crack() -> Result<()> {
         username: "testuser".to_string(),
         algorithm: Algorithm::BlitzHash,
         hash: hex::encode(hash),
-        salt: String::new(),
+        ..Default::default()
     };
     
     // create simple generator with known password
@@ -339,6 +3169,59 @@ fn test_simple_crack() -> Result<()> {
     
     assert_eq!(result.matches.len(), 1);
     assert_eq!(result.matches[0].password_string(), "password");
+    println!("   Cracked in {:.1} ms ({} guesses, {})",
+        result.total_time * 1000.0,
+        result.statistics.guesses_tried,
+        format_hashes_per_sec(result.statistics.hashes_per_second));
+    
+    Ok(())
+}
+
+// helper: crack a low-parameter scrypt phc string of a known password
+fn test_kdf_crack() -> Result<()> {
+    use crate::core::hasher::*;
+    
+    let phc = hash_phc(Algorithm::Scrypt, b"kdf42", b"selftestsalt")?;
+    assert!(verify_phc(Algorithm::Scrypt, b"kdf42", &phc));
+    println!("   scrypt: OK ({})", phc.split('$').nth(2).unwrap_or(""));
+    
+    // known low-cost argon2id hash of "password"
+    let argon2_phc = "$argon2id$v=19$m=1024,t=2,p=1$YmxpdHpmb3JnZXNhbHQ$eT81nEcRacIMTVZMus9MsYNtefiXGBKkhovOMToAXiA";
+    assert!(verify_phc(Algorithm::Argon2, b"password", argon2_phc));
+    assert!(!verify_phc(Algorithm::Argon2, b"passwore", argon2_phc));
+    println!("   argon2id: OK (m=1024,t=2,p=1)");
+    
+    let targets = vec![
+        Target {
+            id: "kdf".to_string(),
+            username: "testuser".to_string(),
+            algorithm: Algorithm::Scrypt,
+            hash: phc,
+            ..Default::default()
+        },
+        Target {
+            id: "argon".to_string(),
+            username: "testuser".to_string(),
+            algorithm: Algorithm::Argon2,
+            hash: hash_phc(Algorithm::Argon2, b"kdf07", b"selftestsalt")?,
+            ..Default::default()
+        },
+    ];
+    
+    let mut engine = Engine::new(
+        targets,
+        Box::new(MaskGenerator::new("kdf?d?d")?),
+        num_cpus(),
+        16,
+    );
+    
+    let result = engine.run(|_| {})?;
+    
+    assert_eq!(result.matches.len(), 2);
+    for m in &result.matches {
+        let expected = if m.target_id == "kdf" { "kdf42" } else { "kdf07" };
+        assert_eq!(m.password_string(), expected);
+    }
     
     Ok(())
 }
@@ -360,9 +3243,26 @@ struct BenchmarkRecord {
     found: bool,
     password_length: String,
     found_in_s: String,
+    /// absent in logs written before the column existed
+    #[serde(default)]
+    pinned_threads: Option<bool>,
+    /// share of the keyspace searched; blank when its size was unknown,
+    /// absent in older logs
+    #[serde(default)]
+    keyspace_fraction: Option<f64>,
 }
 
 // helper functions
+/// sorted copy of `values` without the lowest and highest `fraction` of
+/// samples (rounded down, so small samples may lose nothing)
+fn trimmed(values: &[f64], fraction: f64) -> Vec<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    
+    let cut = (sorted.len() as f64 * fraction) as usize;
+    sorted[cut..sorted.len() - cut].to_vec()
+}
+
 fn median(values: &[f64]) -> f64 {
     if values.is_empty() {
         return 0.0;
@@ -379,8 +3279,11 @@ fn median(values: &[f64]) -> f64 {
     }
 }
 
-fn format_number(n: u64) -> String {
-    if n >= 1_000_000_000_000 {
+fn format_number(n: impl Into<u128>) -> String {
+    let n: u128 = n.into();
+    if n >= 1_000_000_000_000_000 {
+        format!("{:.2e}", n as f64)
+    } else if n >= 1_000_000_000_000 {
         format!("{:.2}T", n as f64 / 1_000_000_000_000.0)
     } else if n >= 1_000_000_000 {
         format!("{:.2}B", n as f64 / 1_000_000_000.0)
@@ -393,6 +3296,49 @@ fn format_number(n: u64) -> String {
     }
 }
 
+/// a fraction as a percentage, in scientific notation once it's too small
+/// to show in fixed point
+fn format_share(fraction: f64) -> String {
+    let percent = fraction * 100.0;
+    if percent == 0.0 || percent >= 0.01 {
+        format!("{:.2}%", percent)
+    } else {
+        format!("{:.1e}%", percent)
+    }
+}
+
+/// parse a byte size like `512M`, `4G` or `1048576` (binary units, so
+/// `1K` is 1024 bytes; a trailing `B`/`iB` is allowed)
+fn parse_size(s: &str) -> Result<u64, String> {
+    let upper = s.trim().to_ascii_uppercase();
+    let digits = upper.trim_end_matches("IB").trim_end_matches('B');
+    
+    let (number, shift) = match digits.char_indices().last() {
+        Some((i, 'K')) => (&digits[..i], 10),
+        Some((i, 'M')) => (&digits[..i], 20),
+        Some((i, 'G')) => (&digits[..i], 30),
+        Some((i, 'T')) => (&digits[..i], 40),
+        _ => (digits, 0),
+    };
+    
+    let value: f64 = number.trim().parse()
+        .map_err(|_| format!("invalid size '{}' (expected e.g. 512M or 4G)", s))?;
+    if value < 0.0 || !value.is_finite() {
+        return Err(format!("invalid size '{}'", s));
+    }
+    Ok((value * (1u64 << shift) as f64) as u64)
+}
+
+fn format_bytes(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 * 1024 {
+        format!("{:.1} GiB", bytes as f64 / (1024.0 * 1024.0 * 1024.0))
+    } else if bytes >= 1024 * 1024 {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{} KiB", bytes / 1024)
+    }
+}
+
 fn format_hashes_per_sec(h: f64) -> String {
     if h >= 1_000_000_000.0 {
         format!("{:.2} GH/s", h / 1_000_000_000.0)
@@ -410,4 +3356,727 @@ fn num_cpus() -> usize {
     std::thread::available_parallelism()
         .map(|n| n.get())
         .unwrap_or(4)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct RunCli {
+        #[command(flatten)]
+        args: RunArgs,
+    }
+
+    fn parse_run(argv: &[&str]) -> RunArgs {
+        let mut full = vec!["run"];
+        full.extend_from_slice(argv);
+        RunCli::try_parse_from(full).unwrap().args
+    }
+
+    #[test]
+    fn test_preset_expands_flags() {
+        let mut args = parse_run(&["--targets", "t.json", "--preset", "pin4"]);
+        args.apply_preset();
+
+        assert!(matches!(args.strategy, Some(Strategy::Brute)));
+        assert_eq!(args.charset.as_deref(), Some("0123456789"));
+        assert_eq!(args.min_len, Some(4));
+        assert_eq!(args.max_len, Some(4));
+    }
+
+    #[test]
+    fn test_explicit_flags_override_preset() {
+        let mut args = parse_run(&[
+            "--targets", "t.json", "--preset", "pin4", "--max-len", "5", "--charset", "0123",
+        ]);
+        args.apply_preset();
+
+        assert_eq!(args.min_len, Some(4));
+        assert_eq!(args.max_len, Some(5));
+        assert_eq!(args.charset.as_deref(), Some("0123"));
+    }
+
+    #[test]
+    fn test_ntlm_common_preset_adds_rules() {
+        let mut args = parse_run(&["--targets", "t.json", "--preset", "ntlm-common"]);
+        args.apply_preset();
+
+        assert!(matches!(args.strategy, Some(Strategy::Dictionary)));
+        assert!(args.common_rules);
+    }
+
+    #[test]
+    fn test_batch_sizes_parse() {
+        assert_eq!("4096".parse::<BatchSizes>().unwrap(), BatchSizes::uniform(4096));
+        assert_eq!("bcrypt=64, 8192 ,blitzhash=65536".parse::<BatchSizes>().unwrap(), BatchSizes {
+            default: Some(8192),
+            per_algorithm: vec![(Algorithm::Bcrypt, 64), (Algorithm::BlitzHash, 65536)],
+        });
+        for bad in ["0", "bcrypt=0", "1,2", "md5=1,md5=2", "rot13=5", "many"] {
+            assert!(bad.parse::<BatchSizes>().is_err(), "{}", bad);
+        }
+        
+        // a preset only fills in the default size
+        let mut args = parse_run(&["--targets", "t.json", "--preset", "fast-dict", "--batch-size", "bcrypt=64"]);
+        args.apply_preset();
+        assert_eq!(args.batch_size, Some(BatchSizes {
+            default: Some(16384),
+            per_algorithm: vec![(Algorithm::Bcrypt, 64)],
+        }));
+    }
+    
+    #[test]
+    fn test_mask_len_mismatch_is_usage_error() {
+        assert_eq!(mask_of_len("?l?l?d?d", Some(4)).unwrap().positions(), 4);
+        assert_eq!(mask_of_len("\u{e9}t\u{e9}?d", Some(4)).unwrap().positions(), 6);
+        
+        // a dropped ?l
+        let err = mask_of_len("?l?l?d", Some(4)).err().unwrap();
+        assert_eq!(err.to_string(), "mask ?l?l?d gives 3-character candidates, expected 4");
+        assert_eq!(crate::cli::exit_code::for_error(&err), crate::cli::exit_code::INVALID_ARGS);
+    }
+    
+    #[test]
+    fn test_algorithm_list_reports_unknown_names() {
+        assert_eq!(parse_algorithm_list("md5, SHA1,").unwrap(), vec![Algorithm::Md5, Algorithm::Sha1]);
+        
+        let err = parse_algorithm_list("md5,shaX,sha256,rot13").unwrap_err();
+        assert!(err.to_string().starts_with("unknown algorithms shaX, rot13 (expected"), "{}", err);
+        assert_eq!(crate::cli::exit_code::for_error(&err), crate::cli::exit_code::INVALID_ARGS);
+        
+        assert!(parse_algorithm_list("sha").unwrap_err().to_string().starts_with("unknown algorithm sha "));
+        assert!(parse_algorithm_list(" , ").is_err());
+    }
+    
+    #[test]
+    fn test_explicit_flags_override_config() {
+        let config = RunConfig {
+            strategy: "dictionary,mask".to_string(),
+            algorithms: vec![Algorithm::Md5],
+            wordlists: vec!["words.txt".to_string()],
+            no_trim: true,
+            mask: Some("?d?d?d".to_string()),
+            charset: None,
+            template: None,
+            min_len: 1,
+            max_len: 8,
+            skip: 10,
+            limit: None,
+            generator_cmd: None,
+            workers: 3,
+            batch_size: 512,
+            algorithm_batch_sizes: vec![(Algorithm::Bcrypt, 8)],
+            repeat: 2,
+        };
+
+        let mut args = parse_run(&["--targets", "t.json", "--mask", "?l?l", "--skip", "0"]);
+        args.apply_config(&config).unwrap();
+
+        assert_eq!(args.strategy_pipeline, vec![Strategy::Dictionary, Strategy::Mask]);
+        assert_eq!(args.wordlist, vec![PathBuf::from("words.txt")]);
+        assert!(args.no_trim);
+        assert_eq!(args.mask.as_deref(), Some("?l?l"));
+        assert_eq!(args.skip, Some(0));
+        assert_eq!(args.workers, Some(3));
+        assert_eq!(args.batch_size, Some(BatchSizes {
+            default: Some(512),
+            per_algorithm: vec![(Algorithm::Bcrypt, 8)],
+        }));
+        assert_eq!(args.repeat, Some(2));
+
+        let mut args = parse_run(&["--targets", "t.json", "--strategy", "brute"]);
+        args.apply_config(&config).unwrap();
+        assert_eq!(args.strategy, Some(Strategy::Brute));
+        assert!(args.strategy_pipeline.is_empty());
+
+        let bad = RunConfig { strategy: "rainbow".to_string(), ..config };
+        assert!(parse_run(&["--targets", "t.json"]).apply_config(&bad).is_err());
+    }
+
+    #[test]
+    fn test_compare_two_algorithms() {
+        let options = GeneratorOptions {
+            strategy: Strategy::Mask,
+            mask: Some("?l?d".to_string()),
+            min_len: 1,
+            max_len: 8,
+            ..Default::default()
+        };
+        let algorithms = [Algorithm::Md5, Algorithm::Sha256];
+        let timings = compare_algorithms_runs("q7", &algorithms, &options, 2, std::time::Duration::from_secs(60)).unwrap();
+
+        assert_eq!(timings.len(), 2);
+        assert_eq!(timings[0].algorithm, Algorithm::Md5);
+        assert_eq!(timings[1].algorithm, Algorithm::Sha256);
+        assert!(timings.iter().all(|t| t.found));
+        // same keyspace in the same order: both reach the password together
+        assert_eq!(timings[0].guesses, timings[1].guesses);
+
+        let mut slow = timings[1].clone();
+        slow.found = false;
+        slow.hashes_per_second = 1.0;
+        let table = comparison_table(&[timings[0].clone(), slow]);
+        assert!(table.contains("time limit"), "{}", table);
+        assert!(table.contains("to crack"), "{}", table);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unreadable_lists_in_a_directory_are_skipped() {
+        let dir = std::env::temp_dir().join(format!("blitzforge_lists_{}", std::process::id()));
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("b.txt"), "beta\n").unwrap();
+        fs::write(dir.join("a.txt"), "alpha\n").unwrap();
+        std::os::unix::fs::symlink(dir.join("missing.txt"), dir.join("dangling.txt")).unwrap();
+
+        let expanded = expand_wordlists(&[dir.clone()]);
+        fs::remove_dir_all(&dir).ok();
+        assert_eq!(expanded.unwrap(), [dir.join("a.txt"), dir.join("b.txt")]);
+    }
+
+    #[test]
+    fn test_dictionary_cache_reads_the_wordlist_once() {
+        let path = std::env::temp_dir().join(format!("blitzforge_cache_{}.txt", std::process::id()));
+        fs::write(&path, "beta\nalpha\ngamma\n").unwrap();
+        let mut options = GeneratorOptions {
+            strategy: Strategy::Dictionary,
+            wordlists: vec![path.clone()],
+            min_len: 1,
+            max_len: 8,
+            sort_wordlist: WordlistSort::Lex,
+            ..Default::default()
+        };
+        options.wordlist_cache = Some(dictionary_in_memory(&options).unwrap());
+
+        // gone from disk, so every generator after this comes from the cache
+        fs::remove_file(&path).unwrap();
+        for _ in 0..2 {
+            let mut generator = create_generator(&options).unwrap();
+            assert_eq!(generator.next_batch(10).unwrap(), vec![b"alpha".to_vec(), b"beta".to_vec(), b"gamma".to_vec()]);
+        }
+
+        options.wordlist_cache = None;
+        assert!(create_generator(&options).is_err());
+    }
+
+    #[test]
+    fn test_format_seconds() {
+        assert_eq!(format_seconds(0.5), "0.50s");
+        assert_eq!(format_seconds(90.0), "1.5 min");
+        assert_eq!(format_seconds(3.0 * 86400.0), "3.0 days");
+    }
+
+    #[test]
+    fn test_kdf_worker_cap() {
+        let argon2 = |m: u32| Target {
+            id: format!("a{}", m),
+            username: "u".to_string(),
+            algorithm: Algorithm::Argon2,
+            hash: format!("$argon2id$v=19$m={},t=2,p=1$c2FsdHNhbHQ$AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA", m),
+            ..Default::default()
+        };
+
+        let gib = 1024 * 1024 * 1024;
+
+        // the costliest target decides: 256 mib per hash -> 4 workers in 1 gib
+        let per_hash = kdf_memory_per_hash(&[argon2(1024), argon2(256 * 1024)]).unwrap();
+        assert_eq!(per_hash, 256 * 1024 * 1024);
+        assert_eq!(kdf_worker_cap(per_hash, gib), 4);
+        assert_eq!(kdf_worker_cap(per_hash, 3 * gib + 1), 12);
+        // more than the budget still leaves one worker
+        assert_eq!(kdf_worker_cap(4 * gib, gib), 1);
+
+        // scrypt: 128 * r * n = 128 * 8 * 2^17 = 128 mib
+        let mut scrypt = argon2(0);
+        scrypt.algorithm = Algorithm::Scrypt;
+        scrypt.hash = "$scrypt$ln=17,r=8,p=1$c2FsdHNhbHQ$AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA".to_string();
+        assert_eq!(kdf_memory_per_hash(&[scrypt]), Some(128 * 1024 * 1024));
+
+        assert_eq!(kdf_memory_per_hash(&[]), None);
+        assert_eq!(kdf_worker_cap(0, gib), usize::MAX);
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert_eq!(parse_size("512M"), Ok(512 * 1024 * 1024));
+        assert_eq!(parse_size("4g"), Ok(4 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size("1.5GiB"), Ok(3 * 512 * 1024 * 1024));
+        assert_eq!(parse_size("64KB"), Ok(64 * 1024));
+        assert!(parse_size("lots").is_err());
+        assert!(parse_size("-1G").is_err());
+    }
+
+    #[test]
+    fn test_preview_mask_first_and_last() {
+        let mut gen = MaskGenerator::new("?d?d").unwrap();
+        let preview = preview_candidates(&mut gen, 3, PREVIEW_SCAN_LIMIT);
+
+        assert_eq!(preview.first, vec![b"00".to_vec(), b"01".to_vec(), b"02".to_vec()]);
+        assert_eq!(preview.last, Some(vec![b"97".to_vec(), b"98".to_vec(), b"99".to_vec()]));
+        assert_eq!(preview.total, Some(100));
+    }
+
+    #[test]
+    fn test_preview_skips_tail_of_huge_keyspace() {
+        let mut gen = BruteForceGenerator::new("abc", 1, 3);
+        let preview = preview_candidates(&mut gen, 2, 10);
+
+        assert_eq!(preview.first, vec![b"a".to_vec(), b"b".to_vec()]);
+        assert_eq!(preview.last, None);
+        assert_eq!(preview.total, Some(3 + 9 + 27));
+    }
+
+    #[test]
+    fn test_validate_targets_rejects_or_skips_mismatched_length() {
+        let md5 = |id: &str| Target {
+            id: id.to_string(),
+            username: "u".to_string(),
+            algorithm: Algorithm::Md5,
+            hash: "5f4dcc3b5aa765d61d8327deb882cf99".to_string(),
+            ..Default::default()
+        };
+        let mut wrong = md5("wrong");
+        wrong.algorithm = Algorithm::Sha256;
+        let targets = [md5("ok"), wrong];
+
+        let checked = || targets.iter().cloned().map(Target::checked).collect();
+
+        let err = validate_targets(checked(), false, &TerminalUI::new()).unwrap_err().to_string();
+        assert!(err.contains("invalid target wrong"), "{}", err);
+
+        let (kept, errored) = validate_targets(checked(), true, &TerminalUI::new()).unwrap();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].id, "ok");
+        assert_eq!(errored[0].id, "wrong");
+    }
+
+    #[test]
+    fn test_skip_invalid_isolates_malformed_targets() {
+        let path = std::env::temp_dir().join(format!("blitzforge_errored_{}.json", std::process::id()));
+        fs::write(&path, r#"[
+            {"id": "good", "username": "a", "hash_algo": "md5", "hash_hex": "5f4dcc3b5aa765d61d8327deb882cf99"},
+            {"id": "bad_hex", "username": "b", "hash_algo": "md5", "hash_hex": "not-hex"},
+            {"id": "unknown_scheme", "username": "c", "hash_algo": "md6", "hash_hex": "00"},
+            {"username": "d", "hash_algo": "sha1"},
+            {"id": "also_good", "username": "e", "hash_algo": "sha1", "hash_hex": "5baa61e4c9b93f3f0682250b6cf8331b7ee68fd8"}
+        ]"#).unwrap();
+        
+        // all or nothing without --skip-invalid
+        assert!(load_targets(&path).is_err());
+        
+        let checked: Vec<Result<Target, TargetError>> = targets_file::parse_targets_each(&fs::read(&path).unwrap()).unwrap()
+            .into_iter()
+            .map(|entry| entry.and_then(Target::checked))
+            .collect();
+        fs::remove_file(&path).ok();
+        assert_eq!(checked.len(), 5);
+        
+        let (kept, errored) = validate_targets(checked, true, &TerminalUI::new()).unwrap();
+        assert_eq!(kept.iter().map(|t| t.id.as_str()).collect::<Vec<_>>(), ["good", "also_good"]);
+        let ids: Vec<&str> = errored.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["bad_hex", "unknown_scheme", "#4"]);
+        assert!(errored[0].reason.contains("not valid hex"), "{}", errored[0]);
+        assert!(errored[1].reason.contains("md6"), "{}", errored[1]);
+        assert!(errored[2].reason.contains("missing field"), "{}", errored[2]);
+        
+        // the valid targets are attacked as usual
+        let words = Box::new(MemoryGenerator::new(vec![b"password".to_vec()]));
+        let result = Engine::new(kept, words, 1, 16).run(|_| {}).unwrap();
+        assert_eq!(result.matches.len(), 2);
+    }
+
+    #[test]
+    fn test_no_preset_leaves_flags_untouched() {
+        let mut args = parse_run(&["--targets", "t.json", "--strategy", "mask", "--mask", "?d"]);
+        args.apply_preset();
+
+        assert!(matches!(args.strategy, Some(Strategy::Mask)));
+        assert_eq!(args.batch_size, None);
+        assert_eq!(args.charset, None);
+    }
+
+    #[test]
+    fn test_like_fills_brute_force_flags() {
+        let mut args = parse_run(&["--targets", "t.json", "--like", "Abc123", "--max-len", "7"]);
+        args.apply_like().unwrap();
+
+        assert!(matches!(args.strategy, Some(Strategy::Brute)));
+        assert_eq!(args.charset.as_deref(), Some(tools::infer_charset("Abc123").unwrap().0.as_str()));
+        // explicit flags win
+        assert_eq!((args.min_len, args.max_len), (Some(6), Some(7)));
+
+        let mut args = parse_run(&["--targets", "t.json", "--like", "caf\u{e9}"]);
+        let err = args.apply_like().err().unwrap();
+        assert_eq!(crate::cli::exit_code::for_error(&err), crate::cli::exit_code::INVALID_ARGS);
+    }
+
+    #[test]
+    fn test_shards_tile_keyspace() {
+        for (total, shards) in [(100, 4), (101, 4), (7, 7), (1000, 3)] {
+            let ranges = shard_ranges(total, shards);
+            assert_eq!(ranges.len() as u64, shards);
+
+            let mut next = 0;
+            for &(skip, limit) in &ranges {
+                assert_eq!(skip, next, "shards must be contiguous");
+                assert!(limit == total / shards || limit == total / shards + 1);
+                next = skip + limit;
+            }
+            assert_eq!(next, total);
+        }
+    }
+
+    #[test]
+    fn test_split_counts_a_single_wordlist() {
+        let path = std::env::temp_dir().join(format!("blitzforge_split_{}.txt", std::process::id()));
+        fs::write(&path, "alpha\nbeta\ngamma").unwrap();
+
+        let options = GeneratorOptions {
+            strategy: Strategy::Dictionary,
+            wordlists: vec![path.clone()],
+            min_len: 1,
+            max_len: 8,
+            ..Default::default()
+        };
+        let size = split_size(&options);
+        fs::remove_file(&path).ok();
+        assert_eq!(size.unwrap(), 3);
+    }
+
+    #[test]
+    fn test_shard_generators_cover_keyspace_once() {
+        let options = |skip, limit| GeneratorOptions {
+            strategy: Strategy::Brute,
+            charset: Some("abc".to_string()),
+            min_len: 1,
+            max_len: 3,
+            skip,
+            limit,
+            ..Default::default()
+        };
+
+        let drain = |gen: &mut Box<dyn Generator>| {
+            let mut all = Vec::new();
+            while let Some(batch) = gen.next_batch(5) {
+                all.extend(batch);
+            }
+            all
+        };
+
+        let full = drain(&mut create_generator(&options(0, None)).unwrap());
+
+        let mut sharded = Vec::new();
+        for (skip, limit) in shard_ranges(full.len() as u64, 4) {
+            sharded.extend(drain(&mut create_generator(&options(skip, Some(limit))).unwrap()));
+        }
+        assert_eq!(sharded, full);
+    }
+
+    fn record(algorithm: &str, hashes_per_s: f64) -> BenchmarkRecord {
+        BenchmarkRecord {
+            timestamp: String::new(),
+            target_id: "t".to_string(),
+            algorithm: algorithm.to_string(),
+            strategy: "mask".to_string(),
+            workers: 1,
+            keyspace_size: String::new(),
+            guesses_tried: 0,
+            time_s: 1.0,
+            hashes_per_s,
+            found: true,
+            password_length: String::new(),
+            found_in_s: String::new(),
+            pinned_threads: None,
+            keyspace_fraction: None,
+        }
+    }
+
+    #[test]
+    fn test_report_counts_pinned_runs() {
+        let pinned = |pinned_threads| BenchmarkRecord { pinned_threads, ..record("md5", 500.0) };
+        let runs = vec![pinned(Some(true)), pinned(Some(false)), pinned(None), record("sha1", 500.0)];
+        let summaries = summarize_runs(&runs, None, 0.0).unwrap();
+        let pinned_runs = |algo: &str| summaries.iter().find(|s| s.algorithm == algo).unwrap().pinned_runs;
+
+        assert_eq!(pinned_runs("md5"), Some(1));
+        // older logs without the column say nothing either way
+        assert_eq!(pinned_runs("sha1"), None);
+    }
+
+    #[test]
+    fn test_report_shows_keyspace_searched() {
+        let searched = |keyspace_fraction| BenchmarkRecord { keyspace_fraction, ..record("md5", 500.0) };
+        let runs = vec![searched(Some(0.02)), searched(Some(1.0)), searched(Some(0.5)), searched(None), record("sha1", 500.0)];
+        let summaries = summarize_runs(&runs, None, 0.0).unwrap();
+        assert_eq!(summaries[0].median_keyspace_fraction, Some(0.5));
+        assert_eq!(summaries[1].median_keyspace_fraction, None);
+
+        let table = markdown_report(&summaries, runs.len());
+        assert!(table.contains("| 4/4 (50.00%) |"), "{}", table);
+        assert!(table.contains("| 1/1 |"), "{}", table);
+    }
+
+    #[test]
+    fn test_report_by_length() {
+        let row = |found: bool, length: &str, found_in_s: &str| BenchmarkRecord {
+            found,
+            password_length: length.to_string(),
+            found_in_s: found_in_s.to_string(),
+            ..record("md5", 500.0)
+        };
+        let runs = vec![
+            row(true, "6", "9.0"),
+            row(true, "3", "0.5"),
+            row(true, " 3 ", "0.1"),
+            row(true, "3", "0.2"),
+            row(true, "6", "3.0"),
+            row(true, "8", ""),
+            // not found, blank or garbled lengths don't count
+            row(false, "", ""),
+            row(true, "", "1.0"),
+            row(true, "abc", "1.0"),
+        ];
+
+        let lengths = summarize_by_length(&runs);
+        assert_eq!(lengths, vec![
+            LengthSummary { length: 3, found: 3, median_found_in_s: Some(0.2) },
+            LengthSummary { length: 6, found: 2, median_found_in_s: Some(6.0) },
+            LengthSummary { length: 8, found: 1, median_found_in_s: None },
+        ]);
+
+        let table = markdown_length_table(&lengths);
+        assert!(table.contains("| 3 | 3 | 0.20s |"), "{}", table);
+        assert!(table.contains("| 8 | 1 | - |"), "{}", table);
+    }
+
+    #[test]
+    fn test_report_relative_cost_from_medians() {
+        let runs = vec![
+            record("md5", 400.0),
+            record("md5", 600.0),
+            record("md5", 500.0),
+            record("sha256", 100.0),
+            record("blitzhash", 1000.0),
+        ];
+
+        let summaries = summarize_runs(&runs, Some("md5"), 0.0).unwrap();
+        let cost = |algo: &str| summaries.iter().find(|s| s.algorithm == algo).unwrap().relative_cost;
+
+        assert_eq!(cost("md5"), Some(1.0));
+        assert_eq!(cost("sha256"), Some(5.0));
+        assert_eq!(cost("blitzhash"), Some(0.5));
+
+        // aliases resolve to the canonical name
+        let summaries = summarize_runs(&runs, Some("blitz"), 0.0).unwrap();
+        assert_eq!(summaries[0].algorithm, "blitzhash");
+        assert_eq!(summaries[0].relative_cost, Some(1.0));
+    }
+
+    #[test]
+    fn test_report_unknown_baseline_errors() {
+        let runs = vec![record("md5", 400.0)];
+        assert!(summarize_runs(&runs, Some("sha1"), 0.0).is_err());
+        assert!(summarize_runs(&runs, None, 0.0).unwrap()[0].relative_cost.is_none());
+    }
+
+    #[test]
+    fn test_report_warns_on_mislabeled_data() {
+        let clean = vec![record("md5", 1000.0), record("md5", 1200.0), record("sha256", 10.0)];
+        assert!(data_warnings(&clean).is_empty());
+
+        // a slow kdf's rows logged under md5
+        let mislabeled = vec![record("md5", 1_000_000.0), record("md5", 900_000.0), record("md5", 50.0)];
+        let warnings = data_warnings(&mislabeled);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("md5 runs range from"), "{}", warnings[0]);
+
+        let placeholders: Vec<BenchmarkRecord> = [400.0, 410.0].iter()
+            .map(|&hps| BenchmarkRecord {
+                strategy: "dictionary".to_string(),
+                workers: 8,
+                keyspace_size: "unknown".to_string(),
+                ..record("md5", hps)
+            })
+            .collect();
+        assert!(data_warnings(&placeholders)[0].contains("older logger"));
+
+        let one_label: Vec<BenchmarkRecord> = ["100", "1000000"].iter()
+            .map(|k| BenchmarkRecord { keyspace_size: k.to_string(), ..record("md5", 500.0) })
+            .collect();
+        assert!(data_warnings(&one_label)[0].starts_with("2 different keyspace sizes"));
+
+        // sizes past u64, as logged for a long mask
+        let huge: Vec<BenchmarkRecord> = [26u128.pow(14), 26u128.pow(15)].iter()
+            .map(|k| BenchmarkRecord { keyspace_size: k.to_string(), ..record("md5", 500.0) })
+            .collect();
+        assert!(data_warnings(&huge)[0].starts_with("2 different keyspace sizes"));
+    }
+
+    #[test]
+    fn test_report_trim_drops_outliers() {
+        // eight steady runs around 1000 H/s, one stall and one spike
+        let mut runs: Vec<BenchmarkRecord> = [980.0, 990.0, 995.0, 1000.0, 1000.0, 1005.0, 1010.0, 1020.0]
+            .iter()
+            .map(|&hps| record("md5", hps))
+            .collect();
+        runs.push(record("md5", 10.0));
+        runs.push(record("md5", 50_000.0));
+
+        let raw = &summarize_runs(&runs, None, 0.0).unwrap()[0];
+        assert_eq!(raw.peak_hashes_per_s, 50_000.0);
+        assert_eq!(raw.trimmed_mean_hashes_per_s, None);
+        assert_eq!(raw.trimmed_runs, 0);
+
+        let trimmed = &summarize_runs(&runs, None, 0.1).unwrap()[0];
+        assert_eq!(trimmed.runs, 10);
+        assert_eq!(trimmed.trimmed_runs, 2);
+        assert_eq!(trimmed.peak_hashes_per_s, 1020.0);
+        assert_eq!(trimmed.trimmed_mean_hashes_per_s, Some(1000.0));
+        // symmetric trimming never moves the median
+        assert_eq!(trimmed.median_hashes_per_s, raw.median_hashes_per_s);
+
+        assert!(summarize_runs(&runs, None, 0.5).is_err());
+    }
+
+    #[test]
+    fn test_truncation_caps_generators() {
+        let bcrypt = Target {
+            id: "b".to_string(),
+            username: "u".to_string(),
+            algorithm: Algorithm::Bcrypt,
+            hash: hasher::hash_phc(Algorithm::Bcrypt, b"x", &[0u8; 16]).unwrap(),
+            ..Default::default()
+        };
+        let mut md5 = bcrypt.clone();
+        md5.algorithm = Algorithm::Md5;
+
+        assert_eq!(truncation_len(std::slice::from_ref(&bcrypt)), Some(72));
+        // one non-truncating target means every length matters
+        assert_eq!(truncation_len(&[bcrypt, md5]), None);
+
+        let options = |strategy, mask: Option<&str>| GeneratorOptions {
+            strategy,
+            mask: mask.map(str::to_string),
+            charset: Some("ab".to_string()),
+            min_len: 1,
+            max_len: 5,
+            max_candidate_len: Some(3),
+            ..Default::default()
+        };
+
+        // brute force stops at the truncation length: 2 + 4 + 8
+        let brute = create_generator(&options(Strategy::Brute, None)).unwrap();
+        assert_eq!(brute.estimated_size(), Some(14));
+
+        // mask positions past it are dropped, so each prefix is tried once
+        let mut mask = create_generator(&options(Strategy::Mask, Some("?d?d?d?d?d"))).unwrap();
+        assert_eq!(mask.estimated_size(), Some(1000));
+        assert_eq!(mask.next_batch(1).unwrap()[0], b"000");
+    }
+
+    #[test]
+    fn test_repeat_summary_over_three_runs() {
+        let target = Target {
+            id: "t".to_string(),
+            username: "u".to_string(),
+            algorithm: Algorithm::Md5,
+            hash: hex::encode(hasher::create_hasher(Algorithm::Md5).hash(b"b7")),
+            ..Default::default()
+        };
+
+        let results: Vec<CrackingResult> = (0..3)
+            .map(|_| {
+                let generator = Box::new(MaskGenerator::new("?l?d").unwrap());
+                Engine::new(vec![target.clone()], generator, 2, 64).run(|_| {}).unwrap()
+            })
+            .collect();
+
+        let summary = summarize_repeats(&results);
+        assert_eq!(summary.runs, 3);
+        assert_eq!(summary.found_range, (1, 1));
+        assert_eq!(summary.targets_total, 1);
+        assert!(summary.min_hashes_per_s <= summary.median_hashes_per_s);
+        assert!(summary.median_hashes_per_s <= summary.peak_hashes_per_s);
+    }
+
+    #[test]
+    fn test_confidence_interval_stopping_rule() {
+        // mean 100, sample variance 5: half width t(4) * sqrt(5 / 5)
+        let ci = ConfidenceInterval::of(&[97.0, 99.0, 101.0, 103.0, 100.0]).unwrap();
+        assert!((ci.mean - 100.0).abs() < 1e-9);
+        assert!((ci.half_width - 2.776).abs() < 1e-9);
+        assert!((ci.relative_width() - 5.552).abs() < 1e-9);
+        assert!(ConfidenceInterval::of(&[100.0]).is_none());
+
+        // too few runs never stop, however close they are
+        assert!(!ci_converged(&[100.0, 100.0], 5.0));
+        assert!(ci_converged(&[100.0, 100.0, 100.0], 5.0));
+
+        // noisy runs keep going until more samples narrow the interval
+        let noisy = [90.0, 110.0, 95.0, 105.0];
+        assert!(!ci_converged(&noisy, 5.0));
+        let settled: Vec<f64> = noisy.iter().copied().cycle().take(80).collect();
+        assert!(ci_converged(&settled, 5.0));
+        assert!(!ci_converged(&settled, 1.0));
+    }
+
+    #[test]
+    fn test_shuffle_is_deterministic_for_a_seed() {
+        let targets = tools::generate_demo_targets(
+            &(0..20).map(|n| format!("pw{}", n)).collect::<Vec<_>>(),
+            &[Algorithm::Md5],
+            Default::default(),
+        ).unwrap();
+        let ids = |targets: &[Target]| targets.iter().map(|t| t.id.clone()).collect::<Vec<_>>();
+        let shuffled = |seed| {
+            let mut copy = targets.clone();
+            shuffle_target_order(&mut copy, seed);
+            ids(&copy)
+        };
+        
+        assert_eq!(shuffled(7), shuffled(7));
+        assert_ne!(shuffled(7), shuffled(8));
+        assert_ne!(shuffled(7), ids(&targets));
+        
+        // a permutation: nothing lost or duplicated
+        let mut sorted = shuffled(7);
+        sorted.sort();
+        let mut original = ids(&targets);
+        original.sort();
+        assert_eq!(sorted, original);
+    }
+    
+    #[test]
+    fn test_explain_keyspace_breakdown() {
+        let mask = MaskGenerator::new("?l?l?d!").unwrap();
+        let positions: Vec<(String, usize)> = mask.charsets().iter()
+            .map(|cs| (charset_label(&cs.chars, DEFAULT_CHARSET), cs.chars.len()))
+            .collect();
+        assert_eq!(keyspace_breakdown(&positions, mask.positions()), vec![
+            "    pos  charset   size  keyspace",
+            "      1  ?l          26  26",
+            "      2  ?l          26  676",
+            "      3  ?d          10  6760",
+            "      4  '!'          1  6760",
+        ]);
+        
+        // brute force counts every length from min_len up
+        let positions = vec![("charset".to_string(), 10); 3];
+        let lines = keyspace_breakdown(&positions, 2);
+        assert_eq!(lines[3], "      3  charset     10  1000");
+        assert_eq!(lines.last().unwrap(), "   lengths 2 to 3 together: 1100");
+    }
+    
+    #[test]
+    fn test_read_passwords_from_any_reader() {
+        let input = std::io::Cursor::new("alpha\n\n  \nbeta\r\ngamma");
+        assert_eq!(read_passwords(input, "input").unwrap(), vec!["alpha", "beta", "gamma"]);
+
+        // a bad line stops the read with an error, not a short list
+        let input = std::io::Cursor::new(b"alpha\nbe\xffta\ngamma\n".to_vec());
+        let err = read_passwords(input, "passwords.txt").unwrap_err();
+        assert!(err.to_string().starts_with("passwords.txt:2: "), "{}", err);
+    }
+}
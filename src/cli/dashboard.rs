@@ -0,0 +1,229 @@
+// full-screen terminal dashboard (--tui) built on ratatui
+
+use anyhow::Result;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Gauge, List, ListItem, Paragraph, Row, Sparkline, Table},
+    Frame, Terminal,
+};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+use std::time::{Duration, Instant};
+
+use crate::cli::ui::UiStream;
+use crate::core::{Statistics, Target};
+
+/// minimum time between redraws - batches can complete far faster than this
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// throughput samples kept for the sparkline
+const HISTORY_LEN: usize = 120;
+
+/// cracked credentials kept in the feed
+const FEED_LEN: usize = 100;
+
+pub struct Dashboard {
+    terminal: Terminal<CrosstermBackend<Box<dyn Write>>>,
+    targets: Vec<(String, String, String)>,
+    cracked: HashMap<String, String>,
+    /// `--mask-output`: passwords shown as `p******d`
+    mask_passwords: bool,
+    feed: VecDeque<String>,
+    history: VecDeque<u64>,
+    last_draw: Option<Instant>,
+}
+
+/// the dashboard draws on stderr (`--progress-to-stderr`); read by the
+/// panic hook, which can't borrow the dashboard
+static ON_STDERR: AtomicBool = AtomicBool::new(false);
+
+fn screen() -> Box<dyn Write> {
+    let stream = if ON_STDERR.load(Ordering::Relaxed) { UiStream::Stderr } else { UiStream::Stdout };
+    stream.writer()
+}
+
+/// put the terminal back the way we found it
+fn restore_terminal() {
+    disable_raw_mode().ok();
+    execute!(screen(), LeaveAlternateScreen, crossterm::cursor::Show).ok();
+}
+
+impl Dashboard {
+    /// switch to the alternate screen; restored on drop, on panic, and on q / ctrl-c
+    pub fn start(targets: &[Target], stream: UiStream) -> Result<Self> {
+        ON_STDERR.store(stream == UiStream::Stderr, Ordering::Relaxed);
+        enable_raw_mode()?;
+        execute!(screen(), EnterAlternateScreen, crossterm::cursor::Hide)?;
+
+        // a panic mid-run would otherwise leave the terminal in the alternate screen
+        static PANIC_HOOK: Once = Once::new();
+        PANIC_HOOK.call_once(|| {
+            let previous_hook = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                restore_terminal();
+                previous_hook(info);
+            }));
+        });
+
+        let terminal = Terminal::new(CrosstermBackend::new(screen()))?;
+
+        Ok(Self {
+            terminal,
+            targets: targets.iter()
+                .map(|t| (t.id.clone(), t.username.clone(), t.algorithm.to_string()))
+                .collect(),
+            cracked: HashMap::new(),
+            mask_passwords: false,
+            feed: VecDeque::with_capacity(FEED_LEN),
+            history: VecDeque::with_capacity(HISTORY_LEN),
+            last_draw: None,
+        })
+    }
+
+    /// show cracked passwords by their first and last character only
+    pub fn with_masked_passwords(mut self, masked: bool) -> Self {
+        self.mask_passwords = masked;
+        self
+    }
+
+    pub fn update(&mut self, stats: &Statistics) {
+        for m in &stats.new_matches {
+            let password = m.display_password(self.mask_passwords);
+            if self.feed.len() == FEED_LEN {
+                self.feed.pop_back();
+            }
+            self.feed.push_front(format!(
+                "[{:>7.2}s] {}@{} ({}) → {}",
+                m.time_seconds, m.username, m.target_id, m.algorithm, password,
+            ));
+            self.cracked.insert(m.target_id.clone(), password);
+        }
+
+        self.handle_input();
+
+        let due = self.last_draw.is_none_or(|t| t.elapsed() >= REDRAW_INTERVAL);
+        if !due && stats.new_matches.is_empty() {
+            return;
+        }
+        self.last_draw = Some(Instant::now());
+
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(stats.current_hashes_per_second as u64);
+
+        let history: Vec<u64> = self.history.iter().copied().collect();
+        let targets = &self.targets;
+        let cracked = &self.cracked;
+        let feed = &self.feed;
+        self.terminal
+            .draw(|frame| draw(frame, stats, targets, cracked, feed, &history))
+            .ok();
+    }
+
+    /// q or ctrl-c stops the attack (twice quits); raw mode swallows the usual sigint
+    fn handle_input(&mut self) {
+        while event::poll(Duration::ZERO).unwrap_or(false) {
+            if let Ok(Event::Key(key)) = event::read() {
+                let ctrl_c = key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if key.code == KeyCode::Char('q') || ctrl_c {
+                    if super::interrupt::stop_requested() {
+                        restore_terminal();
+                        std::process::exit(super::exit_code::INTERRUPTED);
+                    }
+                    super::interrupt::stop_flag().store(true, std::sync::atomic::Ordering::Relaxed);
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+fn draw(
+    frame: &mut Frame,
+    stats: &Statistics,
+    targets: &[(String, String, String)],
+    cracked: &HashMap<String, String>,
+    feed: &VecDeque<String>,
+    history: &[u64],
+) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(6),
+            Constraint::Length(8),
+        ])
+        .split(frame.size());
+
+    let header = Paragraph::new(format!(
+        " ⚡ {}   🎯 {}/{} found   ⏱  {}   guesses: {}   (q to quit)",
+        super::ui::format_hashes_per_sec(stats.current_hashes_per_second),
+        stats.targets_found,
+        stats.targets_total,
+        super::ui::format_duration(stats.elapsed_seconds),
+        stats.guesses_tried,
+    ))
+    .block(Block::default().borders(Borders::ALL).title(" BlitzForge - DEMO MODE "));
+    frame.render_widget(header, rows[0]);
+
+    let (ratio, label) = match stats.progress {
+        Some(p) => (p.clamp(0.0, 1.0), format!("{:.1}% of keyspace", p * 100.0)),
+        None => (0.0, "keyspace size unknown".to_string()),
+    };
+    let gauge = Gauge::default()
+        .block(Block::default().borders(Borders::ALL).title(" Keyspace "))
+        .gauge_style(Style::default().fg(Color::Cyan))
+        .ratio(ratio)
+        .label(label);
+    frame.render_widget(gauge, rows[1]);
+
+    let middle = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+        .split(rows[2]);
+
+    let target_rows: Vec<Row> = targets.iter()
+        .map(|(id, username, algorithm)| {
+            let (status, style) = match cracked.get(id) {
+                Some(password) => (format!("✅ {}", password), Style::default().fg(Color::Green)),
+                None => ("…".to_string(), Style::default().fg(Color::DarkGray)),
+            };
+            Row::new(vec![format!("{}@{}", username, id), algorithm.clone(), status]).style(style)
+        })
+        .collect();
+    let table = Table::new(
+        target_rows,
+        [Constraint::Percentage(45), Constraint::Length(10), Constraint::Min(10)],
+    )
+    .header(Row::new(vec!["target", "algo", "status"]).style(Style::default().add_modifier(Modifier::BOLD)))
+    .block(Block::default().borders(Borders::ALL).title(" Targets "));
+    frame.render_widget(table, middle[0]);
+
+    let sparkline = Sparkline::default()
+        .block(Block::default().borders(Borders::ALL).title(" Throughput "))
+        .style(Style::default().fg(Color::Yellow))
+        .data(history);
+    frame.render_widget(sparkline, middle[1]);
+
+    let items: Vec<ListItem> = feed.iter().map(|line| ListItem::new(line.as_str())).collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(" Cracked "))
+        .style(Style::default().fg(Color::Green));
+    frame.render_widget(list, rows[3]);
+}
@@ -0,0 +1,47 @@
+// process exit codes - the scripting contract documented in the readme
+
+use std::fmt;
+
+/// `run` finished and cracked every target (also: any other command succeeded)
+pub const ALL_FOUND: i32 = 0;
+
+/// unexpected failure (unreadable file, bad targets, ...)
+pub const ERROR: i32 = 1;
+
+/// `run` finished but some targets were not cracked
+pub const SOME_UNFOUND: i32 = 2;
+
+/// invalid or missing command-line arguments
+pub const INVALID_ARGS: i32 = 3;
+
+/// stopped by ctrl-c (or q in the dashboard)
+pub const INTERRUPTED: i32 = 4;
+
+/// stopped by sigterm, after writing what the attack had found
+pub const TERMINATED: i32 = 5;
+
+/// an argument problem found after parsing (e.g. `--mask` missing for the
+/// mask strategy), so it exits with `INVALID_ARGS` rather than `ERROR`
+#[derive(Debug)]
+pub struct UsageError(pub String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+pub fn usage_error(message: impl Into<String>) -> anyhow::Error {
+    UsageError(message.into()).into()
+}
+
+/// exit code for a failed command
+pub fn for_error(err: &anyhow::Error) -> i32 {
+    if err.downcast_ref::<UsageError>().is_some() {
+        INVALID_ARGS
+    } else {
+        ERROR
+    }
+}
@@ -0,0 +1,226 @@
+// guided `interactive` mode: prompts for each run option, validating as it
+// goes, then hands the equivalent `run` command line to `run_cracking`
+
+use anyhow::{bail, Result};
+use clap::{Parser, ValueEnum};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+use crate::cli::commands::{self, shell_quote, RunArgs, Strategy, DEFAULT_CHARSET};
+use crate::core::{load_targets, CrackingResult, MaskGenerator};
+
+/// strategies offered by the prompt (hybrid isn't implemented yet)
+const STRATEGIES: [Strategy; 3] = [Strategy::Dictionary, Strategy::Mask, Strategy::Brute];
+
+#[derive(Parser)]
+#[command(name = "blitzforge")]
+struct InteractiveRun {
+    #[command(flatten)]
+    args: RunArgs,
+}
+
+/// reads answers line by line. on a terminal a bad answer re-asks the
+/// question; on piped input it's an error, since every later answer would
+/// otherwise land on the wrong question
+struct Prompter<R, W> {
+    input: R,
+    output: W,
+    tty: bool,
+}
+
+impl<R: BufRead, W: Write> Prompter<R, W> {
+    /// ask until `parse` accepts the answer. an empty answer takes `default`
+    fn ask<T>(
+        &mut self,
+        question: &str,
+        default: Option<&str>,
+        parse: impl Fn(&str) -> Result<T, String>,
+    ) -> Result<T> {
+        loop {
+            if self.tty {
+                match default {
+                    Some(d) => write!(self.output, "{} [{}]: ", question, d)?,
+                    None => write!(self.output, "{}: ", question)?,
+                }
+                self.output.flush()?;
+            }
+
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                bail!("input ended before \"{}\" was answered", question);
+            }
+
+            let answer = match (line.trim(), default) {
+                ("", Some(d)) => d,
+                (answer, _) => answer,
+            };
+
+            match parse(answer) {
+                Ok(value) => return Ok(value),
+                Err(e) if self.tty => writeln!(self.output, "  ❌ {}", e)?,
+                Err(e) => bail!("{}: {}", question, e),
+            }
+        }
+    }
+}
+
+fn parse_targets(answer: &str) -> Result<String, String> {
+    let targets = load_targets(Path::new(answer)).map_err(|e| e.to_string())?;
+    if targets.is_empty() {
+        return Err("no targets found in file".to_string());
+    }
+    Ok(answer.to_string())
+}
+
+fn parse_strategy(answer: &str) -> Result<Strategy, String> {
+    let strategy = Strategy::from_str(answer, true)?;
+    if !STRATEGIES.contains(&strategy) {
+        return Err(format!("{} strategy not yet implemented", answer));
+    }
+    Ok(strategy)
+}
+
+fn parse_wordlist(answer: &str) -> Result<String, String> {
+    if answer.split(',').all(|p| Path::new(p.trim()).exists()) {
+        Ok(answer.to_string())
+    } else {
+        Err(format!("no such file or directory: {}", answer))
+    }
+}
+
+fn parse_mask(answer: &str) -> Result<String, String> {
+    MaskGenerator::new(answer).map_err(|e| e.to_string())?;
+    Ok(answer.to_string())
+}
+
+fn parse_len(answer: &str) -> Result<usize, String> {
+    match answer.parse::<usize>() {
+        Ok(0) | Err(_) => Err("enter a positive whole number".to_string()),
+        Ok(n) => Ok(n),
+    }
+}
+
+/// walk through the prompts and return the equivalent `run` arguments
+fn build_run_argv<R: BufRead, W: Write>(prompter: &mut Prompter<R, W>) -> Result<Vec<String>> {
+    let mut argv = vec!["run".to_string()];
+
+    let targets = prompter.ask("Targets file (json or .bft)", Some("targets.json"), parse_targets)?;
+    argv.extend(["--targets".to_string(), targets]);
+
+    let choices: Vec<String> = STRATEGIES.iter()
+        .filter_map(|s| s.to_possible_value())
+        .map(|v| v.get_name().to_string())
+        .collect();
+    let question = format!("Strategy ({})", choices.join("/"));
+    let strategy = prompter.ask(&question, Some("mask"), parse_strategy)?;
+    let name = strategy.to_possible_value().unwrap().get_name().to_string();
+    argv.extend(["--strategy".to_string(), name]);
+
+    match strategy {
+        Strategy::Dictionary => {
+            let wordlist = prompter.ask("Wordlist path(s), comma-separated", None, parse_wordlist)?;
+            argv.extend(["--wordlist".to_string(), wordlist]);
+        }
+        Strategy::Mask => {
+            let mask = prompter.ask("Mask (?l ?u ?d ?s)", Some("?l?l?l?d?d"), parse_mask)?;
+            argv.extend(["--mask".to_string(), mask]);
+        }
+        Strategy::Brute => {
+            let charset = prompter.ask("Charset", Some(DEFAULT_CHARSET), |s| {
+                Ok(s.to_string())
+            })?;
+            let min_len = prompter.ask("Minimum length", Some("1"), parse_len)?;
+            let max_len = prompter.ask("Maximum length", Some("4"), |s| {
+                let n = parse_len(s)?;
+                if n < min_len {
+                    return Err(format!("must be at least the minimum length ({})", min_len));
+                }
+                Ok(n)
+            })?;
+            argv.extend([
+                "--charset".to_string(), charset,
+                "--min-len".to_string(), min_len.to_string(),
+                "--max-len".to_string(), max_len.to_string(),
+            ]);
+        }
+        Strategy::Hybrid | Strategy::Command => unreachable!("not offered by the prompt"),
+    }
+
+    Ok(argv)
+}
+
+pub fn run_interactive() -> Result<CrackingResult> {
+    let stdin = io::stdin();
+    let tty = stdin.is_terminal();
+
+    if tty {
+        println!("🧭 BlitzForge interactive setup - press enter to accept [defaults]\n");
+    }
+
+    let mut prompter = Prompter { input: stdin.lock(), output: io::stdout(), tty };
+    let argv = build_run_argv(&mut prompter)?;
+
+    let command: Vec<String> = argv.iter().map(|a| shell_quote(a)).collect();
+    println!("\n▶ blitzforge {}\n", command.join(" "));
+
+    let args = InteractiveRun::try_parse_from(argv)?.args;
+    commands::run_cracking(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn scripted(input: &str) -> Prompter<Cursor<Vec<u8>>, Vec<u8>> {
+        Prompter { input: Cursor::new(input.as_bytes().to_vec()), output: Vec::new(), tty: false }
+    }
+
+    fn targets_file(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("blitzforge_{}_{}.json", name, std::process::id()));
+        let targets = crate::tools::generate_demo_targets(&["ab1".to_string()], &[crate::core::Algorithm::Md5], Default::default()).unwrap();
+        std::fs::write(&path, serde_json::to_string(&targets).unwrap()).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn test_scripted_brute_force_answers() {
+        let path = targets_file("brute");
+        let mut prompter = scripted(&format!("{}\nbrute\nabc\n\n3\n", path));
+        let argv = build_run_argv(&mut prompter).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let args = InteractiveRun::try_parse_from(&argv).unwrap().args;
+        assert_eq!(args.charset.as_deref(), Some("abc"));
+        assert_eq!(args.min_len, Some(1));
+        assert_eq!(args.max_len, Some(3));
+    }
+
+    #[test]
+    fn test_scripted_invalid_answer_errors() {
+        let path = targets_file("invalid");
+        let mut prompter = scripted(&format!("{}\nmask\n?q\n", path));
+        let err = build_run_argv(&mut prompter).unwrap_err();
+        std::fs::remove_file(&path).ok();
+        assert!(err.to_string().starts_with("Mask"), "{}", err);
+    }
+
+    #[test]
+    fn test_terminal_reasks_after_invalid_answer() {
+        let path = targets_file("reask");
+        let mut prompter = scripted(&format!("{}\nhybrid\nmask\n?d?d\n", path));
+        prompter.tty = true;
+        let argv = build_run_argv(&mut prompter).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert!(argv.ends_with(&["--mask".to_string(), "?d?d".to_string()]));
+        let shown = String::from_utf8(prompter.output).unwrap();
+        assert!(shown.contains("hybrid strategy not yet implemented"), "{}", shown);
+    }
+
+    #[test]
+    fn test_input_ending_early_errors() {
+        let mut prompter = scripted("");
+        assert!(build_run_argv(&mut prompter).unwrap_err().to_string().contains("input ended"));
+    }
+}
@@ -0,0 +1,79 @@
+// ctrl-c handling. during an attack the first ctrl-c (or q in the dashboard)
+// stops it after the current batch, so the results and the list of what
+// wasn't cracked still print. a second one, or ctrl-c in any other
+// command, exits straight away. sigterm, what docker and kubernetes send on
+// shutdown, stops an attack the same way but only waits `TERM_GRACE` for it
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use super::exit_code;
+
+/// an attack is running and checks `stop_flag` between batches
+static LISTENING: AtomicBool = AtomicBool::new(false);
+
+/// a sigterm arrived
+static TERMINATED: AtomicBool = AtomicBool::new(false);
+
+/// how long a sigterm'd attack gets to finish its batch and write its
+/// results, log, report and database before the process exits regardless -
+/// inside the 10 seconds docker allows before sigkill
+pub const TERM_GRACE: Duration = Duration::from_secs(8);
+
+/// the flag engines are given with `Engine::with_stop_flag`; calling this
+/// also marks an attack as listening for ctrl-c
+pub fn stop_flag() -> Arc<AtomicBool> {
+    static STOP: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+    LISTENING.store(true, Ordering::Relaxed);
+    Arc::clone(STOP.get_or_init(|| Arc::new(AtomicBool::new(false))))
+}
+
+/// whether a stop was requested - later stages and repeats don't start
+pub fn stop_requested() -> bool {
+    LISTENING.load(Ordering::Relaxed) && stop_flag().load(Ordering::Relaxed)
+}
+
+/// ask a running attack to stop, or exit if nothing is listening or a stop
+/// was already asked for
+pub fn on_ctrl_c() {
+    if !LISTENING.load(Ordering::Relaxed) || stop_flag().swap(true, Ordering::Relaxed) {
+        std::process::exit(exit_code::INTERRUPTED);
+    }
+    eprintln!("\n⛔ stopping after the current batch (ctrl-c again to quit now)");
+}
+
+/// whether the run is stopping because of a sigterm, so it exits with
+/// `TERMINATED` instead of `INTERRUPTED`
+pub fn terminated() -> bool {
+    TERMINATED.load(Ordering::Relaxed)
+}
+
+/// stop a running attack after its current batch, with a watchdog that
+/// exits once `TERM_GRACE` is up. a second sigterm, or one outside an
+/// attack, exits straight away
+pub fn on_sigterm() {
+    if !LISTENING.load(Ordering::Relaxed) || TERMINATED.swap(true, Ordering::Relaxed) {
+        std::process::exit(exit_code::TERMINATED);
+    }
+    stop_flag().store(true, Ordering::Relaxed);
+    eprintln!("\n⛔ SIGTERM: stopping after the current batch ({}s grace)", TERM_GRACE.as_secs());
+    
+    std::thread::spawn(|| {
+        std::thread::sleep(TERM_GRACE);
+        eprintln!("⛔ grace period over, exiting without the final results");
+        std::process::exit(exit_code::TERMINATED);
+    });
+}
+
+/// route sigterm to `on_sigterm` from a background thread
+#[cfg(unix)]
+pub fn watch_sigterm() -> std::io::Result<()> {
+    let mut signals = signal_hook::iterator::Signals::new([signal_hook::consts::SIGTERM])?;
+    std::thread::spawn(move || {
+        for _ in signals.forever() {
+            on_sigterm();
+        }
+    });
+    Ok(())
+}
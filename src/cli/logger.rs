@@ -36,6 +36,9 @@ impl BenchmarkLogger {
                 "found",
                 "password_length",
                 "found_in_s",
+                "pinned_threads",
+                "shuffle_seed",
+                "keyspace_fraction",
             ])?;
             writer.flush()?;
         }
@@ -43,8 +46,19 @@ impl BenchmarkLogger {
         Ok(Self { writer })
     }
     
-    pub fn log_result(&mut self, result: &CrackingResult, targets: &[Target]) -> Result<()> {
+    /// one row per target; `strategy`, `workers`, `keyspace` and
+    /// `shuffle_seed` (for `--shuffle-targets`) describe how the run was configured
+    pub fn log_result(
+        &mut self,
+        result: &CrackingResult,
+        targets: &[Target],
+        strategy: &str,
+        workers: usize,
+        keyspace: Option<u128>,
+        shuffle_seed: Option<u64>,
+    ) -> Result<()> {
         let timestamp = Utc::now().to_rfc3339();
+        let fraction = keyspace_fraction(result.statistics.guesses_tried, keyspace);
         
         // log each target (found or not)
         for target in targets {
@@ -61,19 +75,69 @@ impl BenchmarkLogger {
                 &timestamp,
                 &target.id,
                 &target.algorithm.to_string(),
-                "dictionary", // todo: pass actual strategy
-                &"8".to_string(), // todo: pass actual worker count
-                &"unknown".to_string(), // todo: pass actual keyspace
+                strategy,
+                &workers.to_string(),
+                &keyspace.map_or_else(|| "unknown".to_string(), |k| k.to_string()),
                 &result.statistics.guesses_tried.to_string(),
                 &result.total_time.to_string(),
                 &result.statistics.hashes_per_second.to_string(),
                 &found.to_string(),
                 &if found { password_len.to_string() } else { String::new() },
                 &if found { found_in_s.to_string() } else { String::new() },
+                &result.threads_pinned.to_string(),
+                &shuffle_seed.map_or_else(String::new, |s| s.to_string()),
+                &fraction.map_or_else(String::new, |f| f.to_string()),
             ])?;
         }
         
         self.writer.flush()?;
         Ok(())
     }
+}
+
+/// share of the keyspace a run searched before it stopped, so a
+/// time-limited miss reads differently from an exhausted one. `None` when
+/// the size is unknown
+fn keyspace_fraction(guesses_tried: u64, keyspace: Option<u128>) -> Option<f64> {
+    match keyspace {
+        Some(0) | None => None,
+        Some(size) => Some((guesses_tried as f64 / size as f64).min(1.0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Engine, MaskGenerator, RangeGenerator};
+    use crate::core::hasher::create_hasher;
+
+    #[test]
+    fn test_keyspace_fraction_column() {
+        let path = std::env::temp_dir().join(format!("blitzforge_log_fraction_{}.csv", std::process::id()));
+        std::fs::remove_file(&path).ok();
+
+        // not in the keyspace, so the run stops after the 250 of 1000 it's given
+        let target = Target {
+            id: "t".to_string(),
+            username: "u".to_string(),
+            algorithm: crate::core::Algorithm::Md5,
+            hash: hex::encode(create_hasher(crate::core::Algorithm::Md5).hash(b"nope")),
+            ..Default::default()
+        };
+        let generator = RangeGenerator::new(Box::new(MaskGenerator::new("?d?d?d").unwrap()), 0, Some(250));
+        let targets = vec![target];
+        let result = Engine::new(targets.clone(), Box::new(generator), 1, 50).run(|_| {}).unwrap();
+
+        let mut logger = BenchmarkLogger::new(&path).unwrap();
+        logger.log_result(&result, &targets, "mask", 1, Some(1000), None).unwrap();
+        logger.log_result(&result, &targets, "command", 1, None, None).unwrap();
+
+        let mut reader = csv::Reader::from_path(&path).unwrap();
+        let column = reader.headers().unwrap().iter().position(|h| h == "keyspace_fraction").unwrap();
+        let values: Vec<String> = reader.records().map(|r| r.unwrap()[column].to_string()).collect();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(values, ["0.25", ""]);
+        assert_eq!(keyspace_fraction(5000, Some(1000)), Some(1.0));
+    }
 }
\ No newline at end of file
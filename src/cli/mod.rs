@@ -3,6 +3,11 @@
 pub mod commands;
 pub mod ui;
 pub mod logger;
+pub mod dashboard;
+pub mod interactive;
+pub mod exit_code;
+pub mod interrupt;
+pub mod progress_file;
 
 pub use ui::TerminalUI;
 pub use logger::BenchmarkLogger;
\ No newline at end of file
@@ -0,0 +1,122 @@
+// `run --progress-json`: a small json snapshot of the run's statistics,
+// rewritten every refresh for dashboards to poll. each write goes to a
+// temp file renamed over the old one, so a reader never sees half of it
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::core::Statistics;
+
+/// how often the snapshot is rewritten while a run is going
+const REFRESH_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgressSnapshot {
+    /// rfc 3339 time of the snapshot
+    pub updated_at: String,
+    pub guesses_tried: u64,
+    pub hashes_computed: u64,
+    pub hashes_per_second: f64,
+    pub current_hashes_per_second: f64,
+    pub targets_found: usize,
+    pub targets_total: usize,
+    pub elapsed_seconds: f64,
+    /// fraction of the keyspace searched, when the generator can tell
+    pub progress: Option<f64>,
+}
+
+impl From<&Statistics> for ProgressSnapshot {
+    fn from(stats: &Statistics) -> Self {
+        Self {
+            updated_at: chrono::Utc::now().to_rfc3339(),
+            guesses_tried: stats.guesses_tried,
+            hashes_computed: stats.hashes_computed,
+            hashes_per_second: stats.hashes_per_second,
+            current_hashes_per_second: stats.current_hashes_per_second,
+            targets_found: stats.targets_found,
+            targets_total: stats.targets_total,
+            elapsed_seconds: stats.elapsed_seconds,
+            progress: stats.progress,
+        }
+    }
+}
+
+pub struct ProgressFile {
+    path: PathBuf,
+    /// written first, then renamed over `path`
+    temp: PathBuf,
+    last_write: Option<Instant>,
+    /// a failed write is reported once, then the run carries on
+    warned: bool,
+}
+
+impl ProgressFile {
+    pub fn new(path: &Path) -> Self {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        Self {
+            path: path.to_path_buf(),
+            // same directory, so the rename can't cross filesystems
+            temp: path.with_file_name(format!(".{}.tmp", name)),
+            last_write: None,
+            warned: false,
+        }
+    }
+
+    /// rewrite the snapshot now
+    pub fn write(&mut self, stats: &Statistics) -> Result<()> {
+        self.last_write = Some(Instant::now());
+        let json = serde_json::to_string_pretty(&ProgressSnapshot::from(stats))?;
+        fs::write(&self.temp, json)
+            .and_then(|_| fs::rename(&self.temp, &self.path))
+            .with_context(|| format!("failed to write progress file {}", self.path.display()))
+    }
+
+    /// rewrite the snapshot if the last one is a refresh interval old
+    pub fn update(&mut self, stats: &Statistics) {
+        if self.last_write.is_some_and(|at| at.elapsed() < REFRESH_INTERVAL) {
+            return;
+        }
+        if let Err(e) = self.write(stats) {
+            if !std::mem::replace(&mut self.warned, true) {
+                eprintln!("⚠️  {:#}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_is_valid_json_after_update() {
+        let path = std::env::temp_dir().join(format!("blitzforge_progress_{}.json", std::process::id()));
+        let mut file = ProgressFile::new(&path);
+
+        let mut stats = Statistics::new(4);
+        stats.guesses_tried = 1000;
+        stats.targets_found = 1;
+        stats.progress = Some(0.25);
+        file.update(&stats);
+
+        let snapshot: ProgressSnapshot = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(snapshot.guesses_tried, 1000);
+        assert_eq!((snapshot.targets_found, snapshot.targets_total), (1, 4));
+        assert_eq!(snapshot.progress, Some(0.25));
+        assert!(!file.temp.exists());
+
+        // within the refresh interval the file is left alone
+        stats.guesses_tried = 2000;
+        file.update(&stats);
+        let snapshot: ProgressSnapshot = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(snapshot.guesses_tried, 1000);
+
+        file.write(&stats).unwrap();
+        let snapshot: ProgressSnapshot = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(snapshot.guesses_tried, 2000);
+        fs::remove_file(&path).ok();
+    }
+}
@@ -4,68 +4,143 @@ use crossterm::{
     terminal::{Clear, ClearType},
 };
 use indicatif::{ProgressBar, ProgressStyle, MultiProgress};
-use std::io::{stdout, Write};
+use std::io::{stderr, stdout, Write};
 use std::collections::HashMap;
+use std::fmt;
 
-use crate::core::{Statistics, Target, CrackingResult};
+use crate::core::{Statistics, Target, TargetError, CrackingResult, NearMiss, TerminationReason};
+use crate::core::target::mask_password;
+
+/// `println!` to a `TerminalUI`'s stream
+macro_rules! ui_println {
+    ($ui:expr) => { $ui.line(format_args!("")) };
+    ($ui:expr, $($arg:tt)*) => { $ui.line(format_args!($($arg)*)) };
+}
+pub(crate) use ui_println;
+
+/// where the ui writes
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum UiStream {
+    #[default]
+    Stdout,
+    /// leaves stdout to structured output (`run --progress-to-stderr`)
+    Stderr,
+}
+
+impl UiStream {
+    pub fn writer(self) -> Box<dyn Write> {
+        match self {
+            UiStream::Stdout => Box::new(stdout()),
+            UiStream::Stderr => Box::new(stderr()),
+        }
+    }
+}
+
+/// above this many targets the live display leaves out the per-target rows
+pub const SUMMARY_ONLY_ABOVE: usize = 50;
 
 pub struct TerminalUI {
+    stream: UiStream,
+    /// no per-target rows, whatever the target count
+    summary_only: bool,
+    /// show cracked passwords as `p******d` (`--mask-output`)
+    mask_passwords: bool,
     multi_progress: Option<MultiProgress>,
     target_bars: HashMap<String, ProgressBar>,
     stats_bar: Option<ProgressBar>,
+    keyspace_bar: Option<ProgressBar>,
+    /// candidates in the keyspace, which can be more than the bar's u64 length
+    keyspace: Option<u128>,
+    found_bar: Option<ProgressBar>,
 }
 
 impl TerminalUI {
     pub fn new() -> Self {
         Self {
+            stream: UiStream::Stdout,
+            summary_only: false,
+            mask_passwords: false,
             multi_progress: None,
             target_bars: HashMap::new(),
             stats_bar: None,
+            keyspace_bar: None,
+            keyspace: None,
+            found_bar: None,
         }
     }
     
+    pub fn with_stream(mut self, stream: UiStream) -> Self {
+        self.stream = stream;
+        self
+    }
+    
+    pub fn with_summary_only(mut self, summary_only: bool) -> Self {
+        self.summary_only = summary_only;
+        self
+    }
+    
+    /// show cracked passwords by their first and last character only
+    pub fn with_masked_passwords(mut self, masked: bool) -> Self {
+        self.mask_passwords = masked;
+        self
+    }
+    
+    pub fn stream(&self) -> UiStream {
+        self.stream
+    }
+    
+    /// write one line to the ui's stream (see `ui_println!`)
+    pub fn line(&self, args: fmt::Arguments) {
+        writeln!(self.stream.writer(), "{}", args).ok();
+    }
+    
     pub fn print_warning(&self) {
-        let mut stdout = stdout();
+        let mut out = self.stream.writer();
         
-        println!();
+        ui_println!(self);
         execute!(
-            stdout,
+            out,
             SetBackgroundColor(Color::Red),
             SetForegroundColor(Color::White),
             Print("╔═══════════════════════════════════════════════════════════════════╗"),
             ResetColor,
         ).ok();
-        println!();
+        ui_println!(self);
         
         execute!(
-            stdout,
+            out,
             SetBackgroundColor(Color::Red),
             SetForegroundColor(Color::White),
             Print("║           BLITZFORGE - DEMO MODE ACTIVE                           ║"),
             ResetColor,
         ).ok();
-        println!();
+        ui_println!(self);
         
         execute!(
-            stdout,
+            out,
             SetBackgroundColor(Color::Red),
             SetForegroundColor(Color::White),
             Print("║  ⚠️  USE ONLY ON YOUR OWN DEMO TARGETS - LEGAL USE ONLY  ⚠️      ║"),
             ResetColor,
         ).ok();
-        println!();
+        ui_println!(self);
         
         execute!(
-            stdout,
+            out,
             SetBackgroundColor(Color::Red),
             SetForegroundColor(Color::White),
             Print("╚═══════════════════════════════════════════════════════════════════╝"),
             ResetColor,
         ).ok();
-        println!();
+        ui_println!(self);
     }
     
-    pub fn start_display(&mut self, targets: &[Target]) {
+    /// live display: a stats line, then two separate notions of progress -
+    /// how much of the keyspace has been searched, and how many targets are
+    /// cracked - then one status row per target, unless there are more than
+    /// `SUMMARY_ONLY_ABOVE` of them or the ui is summary-only. `keyspace` is
+    /// the number of candidates, if known; otherwise the keyspace row is a spinner
+    pub fn start_display(&mut self, targets: &[Target], keyspace: Option<u128>) {
         let multi = MultiProgress::new();
         
         // create stats bar
@@ -77,16 +152,47 @@ impl TerminalUI {
         );
         self.stats_bar = Some(stats_bar);
         
-        // create progress bar for each target
-        for target in targets {
-            let bar = multi.add(ProgressBar::new(100));
-            let style = ProgressStyle::default_bar()
-                .template(&format!(" [{{bar:30.cyan/blue}}] {{percent:>3}}% {{msg}}"))
+        self.keyspace = keyspace;
+        let keyspace_bar = match keyspace {
+            Some(size) => {
+                // the bar counts in u64; only the message needs the exact size
+                let bar = multi.add(ProgressBar::new(u64::try_from(size).unwrap_or(u64::MAX)));
+                bar.set_style(
+                    ProgressStyle::default_bar()
+                        .template(" Keyspace [{bar:30.cyan/blue}] {percent:>3}% {msg}")
+                        .unwrap()
+                        .progress_chars("█▓▒░ ")
+                );
+                bar
+            }
+            None => {
+                let bar = multi.add(ProgressBar::new_spinner());
+                bar.set_style(
+                    ProgressStyle::default_spinner()
+                        .template(" Keyspace {spinner} {msg}")
+                        .unwrap()
+                );
+                bar
+            }
+        };
+        self.keyspace_bar = Some(keyspace_bar);
+        
+        let found_bar = multi.add(ProgressBar::new(targets.len() as u64));
+        found_bar.set_style(
+            ProgressStyle::default_bar()
+                .template(" Targets  [{bar:30.green/white}] {pos}/{len} cracked")
                 .unwrap()
-                .progress_chars("█▓▒░ ");
-            
-            bar.set_style(style);
-            bar.set_message(format!("[{}@{}] ({})", target.username, target.id, target.algorithm));
+                .progress_chars("█▓▒░ ")
+        );
+        self.found_bar = Some(found_bar);
+        
+        // one status row per target, when there are few enough to read
+        self.target_bars.clear();
+        let per_target = !self.summary_only && targets.len() <= SUMMARY_ONLY_ABOVE;
+        for target in targets.iter().filter(|_| per_target) {
+            let bar = multi.add(ProgressBar::new(1));
+            bar.set_style(ProgressStyle::default_bar().template("{msg}").unwrap());
+            bar.set_message(format!("   ⏳ [{}@{}] ({})", target.username, target.id, target.algorithm));
             
             self.target_bars.insert(target.id.clone(), bar);
         }
@@ -98,22 +204,40 @@ impl TerminalUI {
         // update stats bar
         if let Some(ref stats_bar) = self.stats_bar {
             let msg = format!(
-                "\n⚡ PERFORMANCE: {} | Found: {}/{} | Time: {:02}:{:02}:{:02}",
-                format_hashes_per_sec(stats.hashes_per_second),
-                stats.targets_found,
-                stats.targets_total,
-                (stats.start_time.elapsed().as_secs() / 3600),
-                (stats.start_time.elapsed().as_secs() % 3600) / 60,
-                stats.start_time.elapsed().as_secs() % 60,
+                "\n⚡ PERFORMANCE: {} | Time: {}",
+                format_hashes_per_sec(stats.current_hashes_per_second),
+                format_duration(stats.elapsed_seconds),
             );
             stats_bar.set_message(msg);
         }
         
-        // update target progress (estimated)
-        // note: real implementation would track per-target progress
-        for (_, bar) in &self.target_bars {
-            let progress = (stats.guesses_tried as f64 / 1_000_000.0).min(99.0);
-            bar.set_position(progress as u64);
+        if let Some(ref bar) = self.keyspace_bar {
+            match bar.length() {
+                Some(size) => {
+                    // the generator's own progress is exact under --skip/--limit
+                    let searched = match stats.progress {
+                        Some(fraction) => (fraction * size as f64) as u64,
+                        None => stats.guesses_tried,
+                    };
+                    bar.set_position(searched.min(size));
+                    let total = self.keyspace.unwrap_or(u128::from(size));
+                    bar.set_message(format!("{} / {} candidates", format_number(stats.guesses_tried), format_number(total)));
+                }
+                None => {
+                    bar.set_message(format!("{} candidates tried (size unknown)", format_number(stats.guesses_tried)));
+                    bar.tick();
+                }
+            }
+        }
+        
+        if let Some(ref bar) = self.found_bar {
+            bar.set_position(stats.targets_found as u64);
+        }
+        
+        for m in &stats.new_matches {
+            if let Some(bar) = self.target_bars.get(&m.target_id) {
+                bar.set_message(format!("   ✅ [{}@{}] ({})", m.username, m.target_id, m.algorithm));
+            }
         }
     }
     
@@ -123,38 +247,152 @@ impl TerminalUI {
         }
     }
     
-    pub fn print_results(&self, result: &CrackingResult) {
-        println!("\n");
-        println!("╔═══════════════════════════════════════════════════════════════════╗");
-        println!("║                        CRACKING RESULTS                            ║");
-        println!("╚═══════════════════════════════════════════════════════════════════╝");
+    /// the run's statistics and matches, then what its termination means
+    /// for `unfound`, the targets it didn't crack
+    pub fn print_results(&self, result: &CrackingResult, unfound: &[&Target]) {
+        ui_println!(self, "\n");
+        ui_println!(self, "╔═══════════════════════════════════════════════════════════════════╗");
+        ui_println!(self, "║                        CRACKING RESULTS                            ║");
+        ui_println!(self, "╚═══════════════════════════════════════════════════════════════════╝");
         
-        println!("\n📊 Statistics:");
-        println!("   Total time:       {:.2}s", result.total_time);
-        println!("   Guesses tried:    {}", format_number(result.statistics.guesses_tried));
-        println!("   Hashes computed:  {}", format_number(result.statistics.hashes_computed));
-        println!("   Throughput:       {}", format_hashes_per_sec(result.statistics.hashes_per_second));
+        ui_println!(self, "\n📊 Statistics:");
+        ui_println!(self, "   Total time:       {:.2}s", result.total_time);
+        ui_println!(self, "   Guesses tried:    {}", format_number(result.statistics.guesses_tried));
+        ui_println!(self, "   Hashes computed:  {}", format_number(result.statistics.hashes_computed));
+        ui_println!(self, "   Throughput:       {}", format_hashes_per_sec(result.statistics.hashes_per_second));
         
-        println!("\n🎯 Matches Found: {}/{}", result.matches.len(), result.statistics.targets_total);
+        ui_println!(self, "\n🎯 Matches Found: {}/{}", result.matches.len(), result.statistics.targets_total);
         
         if result.matches.is_empty() {
-            println!("   ❌ No passwords cracked");
+            ui_println!(self, "   ❌ No passwords cracked");
         } else {
             for m in &result.matches {
-                println!("\n   ✅ {}@{}", m.username, m.target_id);
-                println!("      Password:     {}", m.password_string());
-                println!("      Algorithm:    {}", m.algorithm);
-                println!("      Found in:     {:.2}s", m.time_seconds);
-                println!("      After:        {} guesses", format_number(m.guesses_tried));
+                ui_println!(self, "\n   ✅ {}@{}", m.username, m.target_id);
+                ui_println!(self, "      Password:     {}", m.display_password(self.mask_passwords));
+                ui_println!(self, "      Algorithm:    {}", m.algorithm);
+                match m.verified {
+                    Some(true) => ui_println!(self, "      Status:       verified"),
+                    Some(false) => ui_println!(self, "      Status:       ⚠️  collision (unverified)"),
+                    None => {}
+                }
+                match (m.matches_expected(), &m.expected_password) {
+                    (Some(true), _) => ui_println!(self, "      Expected:     ✓ matches the dataset"),
+                    (Some(false), Some(expected)) => ui_println!(self,
+                        "      Expected:     ⚠️  \"{}\" - hash collision or a bug in the tool",
+                        if self.mask_passwords { mask_password(expected) } else { expected.clone() }),
+                    _ => {}
+                }
+                ui_println!(self, "      Found in:     {:.2}s", m.time_seconds);
+                ui_println!(self, "      After:        {} guesses", format_number(m.guesses_tried));
+            }
+        }
+        
+        let checked: Vec<bool> = result.matches.iter().filter_map(|m| m.matches_expected()).collect();
+        if !checked.is_empty() {
+            let wrong = checked.iter().filter(|&&ok| !ok).count();
+            match wrong {
+                0 => ui_println!(self, "\n🧪 Ground truth: all {} checked passwords match", checked.len()),
+                n => ui_println!(self, "\n🧪 Ground truth: ⚠️  {} of {} checked passwords differ from expected_password", n, checked.len()),
+            }
+        }
+        
+        // when every uncracked target was given up on, that says it all
+        if !unfound.is_empty() || result.given_up.is_empty() {
+            ui_println!(self, "\n{}", termination_summary(result.termination, unfound.len(), result.statistics.progress));
+            for target in unfound.iter().take(UNFOUND_LISTED) {
+                ui_println!(self, "   ⏳ {}", target.id);
+            }
+            if unfound.len() > UNFOUND_LISTED {
+                ui_println!(self, "   ... and {} more (--output-unfound lists them all)", unfound.len() - UNFOUND_LISTED);
+            }
+        }
+        
+        if !result.given_up.is_empty() {
+            ui_println!(self, "\n🏳️  Gave up on {} targets (--target-timeout): not cracked, but not ruled out either", result.given_up.len());
+            for id in result.given_up.iter().take(UNFOUND_LISTED) {
+                ui_println!(self, "   ⌛ {}", id);
+            }
+            if result.given_up.len() > UNFOUND_LISTED {
+                ui_println!(self, "   ... and {} more", result.given_up.len() - UNFOUND_LISTED);
             }
         }
         
-        println!();
+        ui_println!(self);
+    }
+    
+    /// `--track-near-misses`: each close miss with its digest, the shared
+    /// prefix split off so it's easy to see how far the match got
+    pub fn print_near_misses(&self, misses: &[NearMiss]) {
+        ui_println!(self, "🎯 Closest Misses: {}", misses.len());
+        for miss in misses {
+            let digest = hex::encode(&miss.digest);
+            let (shared, rest) = digest.split_at(miss.shared_bytes * 2);
+            ui_println!(self, "   {} {:?}: {} byte(s) shared  {}|{}",
+                miss.target_id, String::from_utf8_lossy(&miss.candidate), miss.shared_bytes, shared, rest);
+        }
+        ui_println!(self);
+    }
+    
+    /// targets `--skip-invalid` set aside, each with why
+    pub fn print_errored(&self, errored: &[TargetError]) {
+        ui_println!(self, "🚫 Errored, not attacked: {}", errored.len());
+        for error in errored.iter().take(UNFOUND_LISTED) {
+            ui_println!(self, "   ⚠️  {}", error);
+        }
+        if errored.len() > UNFOUND_LISTED {
+            ui_println!(self, "   ... and {} more (listed in --report-out)", errored.len() - UNFOUND_LISTED);
+        }
+        ui_println!(self);
     }
+    
+    pub fn print_unfound(&self, unfound: &[&Target]) {
+        ui_println!(self, "🔒 Not Cracked: {}", unfound.len());
+        for target in unfound {
+            ui_println!(self, "   ⏳ {}", unfound_line(target));
+        }
+        ui_println!(self);
+    }
+}
+
+/// uncracked targets named under the results; more are summarized
+const UNFOUND_LISTED: usize = 10;
+
+/// what the way a run ended says about the targets it didn't crack. only a
+/// fully searched keyspace rules them out; a stopped run just didn't get to them
+pub fn termination_summary(reason: TerminationReason, unfound: usize, progress: Option<f64>) -> String {
+    let searched = progress
+        .map(|p| format!(" ({:.1}% of the keyspace searched)", p * 100.0))
+        .unwrap_or_default();
+    
+    match reason {
+        TerminationReason::AllFound => "🏁 All targets cracked".to_string(),
+        TerminationReason::Exhausted => format!(
+            "🏁 Keyspace exhausted: the {} remaining targets are NOT in this keyspace", unfound),
+        TerminationReason::TimeLimit => format!(
+            "⏱️  Time limit reached{}: the {} remaining targets may still be in the unsearched part", searched, unfound),
+        TerminationReason::Interrupted => format!(
+            "⛔ Interrupted{}: the {} remaining targets may still be in the unsearched part", searched, unfound),
+        TerminationReason::ThresholdReached => format!(
+            "🎯 Success threshold reached{}: the {} remaining targets weren't searched for any further", searched, unfound),
+    }
+}
+
+/// one-line description of an uncracked target: id, user, algorithm, salt status
+pub fn unfound_line(target: &Target) -> String {
+    format!(
+        "{}\t{}\t{}\t{}",
+        target.id,
+        target.username,
+        target.algorithm,
+        if target.effective_salt().is_empty() { "unsalted" } else { "salted" },
+    )
 }
 
-fn format_number(n: u64) -> String {
-    if n >= 1_000_000_000_000 {
+fn format_number(n: impl Into<u128>) -> String {
+    let n: u128 = n.into();
+    if n >= 1_000_000_000_000_000 {
+        format!("{:.2e}", n as f64)
+    } else if n >= 1_000_000_000_000 {
         format!("{:.2}T", n as f64 / 1_000_000_000_000.0)
     } else if n >= 1_000_000_000 {
         format!("{:.2}B", n as f64 / 1_000_000_000.0)
@@ -167,7 +405,13 @@ fn format_number(n: u64) -> String {
     }
 }
 
-fn format_hashes_per_sec(h: f64) -> String {
+/// hh:mm:ss, whole seconds; hours keep counting past 99
+pub(crate) fn format_duration(secs: f64) -> String {
+    let secs = secs.max(0.0) as u64;
+    format!("{:02}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60)
+}
+
+pub(crate) fn format_hashes_per_sec(h: f64) -> String {
     if h >= 1_000_000_000.0 {
         format!("{:.2} GH/s", h / 1_000_000_000.0)
     } else if h >= 1_000_000.0 {
@@ -177,4 +421,65 @@ fn format_hashes_per_sec(h: f64) -> String {
     } else {
         format!("{:.0} H/s", h)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn targets(count: usize) -> Vec<Target> {
+        let passwords: Vec<String> = (0..count).map(|i| i.to_string()).collect();
+        crate::tools::generate_demo_targets(&passwords, &[crate::core::Algorithm::Md5], Default::default()).unwrap()
+    }
+
+    #[test]
+    fn test_per_target_rows_only_for_few_targets() {
+        let mut ui = TerminalUI::new();
+        ui.start_display(&targets(3), Some(100));
+        assert_eq!(ui.target_bars.len(), 3);
+
+        ui.start_display(&targets(SUMMARY_ONLY_ABOVE + 1), Some(100));
+        assert!(ui.target_bars.is_empty());
+        assert_eq!(ui.found_bar.as_ref().unwrap().length(), Some(SUMMARY_ONLY_ABOVE as u64 + 1));
+
+        let mut ui = TerminalUI::new().with_summary_only(true);
+        ui.start_display(&targets(3), Some(100));
+        assert!(ui.target_bars.is_empty());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0.0), "00:00:00");
+        assert_eq!(format_duration(59.9), "00:00:59");
+        assert_eq!(format_duration(3661.0), "01:01:01");
+        assert_eq!(format_duration(100.0 * 3600.0 + 5.0), "100:00:05");
+        assert_eq!(format_duration(-1.0), "00:00:00");
+    }
+
+    #[test]
+    fn test_format_number_past_u64() {
+        assert_eq!(format_number(999u64), "999");
+        assert_eq!(format_number(2_500_000u64), "2.50M");
+        assert_eq!(format_number(26u128.pow(14)), "6.45e19");
+        assert_eq!(format_number(u128::MAX), "3.40e38");
+    }
+
+    #[test]
+    fn test_termination_summary_per_reason() {
+        assert_eq!(termination_summary(TerminationReason::AllFound, 0, Some(0.2)), "🏁 All targets cracked");
+
+        let exhausted = termination_summary(TerminationReason::Exhausted, 3, Some(1.0));
+        assert!(exhausted.contains("Keyspace exhausted"), "{}", exhausted);
+        assert!(exhausted.contains("3 remaining targets are NOT in this keyspace"), "{}", exhausted);
+
+        let timed_out = termination_summary(TerminationReason::TimeLimit, 2, Some(0.25));
+        assert!(timed_out.starts_with("⏱️  Time limit reached (25.0% of the keyspace searched)"), "{}", timed_out);
+        assert!(timed_out.contains("may still be"), "{}", timed_out);
+
+        let interrupted = termination_summary(TerminationReason::Interrupted, 1, None);
+        assert!(interrupted.starts_with("⛔ Interrupted: the 1 remaining"), "{}", interrupted);
+        assert!(!interrupted.contains("NOT"), "{}", interrupted);
+
+        let threshold = termination_summary(TerminationReason::ThresholdReached, 7, Some(0.5));
+        assert!(threshold.starts_with("🎯 Success threshold reached (50.0%"), "{}", threshold);
+    }
+}
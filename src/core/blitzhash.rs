@@ -1,11 +1,63 @@
 //! blitzhash - high performance hash function
 //! **warning: not cryptographically secure - demo only**
+//!
+//! output format: the 32-byte digest is the four 64-bit state lanes in
+//! order, each written little-endian, on every platform. input bytes are
+//! likewise read as little-endian words. tools that emit each lane
+//! big-endian can be matched with [`Endian::Big`] / `--blitzhash-endian be`
 
 const K1: u64 = 0x517cc1b727220a95;
 const K2: u64 = 0x85ebca6b2f3c8b51;
 const K3: u64 = 0xc2b2ae3d27d4eb4f;
 const K4: u64 = 0x165667b19e3779f9;
 
+/// byte order of each 64-bit lane in a digest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Endian {
+    /// canonical blitzhash output
+    #[default]
+    Little,
+    Big,
+}
+
+impl std::str::FromStr for Endian {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "le" | "little" => Ok(Endian::Little),
+            "be" | "big" => Ok(Endian::Big),
+            _ => Err(format!("unknown endianness: {} (expected le or be)", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Endian {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Endian::Little => write!(f, "le"),
+            Endian::Big => write!(f, "be"),
+        }
+    }
+}
+
+/// convert a digest between the canonical layout and `endian`. swapping
+/// lanes is its own inverse, so this works in both directions
+pub fn reorder_lanes(digest: &[u8], endian: Endian) -> Vec<u8> {
+    match endian {
+        Endian::Little => digest.to_vec(),
+        Endian::Big => digest.chunks(8)
+            .flat_map(|lane| lane.iter().rev().copied())
+            .collect(),
+    }
+}
+
+/// known digest of `blitz_hash(0, b"password")`, canonical layout
+pub const PINNED_PASSWORD_LE: &str = "d405ceac6af71302bcac42ed2020020d00911ba2ea9b4a2f07339dfa8cf2f59b";
+
+/// the same digest with each lane big-endian
+pub const PINNED_PASSWORD_BE: &str = "0213f76aacce05d40d022020ed42acbc2f4a9beaa21b91009bf5f28cfa9d3307";
+
 /// fast unaligned u64 read - no bounds checks
 #[inline(always)]
 unsafe fn read_u64_unaligned(ptr: *const u8) -> u64 {
@@ -292,6 +344,17 @@ mod tests {
         assert_eq!(oneshot, streamed);
     }
 
+    #[test]
+    fn test_output_bytes_pinned() {
+        // locks down the digest format - any change here breaks existing targets
+        let le = blitz_hash(0, b"password");
+        assert_eq!(hex::encode(le), PINNED_PASSWORD_LE);
+
+        let be = reorder_lanes(&le, Endian::Big);
+        assert_eq!(hex::encode(&be), PINNED_PASSWORD_BE);
+        assert_eq!(reorder_lanes(&be, Endian::Big), le.to_vec());
+    }
+
     #[test]
     fn test_empty_input() {
         let h = blitz_hash(0, b"");
@@ -0,0 +1,166 @@
+// candidate batches packed into one reusable buffer. `Generator::next_batch`
+// allocates a vec per candidate; `CandidateSource::next_into` refills the
+// same `CandidateBatch` every time, so a warmed-up batch costs no allocation
+
+use rayon::prelude::*;
+
+use crate::core::Generator;
+
+/// candidates stored back to back in one byte buffer, with the offset each
+/// one ends at. `clear` keeps both allocations for the next fill
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CandidateBatch {
+    data: Vec<u8>,
+    ends: Vec<usize>,
+    capacity: usize,
+}
+
+impl CandidateBatch {
+    /// an empty batch that sources fill with up to `capacity` candidates
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            data: Vec::new(),
+            ends: Vec::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// how many candidates a fill stops at
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.ends.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// room for no more candidates
+    pub fn is_full(&self) -> bool {
+        self.len() >= self.capacity
+    }
+
+    pub fn push(&mut self, candidate: &[u8]) {
+        self.data.extend_from_slice(candidate);
+        self.ends.push(self.data.len());
+    }
+
+    /// append one candidate built byte by byte, without a temporary vec
+    pub fn push_bytes(&mut self, bytes: impl IntoIterator<Item = u8>) {
+        self.data.extend(bytes);
+        self.ends.push(self.data.len());
+    }
+
+    pub fn get(&self, index: usize) -> &[u8] {
+        let start = match index {
+            0 => 0,
+            i => self.ends[i - 1],
+        };
+        &self.data[start..self.ends[index]]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> + '_ {
+        (0..self.len()).map(|i| self.get(i))
+    }
+
+    pub fn par_iter(&self) -> impl IndexedParallelIterator<Item = &[u8]> + '_ {
+        (0..self.len()).into_par_iter().map(|i| self.get(i))
+    }
+
+    /// the candidates as the vec-per-candidate shape `Generator` uses
+    pub fn to_vecs(&self) -> Vec<Vec<u8>> {
+        self.iter().map(<[u8]>::to_vec).collect()
+    }
+}
+
+/// a keyspace that fills a caller-owned batch rather than returning a new
+/// one. every `Generator` is a source through the blanket impl below; the
+/// index-based generators (mask, brute force) fill the buffer directly
+pub trait CandidateSource: Send {
+    /// clear `buf` and fill it with up to `buf.capacity()` candidates.
+    /// `false` once the keyspace is exhausted and nothing was added
+    fn next_into(&mut self, buf: &mut CandidateBatch) -> bool;
+}
+
+impl<G: Generator + ?Sized> CandidateSource for G {
+    fn next_into(&mut self, buf: &mut CandidateBatch) -> bool {
+        buf.clear();
+        self.fill_batch(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{BruteForceGenerator, MaskGenerator, MemoryGenerator, RangeGenerator};
+
+    /// every candidate `next_batch` produces, in order
+    fn by_next_batch(mut generator: impl Generator, size: usize) -> Vec<Vec<u8>> {
+        let mut all = Vec::new();
+        while let Some(batch) = generator.next_batch(size) {
+            all.extend(batch);
+        }
+        all
+    }
+
+    /// every candidate `next_into` produces, reusing one buffer
+    fn by_next_into(mut source: impl CandidateSource, size: usize) -> Vec<Vec<u8>> {
+        let mut buf = CandidateBatch::new(size);
+        let mut all = Vec::new();
+        while source.next_into(&mut buf) {
+            assert!(buf.len() <= size);
+            all.extend(buf.to_vecs());
+        }
+        all
+    }
+
+    #[test]
+    fn test_batch_packs_candidates() {
+        let mut buf = CandidateBatch::new(3);
+        buf.push(b"ab");
+        buf.push(b"");
+        buf.push_bytes(b"xyz".iter().copied());
+
+        assert!(buf.is_full());
+        assert_eq!(buf.get(0), b"ab");
+        assert_eq!(buf.get(1), b"");
+        assert_eq!(buf.to_vecs(), vec![b"ab".to_vec(), Vec::new(), b"xyz".to_vec()]);
+        assert_eq!(buf.par_iter().map(<[u8]>::len).sum::<usize>(), 5);
+
+        buf.clear();
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_both_paths_produce_identical_candidates() {
+        // batch sizes that do and don't divide the keyspace
+        for size in [1, 7, 64, 10_000] {
+            let mask = || MaskGenerator::new("?l?d?d").unwrap();
+            assert_eq!(by_next_into(mask(), size), by_next_batch(mask(), size));
+
+            let brute = || BruteForceGenerator::new("abc", 1, 4);
+            assert_eq!(by_next_into(brute(), size), by_next_batch(brute(), size));
+
+            let range = || RangeGenerator::new(Box::new(mask()), 123, Some(1000));
+            assert_eq!(by_next_into(range(), size), by_next_batch(range(), size));
+
+            // the blanket adapter over a vec-per-candidate generator
+            let words = || MemoryGenerator::new((0..100u8).map(|n| vec![n; n as usize % 5]).collect());
+            assert_eq!(by_next_into(words(), size), by_next_batch(words(), size));
+        }
+
+        assert_eq!(by_next_into(MaskGenerator::new("?d").unwrap(), 4).len(), 10);
+    }
+}
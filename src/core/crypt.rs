@@ -0,0 +1,202 @@
+// crypt-family hash strings (`$1$`, `$5$`, `$6$`, `$2b$`) as found in
+// /etc/shadow and most web dumps. the salt and cost live in the string
+// itself, so targets never need a separate `salt` field for them
+
+use crate::core::Algorithm;
+
+/// characters crypt uses for salts and digests
+const CRYPT_ALPHABET: &str = "./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// lowest rounds sha-crypt accepts - keeps demo cracking quick
+pub const SHA_CRYPT_DEMO_ROUNDS: u32 = 1000;
+
+/// a crypt string split into its parts
+#[derive(Debug, Clone, PartialEq)]
+pub struct CryptHash<'a> {
+    pub algorithm: Algorithm,
+    /// scheme id between the first two `$` (`1`, `5`, `6`, `2a`, `2b`, `2y`)
+    pub id: &'a str,
+    pub salt: &'a str,
+    /// `rounds=N` for sha-crypt, the two-digit cost for bcrypt
+    pub params: Option<&'a str>,
+    pub digest: &'a str,
+}
+
+impl CryptHash<'_> {
+    /// everything before the digest - what crypt needs to rehash a candidate
+    pub fn setting(&self) -> String {
+        match (self.algorithm, self.params) {
+            (Algorithm::Bcrypt, Some(cost)) => format!("${}${}${}", self.id, cost, self.salt),
+            (_, Some(params)) => format!("${}${}${}", self.id, params, self.salt),
+            (_, None) => format!("${}${}", self.id, self.salt),
+        }
+    }
+}
+
+/// split a crypt string into algorithm, salt, parameters and digest,
+/// checking each part's length and alphabet
+pub fn parse_crypt(hash: &str) -> Result<CryptHash<'_>, String> {
+    let Some(rest) = hash.strip_prefix('$') else {
+        return Err("crypt hash must start with '$'".to_string());
+    };
+    let (id, rest) = rest.split_once('$')
+        .ok_or_else(|| "crypt hash has no scheme id".to_string())?;
+
+    let parsed = match id {
+        "1" => {
+            let (salt, digest) = split_salt(rest)?;
+            CryptHash { algorithm: Algorithm::Md5Crypt, id, salt, params: None, digest }
+        }
+        "5" | "6" => {
+            let (params, rest) = match rest.split_once('$') {
+                Some((params, rest)) if params.starts_with("rounds=") => (Some(params), rest),
+                _ => (None, rest),
+            };
+            if let Some(rounds) = params.and_then(|p| p.strip_prefix("rounds=")) {
+                rounds.parse::<u32>().map_err(|_| format!("invalid rounds '{}'", rounds))?;
+            }
+            let (salt, digest) = split_salt(rest)?;
+            let algorithm = if id == "5" { Algorithm::Sha256Crypt } else { Algorithm::Sha512Crypt };
+            CryptHash { algorithm, id, salt, params, digest }
+        }
+        "2a" | "2b" | "2y" => {
+            let (cost, rest) = rest.split_once('$')
+                .ok_or_else(|| "bcrypt hash has no cost".to_string())?;
+            if cost.len() != 2 || cost.parse::<u32>().is_err() {
+                return Err(format!("invalid bcrypt cost '{}'", cost));
+            }
+            if rest.len() != 53 || !rest.is_char_boundary(22) {
+                return Err(format!("bcrypt salt and digest should be 53 chars, got {}", rest.len()));
+            }
+            let (salt, digest) = rest.split_at(22);
+            CryptHash { algorithm: Algorithm::Bcrypt, id, salt, params: Some(cost), digest }
+        }
+        other => return Err(format!("unsupported crypt scheme ${}$", other)),
+    };
+
+    let (max_salt, digest_len) = match parsed.algorithm {
+        Algorithm::Md5Crypt => (8, 22),
+        Algorithm::Sha256Crypt => (16, 43),
+        Algorithm::Sha512Crypt => (16, 86),
+        _ => (22, 31),
+    };
+    if parsed.salt.len() > max_salt {
+        return Err(format!("{} salt is at most {} chars, got {}", parsed.algorithm, max_salt, parsed.salt.len()));
+    }
+    if parsed.digest.len() != digest_len {
+        return Err(format!("{} digest should be {} chars, got {}", parsed.algorithm, digest_len, parsed.digest.len()));
+    }
+    if let Some(c) = parsed.salt.chars().chain(parsed.digest.chars()).find(|c| !CRYPT_ALPHABET.contains(*c)) {
+        return Err(format!("invalid character '{}' in crypt hash", c));
+    }
+
+    Ok(parsed)
+}
+
+fn split_salt(rest: &str) -> Result<(&str, &str), String> {
+    rest.split_once('$')
+        .ok_or_else(|| "crypt hash has no digest after the salt".to_string())
+}
+
+/// hash `password` under the salt and parameters of `setting` (a crypt
+/// string or just its `$id$...$salt` prefix)
+pub fn crypt(algorithm: Algorithm, setting: &str, password: &[u8]) -> Option<String> {
+    if !matches!(algorithm, Algorithm::Md5Crypt | Algorithm::Sha256Crypt | Algorithm::Sha512Crypt) {
+        return None;
+    }
+    // `unix::crypt` picks the scheme from the setting's `$id$` - the same
+    // one as `algorithm`, for settings from `parse_crypt` or `demo_setting`
+    pwhash::unix::crypt(password, setting).ok()
+}
+
+/// verify a candidate by rehashing it with the target's own salt and rounds
+pub fn verify_crypt(password: &[u8], hash: &str) -> bool {
+    let Ok(parsed) = parse_crypt(hash) else {
+        return false;
+    };
+
+    if parsed.algorithm == Algorithm::Bcrypt {
+        return bcrypt::verify(password, hash).unwrap_or(false);
+    }
+
+    crypt(parsed.algorithm, &parsed.setting(), password).as_deref() == Some(hash)
+}
+
+/// crypt setting for a new demo hash; the salt bytes are mapped onto the
+/// crypt alphabet and cut to the scheme's maximum length
+pub fn demo_setting(algorithm: Algorithm, salt: &[u8]) -> Option<String> {
+    let alphabet = CRYPT_ALPHABET.as_bytes();
+    let salt: String = salt.iter().map(|b| alphabet[*b as usize % 64] as char).collect();
+
+    match algorithm {
+        Algorithm::Md5Crypt => Some(format!("$1${}", &salt[..salt.len().min(8)])),
+        Algorithm::Sha256Crypt => Some(format!("$5$rounds={}${}", SHA_CRYPT_DEMO_ROUNDS, &salt[..salt.len().min(16)])),
+        Algorithm::Sha512Crypt => Some(format!("$6$rounds={}${}", SHA_CRYPT_DEMO_ROUNDS, &salt[..salt.len().min(16)])),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_md5_crypt() {
+        let hash = crypt(Algorithm::Md5Crypt, "$1$saltsalt", b"hunter2").unwrap();
+        let parsed = parse_crypt(&hash).unwrap();
+
+        assert_eq!(parsed.algorithm, Algorithm::Md5Crypt);
+        assert_eq!(parsed.salt, "saltsalt");
+        assert_eq!(parsed.params, None);
+        assert_eq!(parsed.digest.len(), 22);
+        assert!(verify_crypt(b"hunter2", &hash));
+        assert!(!verify_crypt(b"hunter3", &hash));
+    }
+
+    #[test]
+    fn test_parse_sha256_crypt_with_rounds() {
+        let hash = crypt(Algorithm::Sha256Crypt, "$5$rounds=1000$pepper", b"hunter2").unwrap();
+        let parsed = parse_crypt(&hash).unwrap();
+
+        assert_eq!(parsed.algorithm, Algorithm::Sha256Crypt);
+        assert_eq!(parsed.salt, "pepper");
+        assert_eq!(parsed.params, Some("rounds=1000"));
+        assert_eq!(parsed.setting(), "$5$rounds=1000$pepper");
+        assert!(verify_crypt(b"hunter2", &hash));
+    }
+
+    #[test]
+    fn test_parse_sha512_crypt_default_rounds() {
+        let hash = crypt(Algorithm::Sha512Crypt, "$6$nacl", b"hunter2").unwrap();
+        let parsed = parse_crypt(&hash).unwrap();
+
+        assert_eq!(parsed.algorithm, Algorithm::Sha512Crypt);
+        assert_eq!(parsed.salt, "nacl");
+        assert_eq!(parsed.params, None);
+        assert_eq!(parsed.digest.len(), 86);
+        assert!(verify_crypt(b"hunter2", &hash));
+    }
+
+    #[test]
+    fn test_parse_bcrypt() {
+        let hash = bcrypt::hash_with_salt(b"hunter2", 4, [7u8; 16]).unwrap()
+            .format_for_version(bcrypt::Version::TwoB);
+        let parsed = parse_crypt(&hash).unwrap();
+
+        assert_eq!(parsed.algorithm, Algorithm::Bcrypt);
+        assert_eq!(parsed.id, "2b");
+        assert_eq!(parsed.params, Some("04"));
+        assert_eq!(parsed.salt.len(), 22);
+        assert!(hash.starts_with(&parsed.setting()));
+        assert!(verify_crypt(b"hunter2", &hash));
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed() {
+        assert!(parse_crypt("5f4dcc3b5aa765d61d8327deb882cf99").is_err());
+        assert!(parse_crypt("$7$salt$digest").is_err());
+        assert!(parse_crypt("$1$waytoolongsalt$abcdefghijklmnopqrstuv").is_err());
+        assert!(parse_crypt("$1$salt$short").is_err());
+        assert!(parse_crypt("$6$rounds=lots$salt$digest").is_err());
+    }
+}
@@ -0,0 +1,128 @@
+// the fast path's digest lookup. target digests are sorted by their leading
+// eight bytes and bucketed on the top bits of those, so a probe reads one
+// bucket - about one entry, since digests are uniformly random - out of flat
+// arrays, and a miss never touches a full digest. against the hashmap it
+// replaced this is about 1.5x faster per probe from 16 targets to 100k;
+// `cargo bench -- digest_lookup` has both, plus a binary search and a
+// linear scan over the same digests
+
+/// decoded digests → positions in the target list
+#[derive(Debug, Clone)]
+pub struct DigestTable {
+    /// each digest's leading eight bytes, big-endian, sorted
+    prefixes: Vec<u64>,
+    /// the full digests, in `prefixes` order
+    digests: Vec<Vec<u8>>,
+    /// target positions, in `prefixes` order
+    positions: Vec<usize>,
+    /// bucket `b` holds entries `starts[b]..starts[b + 1]`
+    starts: Vec<u32>,
+    /// prefix bits dropped to get the bucket
+    shift: u32,
+}
+
+/// a digest's first eight bytes as a big-endian number, zero-padded when
+/// it's shorter, so prefix order agrees with byte order
+fn prefix(digest: &[u8]) -> u64 {
+    if let Some(head) = digest.first_chunk::<8>() {
+        return u64::from_be_bytes(*head);
+    }
+    let mut bytes = [0u8; 8];
+    let len = digest.len().min(8);
+    bytes[..len].copy_from_slice(&digest[..len]);
+    u64::from_be_bytes(bytes)
+}
+
+impl DigestTable {
+    /// index `entries` of (digest, position). positions sharing a digest
+    /// come back from `get` in the order they're given here
+    pub fn new(entries: impl IntoIterator<Item = (Vec<u8>, usize)>) -> Self {
+        let mut entries: Vec<(u64, Vec<u8>, usize)> = entries.into_iter()
+            .map(|(digest, position)| (prefix(&digest), digest, position))
+            .collect();
+        // stable, so shared digests keep their order
+        entries.sort_by_key(|&(prefix, _, _)| prefix);
+
+        // about one entry per bucket
+        let bits = entries.len().next_power_of_two().trailing_zeros().clamp(1, 32);
+        let shift = 64 - bits;
+        let mut starts = vec![0u32; (1 << bits) + 1];
+        for &(prefix, _, _) in &entries {
+            starts[(prefix >> shift) as usize + 1] += 1;
+        }
+        let mut total = 0;
+        for start in &mut starts {
+            total += *start;
+            *start = total;
+        }
+
+        let mut table = Self {
+            prefixes: Vec::with_capacity(entries.len()),
+            digests: Vec::with_capacity(entries.len()),
+            positions: Vec::with_capacity(entries.len()),
+            starts,
+            shift,
+        };
+        for (prefix, digest, position) in entries {
+            table.prefixes.push(prefix);
+            table.digests.push(digest);
+            table.positions.push(position);
+        }
+        table
+    }
+
+    /// the positions whose digest is `digest`
+    pub fn get<'a>(&'a self, digest: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        let prefix = prefix(digest);
+        let bucket = (prefix >> self.shift) as usize;
+        let (start, end) = match self.starts.get(bucket..bucket + 2) {
+            Some(&[start, end]) => (start as usize, end as usize),
+            _ => (0, 0),
+        };
+        (start..end)
+            .filter(move |&i| self.prefixes[i] == prefix && self.digests[i] == digest)
+            .map(move |i| self.positions[i])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::hasher::create_hasher;
+    use crate::core::Algorithm;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_lookups_match_a_hashmap() {
+        let hasher = create_hasher(Algorithm::Md5);
+        let mut entries: Vec<(Vec<u8>, usize)> = (0..1000)
+            .map(|n| (hasher.hash(format!("pw{}", n).as_bytes()), n))
+            .collect();
+        // a digest shared by three targets, a short one and an empty one
+        entries.push((entries[10].0.clone(), 1000));
+        entries.push((entries[10].0.clone(), 1001));
+        entries.push((vec![0xab, 0xcd], 1002));
+        entries.push((Vec::new(), 1003));
+
+        let mut map: HashMap<Vec<u8>, Vec<usize>> = HashMap::new();
+        for (digest, position) in &entries {
+            map.entry(digest.clone()).or_default().push(*position);
+        }
+        let table = DigestTable::new(entries.clone());
+
+        for (digest, _) in &entries {
+            assert_eq!(table.get(digest).collect::<Vec<_>>(), map[digest]);
+        }
+        assert_eq!(table.get(&entries[10].0).collect::<Vec<_>>(), vec![10, 1000, 1001]);
+
+        // misses, including a digest sharing a known one's prefix
+        let mut near = entries[5].0.clone();
+        near[15] ^= 1;
+        for miss in [near, hasher.hash(b"not a target"), vec![0xab], vec![0xab, 0xcd, 0]] {
+            assert_eq!(table.get(&miss).count(), 0, "{:?}", miss);
+        }
+
+        let empty = DigestTable::new(Vec::new());
+        assert_eq!(empty.get(&entries[0].0).count(), 0);
+    }
+}
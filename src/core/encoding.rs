@@ -0,0 +1,174 @@
+// byte encoding of password text. digests are over bytes, so "é" hashed as
+// utf-8 (c3 a9), utf-16le (e9 00) or latin-1 (e9) gives three different
+// targets - generate-targets records the encoding so the cracker matches it.
+// also the case folding some legacy schemes apply before hashing
+
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+use crate::core::CandidateTransform;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PasswordEncoding {
+    #[default]
+    Utf8,
+    /// what windows hashes for ntlm (md4 over utf-16le)
+    Utf16le,
+    /// one byte per char, only up to U+00FF
+    Latin1,
+}
+
+impl PasswordEncoding {
+    /// the bytes that get hashed for `password`, or `None` if the encoding
+    /// can't represent one of its chars
+    pub fn encode(self, password: &str) -> Option<Vec<u8>> {
+        match self {
+            PasswordEncoding::Utf8 => Some(password.as_bytes().to_vec()),
+            PasswordEncoding::Utf16le => Some(password.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+            PasswordEncoding::Latin1 => password.chars()
+                .map(|c| u8::try_from(u32::from(c)).ok())
+                .collect(),
+        }
+    }
+
+    /// re-encode a candidate, which generators produce as utf-8. utf-8 is
+    /// passed through untouched, whatever the bytes; otherwise `None` if
+    /// the candidate isn't valid utf-8 or can't be represented
+    pub fn encode_candidate(self, candidate: &[u8]) -> Option<Cow<'_, [u8]>> {
+        if self == PasswordEncoding::Utf8 {
+            return Some(Cow::Borrowed(candidate));
+        }
+        let text = std::str::from_utf8(candidate).ok()?;
+        self.encode(text).map(Cow::Owned)
+    }
+}
+
+impl std::str::FromStr for PasswordEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "utf8" | "utf-8" => Ok(PasswordEncoding::Utf8),
+            "utf16le" | "utf-16le" => Ok(PasswordEncoding::Utf16le),
+            "latin1" | "latin-1" | "iso-8859-1" => Ok(PasswordEncoding::Latin1),
+            _ => Err(format!("unknown encoding: {} (expected utf8, utf16le or latin1)", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for PasswordEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PasswordEncoding::Utf8 => write!(f, "utf8"),
+            PasswordEncoding::Utf16le => write!(f, "utf16le"),
+            PasswordEncoding::Latin1 => write!(f, "latin1"),
+        }
+    }
+}
+
+/// case a scheme folds passwords to before hashing. lm hashes and some
+/// old application logins uppercase the password, so any casing of it
+/// logs in and a lowercase wordlist only matches once folded the same way
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaseFold {
+    #[default]
+    None,
+    Upper,
+    Lower,
+}
+
+impl CaseFold {
+    /// `candidate` folded - as unicode text when it's valid utf-8, byte by
+    /// byte in ascii otherwise
+    pub fn fold(self, candidate: &[u8]) -> Vec<u8> {
+        match (self, std::str::from_utf8(candidate)) {
+            (CaseFold::None, _) => candidate.to_vec(),
+            (CaseFold::Upper, Ok(text)) => text.to_uppercase().into_bytes(),
+            (CaseFold::Lower, Ok(text)) => text.to_lowercase().into_bytes(),
+            (CaseFold::Upper, Err(_)) => candidate.to_ascii_uppercase(),
+            (CaseFold::Lower, Err(_)) => candidate.to_ascii_lowercase(),
+        }
+    }
+
+    /// the fold as an engine transform (`Engine::with_transform`), or
+    /// `None` when there's nothing to fold
+    pub fn transform(self) -> Option<CandidateTransform> {
+        match self {
+            CaseFold::None => None,
+            fold => Some(Box::new(move |candidate: &[u8]| fold.fold(candidate))),
+        }
+    }
+}
+
+impl std::str::FromStr for CaseFold {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(CaseFold::None),
+            "upper" => Ok(CaseFold::Upper),
+            "lower" => Ok(CaseFold::Lower),
+            _ => Err(format!("unknown case fold: {} (expected none, upper or lower)", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for CaseFold {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CaseFold::None => write!(f, "none"),
+            CaseFold::Upper => write!(f, "upper"),
+            CaseFold::Lower => write!(f, "lower"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_non_ascii() {
+        assert_eq!(PasswordEncoding::Utf8.encode("café").unwrap(), b"caf\xc3\xa9");
+        assert_eq!(PasswordEncoding::Utf16le.encode("café").unwrap(), b"c\0a\0f\0\xe9\0");
+        assert_eq!(PasswordEncoding::Latin1.encode("café").unwrap(), b"caf\xe9");
+        assert_eq!(PasswordEncoding::Latin1.encode("日本"), None);
+
+        // candidates come in as utf-8
+        let candidate = "café".as_bytes();
+        assert_eq!(PasswordEncoding::Latin1.encode_candidate(candidate).unwrap().as_ref(), b"caf\xe9");
+        assert!(PasswordEncoding::Utf16le.encode_candidate(b"\xff").is_none());
+        assert_eq!(PasswordEncoding::Utf8.encode_candidate(b"\xff").unwrap().as_ref(), b"\xff");
+    }
+
+    #[test]
+    fn test_upper_folded_target_cracks_from_lowercase_wordlist() {
+        use crate::core::hasher::create_hasher;
+        use crate::core::{Algorithm, Engine, MemoryGenerator, Target};
+
+        assert_eq!(CaseFold::Upper.fold(b"pass\xffword"), b"PASS\xffWORD");
+        assert_eq!(CaseFold::Lower.fold("ÉTÉ".as_bytes()), "été".as_bytes());
+        assert_eq!(CaseFold::None.fold(b"MiXeD"), b"MiXeD");
+
+        // the scheme stored md5("PASSWORD"); the wordlist is all lowercase
+        let target = Target {
+            id: "legacy".to_string(),
+            username: "bob".to_string(),
+            algorithm: Algorithm::Md5,
+            hash: hex::encode(create_hasher(Algorithm::Md5).hash(b"PASSWORD")),
+            ..Default::default()
+        };
+        let words = || Box::new(MemoryGenerator::new(vec![b"letmein".to_vec(), b"password".to_vec()]));
+
+        let unfolded = Engine::new(vec![target.clone()], words(), 1, 16).run(|_| {}).unwrap();
+        assert!(unfolded.matches.is_empty());
+
+        let mut engine = Engine::new(vec![target], words(), 1, 16)
+            .with_transform(CaseFold::Upper.transform().unwrap());
+        let result = engine.run(|_| {}).unwrap();
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.matches[0].password, b"PASSWORD");
+        assert!(CaseFold::None.transform().is_none());
+    }
+}
@@ -1,10 +1,29 @@
+use std::borrow::Cow;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 use rayon::prelude::*;
 use hashbrown::HashMap;
 use anyhow::Result;
 
-use super::{Generator, Hasher, Target, TargetMatch};
+use super::{Algorithm, CandidateBatch, CandidateSource, DigestTable, Generator, Hasher, PasswordEncoding, Target, TargetMatch};
+use super::crypt::{crypt, parse_crypt};
+use super::hasher::create_hasher;
+
+/// time constant of the current-rate average: after a step change in
+/// speed, the reading has moved ~63% of the way to the new rate this long later
+const THROUGHPUT_SMOOTHING_SECS: f64 = 2.0;
+
+/// how long `--adaptive-workers` measures each worker count for
+const TUNE_WINDOW: Duration = Duration::from_secs(1);
+
+/// rate drop, as a fraction, that counts as "worse" rather than noise
+const TUNE_TOLERANCE: f64 = 0.03;
+
+/// direction changes after which the tuner stops searching
+const TUNE_REVERSALS: u32 = 2;
 
 #[derive(Debug, Clone)]
 pub struct Statistics {
@@ -13,7 +32,20 @@ pub struct Statistics {
     pub targets_found: usize,
     pub targets_total: usize,
     pub start_time: Instant,
+    /// seconds since `start_time` as of the last throughput update, so
+    /// everything displaying it shows the same time
+    pub elapsed_seconds: f64,
+    /// cumulative average since the start - the figure for final reports
     pub hashes_per_second: f64,
+    /// exponentially weighted average of recent throughput - what live
+    /// displays show, since it follows speed changes within seconds
+    pub current_hashes_per_second: f64,
+    /// time and hash count at the previous throughput update
+    last_sample: Option<(Instant, u64)>,
+    /// fraction of the keyspace searched, if the generator can tell
+    pub progress: Option<f64>,
+    /// matches found in the most recent batch, for live feeds
+    pub new_matches: Vec<TargetMatch>,
 }
 
 impl Statistics {
@@ -24,51 +56,654 @@ impl Statistics {
             targets_found: 0,
             targets_total,
             start_time: Instant::now(),
+            elapsed_seconds: 0.0,
             hashes_per_second: 0.0,
+            current_hashes_per_second: 0.0,
+            last_sample: None,
+            progress: None,
+            new_matches: Vec::new(),
         }
     }
     
     pub fn update_throughput(&mut self) {
-        let elapsed = self.start_time.elapsed().as_secs_f64();
+        self.update_throughput_at(Instant::now());
+    }
+    
+    fn update_throughput_at(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.start_time).as_secs_f64();
+        self.elapsed_seconds = elapsed;
         if elapsed > 0.0 {
             self.hashes_per_second = self.hashes_computed as f64 / elapsed;
         }
+        
+        let (last_time, last_hashes) = self.last_sample.unwrap_or((self.start_time, 0));
+        let dt = now.duration_since(last_time).as_secs_f64();
+        if dt <= 0.0 {
+            return;
+        }
+        
+        // batches finish at irregular intervals, so weight each sample by
+        // the time it covers rather than by a fixed factor
+        let instant_rate = (self.hashes_computed - last_hashes) as f64 / dt;
+        self.current_hashes_per_second = match self.last_sample {
+            None => instant_rate,
+            Some(_) => {
+                let alpha = 1.0 - (-dt / THROUGHPUT_SMOOTHING_SECS).exp();
+                self.current_hashes_per_second + alpha * (instant_rate - self.current_hashes_per_second)
+            }
+        };
+        self.last_sample = Some((now, self.hashes_computed));
     }
 }
 
+/// rewrites each candidate before it's hashed (see `Engine::with_transform`)
+pub type CandidateTransform = Box<dyn Fn(&[u8]) -> Vec<u8> + Send + Sync>;
+
 pub struct Engine {
-    targets: Vec<Target>,
+    targets: PreparedTargets,
     generator: Box<dyn Generator>,
     workers: usize,
     batch_size: usize,
+    batch_timeout: Option<Duration>,
+    /// batch sizes for single algorithms (`with_algorithm_batch_sizes`)
+    algorithm_batch_sizes: Vec<(Algorithm, usize)>,
+    pin_threads: bool,
+    transform: Option<CandidateTransform>,
+    fast_path: bool,
+    /// split index-based keyspaces into per-worker ranges (`without_parallel_ranges`)
+    parallel_ranges: bool,
+    adaptive_workers: bool,
+    collapse_duplicates: bool,
+    hash_once: bool,
+    target_timeout: Option<Duration>,
+    /// stop once this many targets are cracked (`with_success_threshold`)
+    success_threshold: Option<usize>,
+    /// matches already known before the run (`with_known_matches`)
+    known: Vec<TargetMatch>,
+    /// secret appended to every candidate before hashing (`with_pepper`)
+    pepper: Vec<u8>,
+    /// how many closest misses to keep (`with_near_misses`)
+    near_misses: Option<usize>,
+    stop: Option<Arc<AtomicBool>>,
     stats: Arc<Mutex<Statistics>>,
 }
 
+/// why a run ended - decides what the user can conclude about the targets
+/// it didn't crack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminationReason {
+    /// every target was cracked
+    AllFound,
+    /// the whole keyspace was searched: what's left isn't in it
+    Exhausted,
+    /// the generator's time limit ran out part way through the keyspace,
+    /// or every target left was given up on (`with_target_timeout`)
+    TimeLimit,
+    /// stopped on request (ctrl-c) part way through the keyspace
+    Interrupted,
+    /// enough targets were cracked (`with_success_threshold`); the rest
+    /// weren't searched for any further
+    ThresholdReached,
+}
+
 #[derive(Debug, Clone)]
 pub struct CrackingResult {
     pub matches: Vec<TargetMatch>,
     pub statistics: Statistics,
     pub total_time: f64,
+    /// batch size in effect when the run ended (smaller than configured if
+    /// the batch timeout kicked in)
+    pub final_batch_size: usize,
+    /// every worker ran pinned to its own core (`with_pinned_threads`).
+    /// `false` when pinning was asked for but the platform couldn't do it
+    pub threads_pinned: bool,
+    pub termination: TerminationReason,
+    /// worker count the adaptive tuner ended on (`with_adaptive_workers`)
+    pub adaptive_workers: Option<usize>,
+    /// ids of targets retired uncracked by `with_target_timeout`, in the
+    /// order they were given up on
+    pub given_up: Vec<String>,
+    /// blitzhash candidates that came closest without matching, closest
+    /// first - empty unless `with_near_misses` was set
+    pub near_misses: Vec<NearMiss>,
+}
+
+/// a candidate whose digest starts the way a target's does but doesn't match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NearMiss {
+    pub target_id: String,
+    pub candidate: Vec<u8>,
+    pub digest: Vec<u8>,
+    /// leading bytes `digest` has in common with the target's
+    pub shared_bytes: usize,
+}
+
+/// the `cap` misses sharing the longest digest prefix with their target,
+/// fed from every worker. most candidates share no byte at all, so the
+/// lock is only taken by one that would make the list
+struct NearMissTracker {
+    cap: usize,
+    /// each blitzhash target's digest by id, decoded once up front
+    wanted: HashMap<String, Vec<u8>>,
+    /// fewest shared bytes that still gets a miss in
+    floor: AtomicUsize,
+    misses: Mutex<Vec<NearMiss>>,
+}
+
+impl NearMissTracker {
+    fn new(cap: usize, targets: &[Target]) -> Self {
+        let wanted = targets.iter()
+            .filter(|t| t.algorithm == Algorithm::BlitzHash)
+            .filter_map(|t| Some((t.id.clone(), hex::decode(&t.hash).ok()?)))
+            .collect();
+        Self { cap: cap.max(1), wanted, floor: AtomicUsize::new(1), misses: Mutex::new(Vec::new()) }
+    }
+    
+    fn observe(&self, target: &Target, candidate: &[u8], digest: &[u8]) {
+        let Some(wanted) = self.wanted.get(&target.id) else {
+            return;
+        };
+        let shared_bytes = digest.iter().zip(wanted).take_while(|(a, b)| a == b).count();
+        if shared_bytes < self.floor.load(Ordering::Relaxed) {
+            return;
+        }
+        
+        let mut misses = self.misses.lock().unwrap();
+        // after those sharing as much, so the earliest of a tie stays
+        let at = misses.partition_point(|m| m.shared_bytes >= shared_bytes);
+        if at >= self.cap {
+            return;
+        }
+        misses.insert(at, NearMiss {
+            target_id: target.id.clone(),
+            candidate: candidate.to_vec(),
+            digest: digest.to_vec(),
+            shared_bytes,
+        });
+        misses.truncate(self.cap);
+        if misses.len() == self.cap {
+            self.floor.store(misses[self.cap - 1].shared_bytes + 1, Ordering::Relaxed);
+        }
+    }
+    
+    fn into_misses(self) -> Vec<NearMiss> {
+        self.misses.into_inner().unwrap()
+    }
+}
+
+/// targets sharing an algorithm, salt and password encoding - one digest
+/// per candidate serves them all
+struct HashGroup<'a> {
+    algorithm: Algorithm,
+    salt: Vec<u8>,
+    /// hash with `salt` even if it's empty (`Target::is_salted`)
+    salted: bool,
+    /// the salt goes after the candidate (`Target::is_salt_after`)
+    salt_after: bool,
+    encoding: PasswordEncoding,
+    hasher: Box<dyn Hasher>,
+    targets: Vec<&'a Target>,
+    /// crypt setting every target in the group shares (`--hash-once`):
+    /// one crypt per candidate, compared as a string against each target
+    setting: Option<String>,
+}
+
+impl HashGroup<'_> {
+    #[inline]
+    fn hash(&self, candidate: &[u8]) -> Vec<u8> {
+        match (self.salted, self.salt_after) {
+            (false, _) => self.hasher.hash(candidate),
+            (true, false) => self.hasher.hash_with_salt(candidate, &self.salt),
+            (true, true) => self.hasher.hash(&[candidate, &self.salt].concat()),
+        }
+    }
+}
+
+/// group targets by (algorithm, salt, salted, salt_after, encoding), sorted by algorithm
+/// name then salt so every run processes the groups in the same order. kdf
+/// targets embed their own salt and cost, so each one gets a group of its
+/// own - except, with `hash_once`, crypt targets sharing a setting
+fn build_hash_groups<'a>(targets: impl IntoIterator<Item = &'a Target>, hash_once: bool) -> Vec<HashGroup<'a>> {
+    type GroupKey = (String, Vec<u8>, bool, bool, PasswordEncoding, Option<String>);
+    
+    let mut by_key: HashMap<GroupKey, Vec<&Target>> = HashMap::new();
+    for target in targets {
+        let own_group = match target.algorithm.is_kdf() {
+            true => hash_once.then(|| shared_setting(target)).flatten().or_else(|| Some(target.id.clone())),
+            false => None,
+        };
+        by_key
+            .entry((target.algorithm.to_string(), target.salt_bytes(), target.is_salted(), target.is_salt_after(), target.encoding(), own_group))
+            .or_insert_with(Vec::new)
+            .push(target);
+    }
+    
+    let mut keyed: Vec<(GroupKey, Vec<&Target>)> = by_key.into_iter().collect();
+    keyed.sort_by(|a, b| a.0.cmp(&b.0));
+    
+    keyed.into_iter()
+        .map(|((_, salt, salted, salt_after, encoding, _), mut targets)| {
+            // stable, so equal priorities keep their file order
+            targets.sort_by_key(|t| std::cmp::Reverse(t.priority.unwrap_or(0)));
+
+            let algorithm = targets[0].algorithm;
+            let setting = match hash_once {
+                true => shared_setting(targets[0]),
+                false => None,
+            };
+            HashGroup {
+                algorithm,
+                salt,
+                salted,
+                salt_after,
+                encoding,
+                hasher: create_hasher(algorithm),
+                targets,
+                setting,
+            }
+        })
+        .collect()
+}
+
+/// the setting (scheme, rounds, salt) of a target whose candidates can be
+/// crypted once and string-compared, for the crypt schemes `crypt` computes.
+/// bcrypt, scrypt and argon2 targets still verify one by one
+fn shared_setting(target: &Target) -> Option<String> {
+    let parsed = parse_crypt(&target.hash).ok()?;
+    matches!(parsed.algorithm, Algorithm::Md5Crypt | Algorithm::Sha256Crypt | Algorithm::Sha512Crypt)
+        .then(|| parsed.setting())
+}
+
+/// the digest index for the headline benchmark case: every member unsalted
+/// under one fast algorithm and encoding. each candidate is then hashed once
+/// and looked up, instead of walking the group comparing hex against every
+/// target. `None` when the targets need the general path
+fn digest_index(targets: &[Target], members: &[usize]) -> Option<DigestTable> {
+    let first = &targets[*members.first()?];
+    let fits = |t: &Target| {
+        t.algorithm == first.algorithm && t.encoding() == first.encoding() && !t.is_salted() && t.hash_bytes.is_none()
+    };
+    if first.algorithm.is_kdf() || !members.iter().all(|&i| fits(&targets[i])) {
+        return None;
+    }
+    
+    // stable, so shared digests keep the priority order the groups use
+    let mut by_priority = members.to_vec();
+    by_priority.sort_by_key(|&i| std::cmp::Reverse(targets[i].priority.unwrap_or(0)));
+    
+    // malformed hex can't match anything; validation reports it
+    let digests = by_priority.into_iter()
+        .filter_map(|i| hex::decode(&targets[i].hash).ok().map(|digest| (digest, i)));
+    Some(DigestTable::new(digests))
+}
+
+/// targets with their hashes decoded and the fast path's digest index built,
+/// ready to hand to any number of engines. cloning shares rather than
+/// copies, so a long target list is prepared once and attacked with one
+/// generator after another
+#[derive(Debug, Clone)]
+pub struct PreparedTargets {
+    targets: Arc<Vec<Target>>,
+    /// `None` when the targets need the general path
+    index: Option<Arc<DigestTable>>,
+}
+
+impl PreparedTargets {
+    pub fn new(targets: Vec<Target>) -> Self {
+        let all: Vec<usize> = (0..targets.len()).collect();
+        let index = digest_index(&targets, &all).map(Arc::new);
+        Self { targets: Arc::new(targets), index }
+    }
+    
+    pub fn targets(&self) -> &[Target] {
+        &self.targets
+    }
+    
+    /// whether runs can take the single-lookup fast path
+    #[cfg(test)]
+    pub fn is_indexed(&self) -> bool {
+        self.index.is_some()
+    }
+}
+
+impl From<Vec<Target>> for PreparedTargets {
+    fn from(targets: Vec<Target>) -> Self {
+        Self::new(targets)
+    }
+}
+
+/// fold targets that are the same hash - algorithm, salt bytes and place, encoding
+/// and decoded digest - onto one representative, as happens when dumps are
+/// merged. returns the representatives and, for each, the targets sharing
+/// it, by position. the highest-priority copy represents the rest, so
+/// report order holds
+fn collapse_duplicates(targets: &[Target]) -> (Vec<usize>, HashMap<&str, Vec<&Target>>) {
+    type DuplicateKey = (String, Vec<u8>, bool, bool, PasswordEncoding, Vec<u8>);
+    
+    let mut by_priority: Vec<(usize, &Target)> = targets.iter().enumerate().collect();
+    by_priority.sort_by_key(|(_, t)| std::cmp::Reverse(t.priority.unwrap_or(0)));
+    
+    let mut first: HashMap<DuplicateKey, (usize, &Target)> = HashMap::new();
+    let mut sharing: HashMap<&str, Vec<&Target>> = HashMap::new();
+    for (index, target) in by_priority {
+        // kdf and crypt strings are compared as written; hex case doesn't matter
+        let digest = match target.algorithm.is_kdf() {
+            true => target.hash.as_bytes().to_vec(),
+            false => hex::decode(&target.hash).unwrap_or_else(|_| target.hash.as_bytes().to_vec()),
+        };
+        let key = (target.algorithm.to_string(), target.salt_bytes(), target.is_salted(), target.is_salt_after(), target.encoding(), digest);
+        match first.get(&key) {
+            Some((_, representative)) => sharing.entry(representative.id.as_str()).or_default().push(target),
+            None => {
+                first.insert(key, (index, target));
+            }
+        }
+    }
+    
+    // back in file order, which the groups' stable sort relies on
+    let mut representatives: Vec<usize> = first.into_values().map(|(index, _)| index).collect();
+    representatives.sort_unstable();
+    (representatives, sharing)
+}
+
+/// the bytes actually hashed for an encoded candidate
+fn peppered<'a>(encoded: Cow<'a, [u8]>, pepper: &[u8]) -> Cow<'a, [u8]> {
+    if pepper.is_empty() {
+        return encoded;
+    }
+    let mut bytes = encoded.into_owned();
+    bytes.extend_from_slice(pepper);
+    Cow::Owned(bytes)
+}
+
+fn target_match(target: &Target, candidate: &[u8], encoded: &[u8], guesses_tried: u64, time_seconds: f64) -> TargetMatch {
+    TargetMatch {
+        target_id: target.id.clone(),
+        username: target.username.clone(),
+        password: candidate.to_vec(),
+        algorithm: target.algorithm,
+        guesses_tried,
+        time_seconds,
+        verified: target.verify(encoded),
+        expected_password: target.expected_password.clone(),
+    }
+}
+
+/// hill-climbs the number of busy workers towards the best measured
+/// throughput. starts at the full count and steps down first - on a laptop
+/// that's throttling, fewer hot cores can hash faster than all of them
+#[derive(Debug, Clone)]
+struct WorkerTuner {
+    max: usize,
+    current: usize,
+    step: isize,
+    last_rate: Option<f64>,
+    reversals: u32,
+}
+
+impl WorkerTuner {
+    fn new(max: usize) -> Self {
+        let max = max.max(1);
+        // with one worker there's nothing to search
+        let reversals = if max == 1 { TUNE_REVERSALS } else { 0 };
+        Self { max, current: max, step: -1, last_rate: None, reversals }
+    }
+    
+    fn workers(&self) -> usize {
+        self.current
+    }
+    
+    fn settled(&self) -> bool {
+        self.reversals >= TUNE_REVERSALS
+    }
+    
+    /// take the rate measured at the current count and pick the next one:
+    /// keep going while it doesn't get worse, turn back when it does
+    fn observe(&mut self, rate: f64) -> usize {
+        if self.settled() {
+            return self.current;
+        }
+        
+        if self.last_rate.is_some_and(|last| rate < last * (1.0 - TUNE_TOLERANCE)) {
+            self.step = -self.step;
+            self.reversals += 1;
+        }
+        self.last_rate = Some(rate);
+        
+        let mut next = self.clamped(self.step);
+        if next == self.current {
+            // at 1 or max: the only way is back
+            self.step = -self.step;
+            self.reversals += 1;
+            next = self.clamped(self.step);
+        }
+        self.current = next;
+        self.current
+    }
+    
+    fn clamped(&self, step: isize) -> usize {
+        (self.current as isize + step).clamp(1, self.max as isize) as usize
+    }
+}
+
+/// hands out an index-based keyspace a batch at a time, each batch cut into
+/// one contiguous range per worker. the workers generate their own range
+/// from a `seek_clone` of the generator, so no single thread produces every
+/// candidate - for cheap hashes that would otherwise be the bottleneck
+#[derive(Debug, Clone)]
+struct RangeSplitter {
+    next: u128,
+    total: u128,
+}
+
+impl RangeSplitter {
+    fn new(total: u128) -> Self {
+        Self { next: 0, total }
+    }
+    
+    /// the next `size` candidates as up to `parts` (start, len) ranges,
+    /// sized within one of each other. empty once the keyspace is used up
+    fn next_round(&mut self, size: u128, parts: usize) -> Vec<(u128, u128)> {
+        let round = size.min(self.total - self.next);
+        let parts = (parts.max(1) as u128).min(round);
+        if parts == 0 {
+            return Vec::new();
+        }
+        
+        let (each, extra) = (round / parts, round % parts);
+        (0..parts)
+            .map(|i| {
+                let range = (self.next, each + u128::from(i < extra));
+                self.next += range.1;
+                range
+            })
+            .collect()
+    }
+    
+    fn progress(&self) -> f64 {
+        match self.total {
+            0 => 1.0,
+            total => self.next as f64 / total as f64,
+        }
+    }
+}
+
+/// shrink the batch size so the next batch fits within the soft timeout.
+/// scales proportionally to how far over budget the last batch ran
+fn adapt_batch_size(current: usize, elapsed: Duration, timeout: Duration) -> usize {
+    if elapsed <= timeout || current <= 1 {
+        return current;
+    }
+    
+    let scaled = (current as f64 * timeout.as_secs_f64() / elapsed.as_secs_f64()) as usize;
+    scaled.clamp(1, current - 1)
 }
 
 impl Engine {
+    /// `targets` is either a plain `Vec<Target>` or `PreparedTargets`
+    /// shared with other engines
     pub fn new(
-        targets: Vec<Target>,
+        targets: impl Into<PreparedTargets>,
         generator: Box<dyn Generator>,
         workers: usize,
         batch_size: usize,
     ) -> Self {
-        let stats = Arc::new(Mutex::new(Statistics::new(targets.len())));
+        let targets = targets.into();
+        let stats = Arc::new(Mutex::new(Statistics::new(targets.targets.len())));
         
         Self {
             targets,
             generator,
             workers,
             batch_size,
+            batch_timeout: None,
+            algorithm_batch_sizes: Vec::new(),
+            pin_threads: false,
+            transform: None,
+            fast_path: true,
+            parallel_ranges: true,
+            adaptive_workers: false,
+            collapse_duplicates: false,
+            hash_once: false,
+            target_timeout: None,
+            success_threshold: None,
+            known: Vec::new(),
+            pepper: Vec::new(),
+            near_misses: None,
+            stop: None,
             stats,
         }
     }
     
+    /// soft per-batch time budget; batches that overrun it shrink the
+    /// batch size for the rest of the run so the ui stays responsive
+    pub fn with_batch_timeout(mut self, timeout: Duration) -> Self {
+        self.batch_timeout = Some(timeout);
+        self
+    }
+    
+    /// pin each worker thread to its own cpu core (wrapping round when
+    /// there are more workers than cores) for steadier benchmarks. where
+    /// the os won't report or set affinity the run goes ahead unpinned
+    pub fn with_pinned_threads(mut self) -> Self {
+        self.pin_threads = true;
+        self
+    }
+    
+    /// apply `transform` to every candidate before hashing, for preprocessing
+    /// no generator covers (unicode normalization, a fixed prefix, ...).
+    /// matches report the transformed bytes, since that's the password
+    pub fn with_transform(mut self, transform: CandidateTransform) -> Self {
+        self.transform = Some(transform);
+        self
+    }
+    
+    /// batch size to use while targets of these algorithms are still
+    /// uncracked, in place of the engine's own. every candidate in a batch
+    /// is hashed for every algorithm, so a batch takes the smallest size of
+    /// the algorithms left: bcrypt=64 keeps a bcrypt run responsive, and
+    /// blitzhash=65536 takes over once only blitzhash targets remain
+    pub fn with_algorithm_batch_sizes(mut self, sizes: Vec<(Algorithm, usize)>) -> Self {
+        self.algorithm_batch_sizes = sizes;
+        self
+    }
+    
+    /// append `pepper` to every candidate's bytes before hashing, for every
+    /// target - a server-side secret kept out of the targets file. matches
+    /// still report the candidate without it
+    pub fn with_pepper(mut self, pepper: Vec<u8>) -> Self {
+        self.pepper = pepper;
+        self
+    }
+    
+    /// keep the `cap` blitzhash candidates whose digests share the most
+    /// leading bytes with a target's, for showing how hash output spreads.
+    /// every blitzhash comparison is then checked, so the fast path is off
+    pub fn with_near_misses(mut self, cap: usize) -> Self {
+        self.near_misses = Some(cap);
+        self
+    }
+    
+    /// count these targets as cracked from the start, as `run --potfile`
+    /// does. they're reported in the result like any other match but never
+    /// hashed for; matches for unknown ids are ignored
+    pub fn with_known_matches(mut self, known: Vec<TargetMatch>) -> Self {
+        self.known = known;
+        self
+    }
+    
+    /// stop before the next batch once `stop` is set, ending the run as
+    /// `TerminationReason::Interrupted` with whatever was found so far
+    pub fn with_stop_flag(mut self, stop: Arc<AtomicBool>) -> Self {
+        self.generator.watch_stop(Arc::clone(&stop));
+        self.stop = Some(stop);
+        self
+    }
+    
+    /// vary how many workers hash each batch, settling on whichever count
+    /// measures fastest. the pool keeps all its threads; the spare ones
+    /// get no work and sit idle
+    pub fn with_adaptive_workers(mut self) -> Self {
+        self.adaptive_workers = true;
+        self
+    }
+    
+    /// hash-compare each set of identical targets once and report a match
+    /// against every id that shares it. see `duplicates_collapsed`
+    pub fn with_duplicate_collapse(mut self) -> Self {
+        self.collapse_duplicates = true;
+        self
+    }
+    
+    /// how many targets `with_duplicate_collapse` folds into another
+    pub fn duplicates_collapsed(&self) -> usize {
+        match self.collapse_duplicates {
+            true => collapse_duplicates(self.targets.targets()).1.values().map(Vec::len).sum(),
+            false => 0,
+        }
+    }
+    
+    /// crypt each candidate once per shared crypt setting rather than once
+    /// per target. fast algorithms already hash once per (algorithm, salt);
+    /// this extends that to md5/sha256/sha512-crypt targets with a common salt
+    pub fn with_hash_once(mut self) -> Self {
+        self.hash_once = true;
+        self
+    }
+    
+    /// give each (algorithm, salt) group `timeout` of the run's wall-clock
+    /// time, charged by its share of the hashing, then give up on its
+    /// targets still uncracked, reporting them in `CrackingResult::given_up`
+    /// rather than as unfound. a slow group retires while the rest carry
+    /// on. checked between batches
+    pub fn with_target_timeout(mut self, timeout: Duration) -> Self {
+        self.target_timeout = Some(timeout);
+        self
+    }
+    
+    /// end the run as `TerminationReason::ThresholdReached` once `count`
+    /// targets are cracked, for when cracking some of a set makes the
+    /// point. checked between batches, so a batch can overshoot it
+    pub fn with_success_threshold(mut self, count: usize) -> Self {
+        self.success_threshold = Some(count);
+        self
+    }
+    
+    /// always take the general per-group path, even when every target is
+    /// unsalted under one algorithm - for measuring what the fast path gains
+    pub fn without_fast_path(mut self) -> Self {
+        self.fast_path = false;
+        self
+    }
+    
+    /// always pull candidates through the generator one batch at a time,
+    /// even for a mask or brute-force keyspace workers could split between
+    /// them - for measuring what the parallel ranges gain
+    pub fn without_parallel_ranges(mut self) -> Self {
+        self.parallel_ranges = false;
+        self
+    }
+    
     pub fn run<F>(&mut self, mut callback: F) -> Result<CrackingResult>
     where
         F: FnMut(&Statistics),
@@ -77,94 +712,358 @@ impl Engine {
         let mut matches = Vec::new();
         let mut found_ids = std::collections::HashSet::new();
         
-        // build target index by algorithm for fast lookup
-        let mut targets_by_algo: HashMap<String, Vec<&Target>> = HashMap::new();
-        for target in &self.targets {
-            targets_by_algo
-                .entry(target.algorithm.to_string())
-                .or_insert_with(Vec::new)
-                .push(target);
+        let targets = Arc::clone(&self.targets.targets);
+        let ids: std::collections::HashSet<&str> = targets.iter().map(|t| t.id.as_str()).collect();
+        for m in std::mem::take(&mut self.known) {
+            if ids.contains(m.target_id.as_str()) && found_ids.insert(m.target_id.clone()) {
+                matches.push(m);
+            }
         }
+        self.stats.lock().unwrap().targets_found = found_ids.len();
+        
+        // identical targets are compared once, their matches fanned out
+        let (representatives, duplicates) = match self.collapse_duplicates {
+            true => collapse_duplicates(&targets),
+            false => ((0..targets.len()).collect(), HashMap::new()),
+        };
+        
+        // group targets by (algorithm, salt) so each digest is computed once
+        let hash_groups = build_hash_groups(representatives.iter().map(|&i| &targets[i]), self.hash_once);
+        // the prepared index covers every target; collapsing needs one of
+        // the representatives alone
+        let fast_index = match (self.fast_path && self.near_misses.is_none(), self.collapse_duplicates) {
+            (false, _) => None,
+            (true, false) => self.targets.index.clone(),
+            (true, true) => digest_index(&targets, &representatives).map(Arc::new),
+        };
         
         // configure rayon thread pool
-        let pool = rayon::ThreadPoolBuilder::new()
-            .num_threads(self.workers)
-            .build()?;
+        let mut builder = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.workers);
+        
+        let core_ids = match self.pin_threads {
+            true => core_affinity::get_core_ids().filter(|ids| !ids.is_empty()),
+            false => None,
+        };
+        let pin_failed = Arc::new(AtomicBool::new(false));
+        
+        if let Some(ids) = core_ids.clone() {
+            let pin_failed = Arc::clone(&pin_failed);
+            builder = builder.start_handler(move |index| {
+                if !core_affinity::set_for_current(ids[index % ids.len()]) {
+                    pin_failed.store(true, Ordering::Relaxed);
+                }
+            });
+        }
+        
+        let pool = builder.build()?;
+        
+        // the cap --batch-timeout shrinks; until it has, no batch size is over it
+        let mut effective_batch_size = self.algorithm_batch_sizes.iter()
+            .map(|&(_, size)| size)
+            .fold(self.batch_size, usize::max);
+        
+        // refilled in place every batch, so candidates aren't allocated one by one
+        let mut batch = CandidateBatch::new(self.batch_size);
+        
+        // a mask or brute-force keyspace is split between the workers instead
+        let mut ranges = match self.parallel_ranges && self.generator.seek_clone(0).is_some() {
+            true => self.generator.estimated_size().map(RangeSplitter::new),
+            false => None,
+        };
+        
+        // with ranges, one buffer per range slot, kept from round to round
+        let mut range_bufs: Vec<CandidateBatch> = Vec::new();
+        
+        // set once a range's generator runs out of time (`DeadlineGenerator`)
+        let ranges_timed_out = AtomicBool::new(false);
+        
+        // adaptive workers: hashes and time since the last measurement
+        let mut tuner = self.adaptive_workers.then(|| WorkerTuner::new(self.workers));
+        let mut window_start = Instant::now();
+        let mut window_hashes = 0u64;
+        
+        // target timeout: each group's share of the wall clock so far, and
+        // the groups retired
+        let mut group_spent = vec![Duration::ZERO; hash_groups.len()];
+        let mut retired = vec![false; hash_groups.len()];
+        let mut given_up = Vec::new();
+        
+        let near_misses = self.near_misses.map(|cap| NearMissTracker::new(cap, &targets));
         
         // main cracking loop
-        loop {
-            // check if all targets found
-            if found_ids.len() >= self.targets.len() {
-                break;
+        let termination = loop {
+            // check if all targets found (or given up on) - before fetching
+            // a batch, so a set that's already solved costs no guesses
+            if found_ids.len() + given_up.len() >= targets.len() {
+                match given_up.is_empty() {
+                    true => break TerminationReason::AllFound,
+                    false => break TerminationReason::TimeLimit,
+                }
+            }
+            
+            if self.success_threshold.is_some_and(|count| found_ids.len() >= count) {
+                break TerminationReason::ThresholdReached;
             }
             
-            // get next batch of candidates
-            let batch = match self.generator.next_batch(self.batch_size) {
-                Some(b) => b,
-                None => break,  // exhausted keyspace
+            if self.stop.as_ref().is_some_and(|stop| stop.load(Ordering::Relaxed)) {
+                break TerminationReason::Interrupted;
+            }
+            
+            let batch_start = Instant::now();
+            
+            // only hash for groups that still have unfound targets
+            let active_groups: Vec<(usize, &HashGroup)> = hash_groups.iter()
+                .enumerate()
+                .filter(|(g, _)| !retired[*g])
+                .filter(|(_, group)| group.targets.iter().any(|t| !found_ids.contains(&t.id)))
+                .collect();
+            
+            // get next batch of candidates, sized for the costliest algorithm
+            // left and within what --batch-timeout allows
+            let size = active_groups.iter()
+                .map(|(_, group)| self.algorithm_batch_sizes.iter()
+                    .find(|(algorithm, _)| *algorithm == group.algorithm)
+                    .map_or(self.batch_size, |&(_, size)| size))
+                .min()
+                .unwrap_or(self.batch_size)
+                .min(effective_batch_size);
+            let round = match ranges.as_mut() {
+                Some(splitter) => {
+                    if ranges_timed_out.load(Ordering::Relaxed) {
+                        break TerminationReason::TimeLimit;
+                    }
+                    // queued candidates (adaptive hints) go ahead of the
+                    // next round, as one ordinary batch
+                    batch.clear();
+                    batch.set_capacity(size);
+                    if self.generator.take_queued(&mut batch) {
+                        None
+                    } else {
+                        let parts = tuner.as_ref().map_or(self.workers, WorkerTuner::workers);
+                        let round = splitter.next_round(size as u128, parts);
+                        if round.is_empty() {
+                            break TerminationReason::Exhausted;
+                        }
+                        Some(round)
+                    }
+                }
+                None => {
+                    batch.set_capacity(size);
+                    if !self.generator.next_into(&mut batch) {
+                        // exhausted keyspace - unless the source itself failed,
+                        // or a stop ended its wait for more (`--follow-wordlist`)
+                        self.generator.finish()?;
+                        if self.stop.as_ref().is_some_and(|stop| stop.load(Ordering::Relaxed)) {
+                            break TerminationReason::Interrupted;
+                        }
+                        match self.generator.timed_out() {
+                            true => break TerminationReason::TimeLimit,
+                            false => break TerminationReason::Exhausted,
+                        }
+                    }
+                    None
+                }
+            };
+            
+            let batch_size = match &round {
+                Some(round) => round.iter().map(|&(_, len)| len).sum::<u128>() as u64,
+                None => batch.len() as u64,
             };
             
-            let batch_size = batch.len() as u64;
+            // matches are stamped with the count before this batch, read
+            // here so workers never take the stats lock while hashing
+            let guesses_before = self.stats.lock().unwrap().guesses_tried;
             
-            // process batch in parallel for each algorithm
-            for (algo_str, algo_targets) in &targets_by_algo {
-                if algo_targets.is_empty() {
-                    continue;
+            // candidates a range's generator didn't produce, out of time
+            let ungenerated = AtomicU64::new(0);
+            
+            // with a target timeout, each worker times its hashing per group
+            // into `spent`, flushed here once per chunk of work
+            let group_nanos: Vec<AtomicU64> = hash_groups.iter().map(|_| AtomicU64::new(0)).collect();
+            let timed_groups = match self.target_timeout {
+                Some(_) => hash_groups.len(),
+                None => 0,
+            };
+            let flush = |spent: &[u64]| {
+                for (total, &nanos) in group_nanos.iter().zip(spent) {
+                    total.fetch_add(nanos, Ordering::Relaxed);
                 }
+            };
+            
+            // each candidate is hashed once per distinct (algorithm, salt)
+            // and compared against its targets
+            let check = |candidate: &[u8], spent: &mut [u64]| -> Vec<TargetMatch> {
+                let transformed = self.transform.as_ref().map(|t| t(candidate));
+                let candidate = transformed.as_deref().unwrap_or(candidate);
+                let mut local_matches = Vec::new();
+                let found_now = |target: &Target, encoded: &[u8], matches: &mut Vec<TargetMatch>| {
+                    let time_seconds = start.elapsed().as_secs_f64();
+                    let sharing = duplicates.get(target.id.as_str()).into_iter().flatten().copied();
+                    for target in std::iter::once(target).chain(sharing) {
+                        matches.push(target_match(target, candidate, encoded, guesses_before, time_seconds));
+                    }
+                };
                 
-                // skip if all targets for this algorithm are found
-                if algo_targets.iter().all(|t| found_ids.contains(&t.id)) {
-                    continue;
+                // one group of unsalted targets: a single digest and lookup
+                if let Some(index) = &fast_index {
+                    let group = &hash_groups[0];
+                    let Some(encoded) = group.encoding.encode_candidate(candidate) else {
+                        return local_matches;
+                    };
+                    let encoded = peppered(encoded, &self.pepper);
+                    let digest = group.hash(&encoded);
+                    for i in index.get(&digest) {
+                        let target = &targets[i];
+                        if !found_ids.contains(&target.id) {
+                            found_now(target, &encoded, &mut local_matches);
+                        }
+                    }
+                    return local_matches;
                 }
                 
-                // create hasher for this algorithm
-                let hasher = super::hasher::create_hasher(algo_targets[0].algorithm);
+                for &(g, group) in &active_groups {
+                    // the group's targets were hashed from these bytes;
+                    // a candidate the encoding can't represent can't match
+                    let Some(encoded) = group.encoding.encode_candidate(candidate) else {
+                        continue;
+                    };
+                    let clock = (!spent.is_empty()).then(Instant::now);
+                    let encoded = peppered(encoded, &self.pepper);
+                    
+                    // kdf groups verify against each target's phc string instead,
+                    // unless they share a crypt setting
+                    let hash = match (&group.setting, group.algorithm.is_kdf()) {
+                        (Some(setting), _) => crypt(group.algorithm, setting, &encoded).map(String::into_bytes),
+                        (None, true) => None,
+                        (None, false) => Some(group.hash(&encoded)),
+                    };
+                    
+                    for target in &group.targets {
+                        // skip if already found
+                        if found_ids.contains(&target.id) {
+                            continue;
+                        }
+                        
+                        // check match
+                        let matched = match (&hash, &group.setting) {
+                            (Some(hash), Some(_)) => hash == target.hash.as_bytes(),
+                            (Some(hash), None) => target.matches(hash),
+                            (None, _) => target.verify_kdf(&encoded),
+                        };
+                        
+                        if matched {
+                            found_now(target, &encoded, &mut local_matches);
+                        } else if let (Some(tracker), Some(hash), Algorithm::BlitzHash) = (&near_misses, &hash, group.algorithm) {
+                            tracker.observe(target, candidate, hash);
+                        }
+                    }
+                    
+                    if let Some(clock) = clock {
+                        spent[g] += clock.elapsed().as_nanos() as u64;
+                    }
+                }
                 
-                // process batch in parallel
-                let batch_matches: Vec<TargetMatch> = pool.install(|| {
-                    batch.par_iter()
-                        .flat_map(|candidate| {
-                            let mut local_matches = Vec::new();
-                            
-                            for target in algo_targets.iter() {
-                                // skip if already found
-                                if found_ids.contains(&target.id) {
-                                    continue;
-                                }
-                                
-                                // compute hash
-                                let hash = if target.salt.is_empty() {
-                                    hasher.hash(candidate)
-                                } else {
-                                    hasher.hash_with_salt(candidate, &target.salt_bytes())
-                                };
-                                
-                                // check match
-                                if target.matches(&hash) {
-                                    let stats = self.stats.lock().unwrap();
-                                    let time_elapsed = start.elapsed().as_secs_f64();
-                                    
-                                    local_matches.push(TargetMatch {
-                                        target_id: target.id.clone(),
-                                        username: target.username.clone(),
-                                        password: candidate.clone(),
-                                        algorithm: target.algorithm,
-                                        guesses_tried: stats.guesses_tried,
-                                        time_seconds: time_elapsed,
-                                    });
-                                }
+                local_matches
+            };
+            
+            // one parallel pass. adaptive mode splits the batch into one chunk
+            // per active worker, so the rest of the pool stays parked
+            // with ranges, each worker generates its own into its own buffer
+            let feeds: Option<Vec<(Box<dyn Generator>, usize)>> = round.map(|round| round.into_iter()
+                .map(|(start, len)| (self.generator.seek_clone(start).expect("queued candidates taken first"), len as usize))
+                .collect());
+            if let Some(feeds) = &feeds {
+                range_bufs.resize_with(range_bufs.len().max(feeds.len()), CandidateBatch::default);
+            }
+            let batch_matches: Vec<TargetMatch> = pool.install(|| match (feeds, &tuner) {
+                (Some(feeds), _) => {
+                    feeds.into_par_iter()
+                        .zip(range_bufs.par_iter_mut())
+                        .flat_map_iter(|((mut generator, len), buf)| {
+                            buf.set_capacity(len);
+                            generator.next_into(buf);
+                            if generator.timed_out() {
+                                ranges_timed_out.store(true, Ordering::Relaxed);
+                                ungenerated.fetch_add((len - buf.len()) as u64, Ordering::Relaxed);
                             }
-                            
-                            local_matches
+                            let mut spent = vec![0; timed_groups];
+                            let found: Vec<_> = buf.iter().flat_map(|c| check(c, &mut spent)).collect();
+                            flush(&spent);
+                            found
                         })
                         .collect()
-                });
-                
-                // record found matches
-                for m in batch_matches {
-                    if found_ids.insert(m.target_id.clone()) {
-                        matches.push(m);
+                }
+                (None, Some(tuner)) => {
+                    let chunk = batch.len().div_ceil(tuner.workers()).max(1);
+                    (0..batch.len()).into_par_iter()
+                        .step_by(chunk)
+                        .flat_map_iter(|first| {
+                            let mut spent = vec![0; timed_groups];
+                            let found: Vec<_> = (first..batch.len().min(first + chunk))
+                                .flat_map(|i| check(batch.get(i), &mut spent))
+                                .collect();
+                            flush(&spent);
+                            found
+                        })
+                        .collect()
+                }
+                (None, None) => batch.par_iter()
+                    .fold(|| (Vec::new(), vec![0; timed_groups]), |(mut found, mut spent), c| {
+                        found.extend(check(c, &mut spent));
+                        (found, spent)
+                    })
+                    .flat_map_iter(|(found, spent)| {
+                        flush(&spent);
+                        found
+                    })
+                    .collect(),
+            });
+            
+            let batch_size = batch_size - ungenerated.load(Ordering::Relaxed);
+            
+            // record found matches
+            let mut new_matches = Vec::new();
+            for m in batch_matches {
+                if found_ids.insert(m.target_id.clone()) {
+                    self.generator.found(&m.password);
+                    new_matches.push(m.clone());
+                    matches.push(m);
+                }
+            }
+            
+            // target timeout: charge the batch's wall time to the groups by
+            // their share of the hashing (all of it to the fast path's one
+            // group), and retire the ones over budget
+            if let Some(timeout) = self.target_timeout {
+                let elapsed = batch_start.elapsed();
+                let nanos: Vec<u64> = active_groups.iter().map(|&(g, _)| group_nanos[g].load(Ordering::Relaxed)).collect();
+                let total: u64 = nanos.iter().sum();
+                for (&(g, group), &spent) in active_groups.iter().zip(&nanos) {
+                    group_spent[g] += match total {
+                        0 => elapsed / active_groups.len() as u32,
+                        _ => elapsed.mul_f64(spent as f64 / total as f64),
+                    };
+                    if group_spent[g] < timeout {
+                        continue;
                     }
+                    retired[g] = true;
+                    for &target in &group.targets {
+                        let sharing = duplicates.get(target.id.as_str()).into_iter().flatten().copied();
+                        for target in std::iter::once(target).chain(sharing) {
+                            if !found_ids.contains(&target.id) {
+                                given_up.push(target.id.clone());
+                            }
+                        }
+                    }
+                }
+            }
+            
+            // slow hashers: shrink subsequent batches if this one overran
+            if let Some(timeout) = self.batch_timeout {
+                let adapted = adapt_batch_size(size, batch_start.elapsed(), timeout);
+                if adapted < size {
+                    effective_batch_size = adapted;
                 }
             }
             
@@ -172,17 +1071,33 @@ impl Engine {
             {
                 let mut stats = self.stats.lock().unwrap();
                 stats.guesses_tried += batch_size;
-                stats.hashes_computed += batch_size * targets_by_algo.len() as u64;
+                stats.hashes_computed += batch_size * active_groups.len() as u64;
                 stats.targets_found = found_ids.len();
+                stats.progress = match &ranges {
+                    Some(splitter) => Some(splitter.progress()),
+                    None => self.generator.progress(),
+                };
+                stats.new_matches = new_matches;
                 stats.update_throughput();
             }
             
+            // adaptive workers: one throughput reading per window
+            if let Some(tuner) = tuner.as_mut() {
+                window_hashes += batch_size * active_groups.len() as u64;
+                let elapsed = window_start.elapsed();
+                if elapsed >= TUNE_WINDOW {
+                    tuner.observe(window_hashes as f64 / elapsed.as_secs_f64());
+                    window_start = Instant::now();
+                    window_hashes = 0;
+                }
+            }
+            
             // callback for ui updates
             {
                 let stats = self.stats.lock().unwrap();
                 callback(&stats);
             }
-        }
+        };
         
         let total_time = start.elapsed().as_secs_f64();
         let final_stats = self.stats.lock().unwrap().clone();
@@ -191,10 +1106,928 @@ impl Engine {
             matches,
             statistics: final_stats,
             total_time,
+            final_batch_size: effective_batch_size.min(self.batch_size),
+            threads_pinned: core_ids.is_some() && !pin_failed.load(Ordering::Relaxed),
+            termination,
+            adaptive_workers: tuner.map(|t| t.workers()),
+            given_up,
+            near_misses: near_misses.map(NearMissTracker::into_misses).unwrap_or_default(),
         })
     }
     
+    /// run on a background thread, yielding each match as it's found:
+    /// `for m in engine.matches() { .. }`. the stream shares the engine's
+    /// stop flag if it has one
+    pub fn matches(mut self) -> MatchStream {
+        let stop = Arc::clone(self.stop.get_or_insert_with(Default::default));
+        self.generator.watch_stop(Arc::clone(&stop));
+        let (sender, receiver) = mpsc::channel();
+        
+        let handle = std::thread::spawn(move || {
+            self.run(|stats| {
+                for m in &stats.new_matches {
+                    // the stream may already be gone; the run still finishes
+                    sender.send(m.clone()).ok();
+                }
+            })
+        });
+        
+        MatchStream { receiver, stop, handle: Some(handle) }
+    }
+    
+    /// targets with no match in `result` - what survived the attack
+    pub fn unfound_targets(&self, result: &CrackingResult) -> Vec<&Target> {
+        let found: std::collections::HashSet<&str> = result.matches.iter()
+            .map(|m| m.target_id.as_str())
+            .collect();
+        
+        self.targets.targets().iter()
+            .filter(|t| !found.contains(t.id.as_str()))
+            .collect()
+    }
+    
     pub fn get_stats(&self) -> Statistics {
         self.stats.lock().unwrap().clone()
     }
-}
\ No newline at end of file
+}
+
+/// matches from an engine running on its own thread (`Engine::matches`).
+/// iteration ends when the run does; dropping the stream early stops the
+/// run after its current batch and waits for the thread
+pub struct MatchStream {
+    receiver: Receiver<TargetMatch>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<Result<CrackingResult>>>,
+}
+
+impl MatchStream {
+    /// stop the run after its current batch; iteration ends soon after
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+    
+    /// wait for the run to end and return its result, the streamed
+    /// matches included
+    pub fn finish(mut self) -> Result<CrackingResult> {
+        let handle = self.handle.take().expect("only taken here or on drop");
+        handle.join().map_err(|_| anyhow::anyhow!("engine thread panicked"))?
+    }
+}
+
+impl Iterator for MatchStream {
+    type Item = TargetMatch;
+    
+    fn next(&mut self) -> Option<TargetMatch> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl Drop for MatchStream {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.stop();
+            handle.join().ok();
+        }
+    }
+}
+
+/// crack a single hash with `generator`, for callers who just want the
+/// password: no ui, callbacks or statistics. `None` if the keyspace runs
+/// out without a match, or `hash` isn't a valid `algo` hash
+pub fn crack_one(hash: &str, algo: Algorithm, salt: Option<&str>, generator: Box<dyn Generator>) -> Option<Vec<u8>> {
+    let target = Target {
+        id: "target".to_string(),
+        username: String::new(),
+        algorithm: algo,
+        hash: hash.to_string(),
+        salt: salt.unwrap_or_default().to_string(),
+        ..Default::default()
+    };
+    target.validate().ok()?;
+    
+    let workers = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let result = Engine::new(vec![target], generator, workers, 4096)
+        .run(|_| {})
+        .ok()?;
+    result.matches.into_iter().next().map(|m| m.password)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::hasher::create_hasher;
+    use crate::core::{Algorithm, HintGenerator, MaskGenerator};
+
+    fn target(id: &str, algorithm: Algorithm, password: &[u8]) -> Target {
+        Target {
+            id: id.to_string(),
+            username: format!("user_{}", id),
+            algorithm,
+            hash: hex::encode(create_hasher(algorithm).hash(password)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_truncated_sha256_target() {
+        let mut truncated = target("short", Algorithm::Sha256, b"zq");
+        truncated.hash.truncate(16);
+        truncated.hash_bytes = Some(8);
+
+        // alone and beside another group: the digest lookup keys full
+        // digests, so truncated targets always take the general path
+        for targets in [vec![truncated.clone()], vec![truncated.clone(), target("m", Algorithm::Md5, b"ab")]] {
+            let count = targets.len();
+            let result = Engine::new(targets, Box::new(MaskGenerator::new("?l?l").unwrap()), 2, 64)
+                .run(|_| {})
+                .unwrap();
+            assert_eq!(result.matches.len(), count);
+            assert!(result.matches.iter().any(|m| m.target_id == "short" && m.password == b"zq"));
+        }
+    }
+
+    #[test]
+    fn test_peppered_targets_need_the_pepper() {
+        // alone (the digest lookup) and beside another group (the general path)
+        for extra in [None, Some(target("s", Algorithm::Sha256, b"zzs3cret"))] {
+            let mut targets = vec![target("p", Algorithm::Md5, b"abs3cret")];
+            targets.extend(extra);
+            let crack = |pepper: &[u8]| {
+                Engine::new(targets.clone(), Box::new(MaskGenerator::new("?l?l").unwrap()), 2, 64)
+                    .with_pepper(pepper.to_vec())
+                    .run(|_| {})
+                    .unwrap()
+                    .matches
+            };
+
+            assert!(crack(b"").is_empty());
+            assert!(crack(b"wrong").is_empty());
+            let found = crack(b"s3cret");
+            assert_eq!(found.len(), targets.len());
+            assert!(found.iter().any(|m| m.target_id == "p" && m.password == b"ab"));
+        }
+    }
+
+    #[test]
+    fn test_match_stream_yields_every_match() {
+        let targets = vec![
+            target("a", Algorithm::Md5, b"ab"),
+            target("b", Algorithm::Sha256, b"zy"),
+            target("c", Algorithm::Md5, b"none"),
+        ];
+        let engine = Engine::new(targets, Box::new(MaskGenerator::new("?l?l").unwrap()), 2, 16);
+
+        let mut stream = engine.matches();
+        let mut found: Vec<(String, Vec<u8>)> = stream.by_ref().map(|m| (m.target_id, m.password)).collect();
+        found.sort();
+        assert_eq!(found, vec![("a".to_string(), b"ab".to_vec()), ("b".to_string(), b"zy".to_vec())]);
+
+        let result = stream.finish().unwrap();
+        assert_eq!(result.termination, TerminationReason::Exhausted);
+        assert_eq!(result.matches.len(), 2);
+
+        // dropped before the end: the run stops instead of searching on
+        let engine = Engine::new(vec![target("d", Algorithm::Md5, b"none")], Box::new(MaskGenerator::new("?l?l?l?l?l?l").unwrap()), 2, 16);
+        let started = Instant::now();
+        drop(engine.matches());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_algorithm_groups_have_stable_order() {
+        let targets = vec![
+            target("a", Algorithm::Sha256, b"x"),
+            target("b", Algorithm::Md5, b"x"),
+            target("c", Algorithm::BlitzHash, b"x"),
+            target("d", Algorithm::Sha1, b"x"),
+            target("e", Algorithm::Md5, b"y"),
+        ];
+
+        for _ in 0..10 {
+            let groups = build_hash_groups(&targets, false);
+            let order: Vec<String> = groups.iter().map(|g| g.algorithm.to_string()).collect();
+            assert_eq!(order, vec!["blitzhash", "md5", "sha1", "sha256"]);
+
+            // targets keep their file order within a group
+            let md5_ids: Vec<&str> = groups[1].targets.iter().map(|t| t.id.as_str()).collect();
+            assert_eq!(md5_ids, vec!["b", "e"]);
+        }
+    }
+
+    #[test]
+    fn test_high_priority_target_reported_first() {
+        let low = target("low", Algorithm::Md5, b"a1");
+        let mut high = target("high", Algorithm::Md5, b"a1");
+        high.priority = Some(10);
+
+        let generator = MaskGenerator::new("?l?d").unwrap();
+        let mut engine = Engine::new(vec![low, high], Box::new(generator), 2, 64);
+        let result = engine.run(|_| {}).unwrap();
+
+        let ids: Vec<&str> = result.matches.iter().map(|m| m.target_id.as_str()).collect();
+        assert_eq!(ids, vec!["high", "low"]);
+    }
+
+    #[test]
+    fn test_found_password_matches_expected() {
+        let mut known = target("known", Algorithm::Md5, b"a1");
+        known.expected_password = Some("a1".to_string());
+
+        let generator = MaskGenerator::new("?l?d").unwrap();
+        let mut engine = Engine::new(vec![known, target("plain", Algorithm::Md5, b"b2")], Box::new(generator), 2, 64);
+        let result = engine.run(|_| {}).unwrap();
+
+        let by_id = |id: &str| result.matches.iter().find(|m| m.target_id == id).unwrap();
+        assert_eq!(by_id("known").matches_expected(), Some(true));
+        assert_eq!(by_id("plain").matches_expected(), None);
+    }
+
+    #[test]
+    fn test_collision_differs_from_expected() {
+        // stands in for a collision: the digest is "a1"'s, the answer isn't
+        let mut collided = target("c", Algorithm::Md5, b"a1");
+        collided.expected_password = Some("a1-original".to_string());
+
+        let generator = MaskGenerator::new("?l?d").unwrap();
+        let mut engine = Engine::new(vec![collided], Box::new(generator), 2, 64);
+        let result = engine.run(|_| {}).unwrap();
+
+        assert_eq!(result.matches[0].password, b"a1");
+        assert_eq!(result.matches[0].matches_expected(), Some(false));
+    }
+
+    #[test]
+    fn test_utf16le_target_cracked_from_utf8_candidate() {
+        let mut ntlm = target("n", Algorithm::Md4, b"");
+        ntlm.hash = hex::encode(create_hasher(Algorithm::Md4).hash(b"c\0a\0f\0\xe9\0"));
+        ntlm.password_encoding = Some(PasswordEncoding::Utf16le);
+        // same password, hashed as utf-8
+        let plain = target("p", Algorithm::Md4, "café".as_bytes());
+
+        let candidates = ["cafe", "café"].iter().map(|w| w.as_bytes().to_vec()).collect();
+        let generator = crate::core::MemoryGenerator::new(candidates);
+        let mut engine = Engine::new(vec![ntlm, plain], Box::new(generator), 2, 64);
+        let result = engine.run(|_| {}).unwrap();
+
+        // both found, each reported as the readable utf-8 candidate
+        assert_eq!(result.matches.len(), 2);
+        assert!(result.matches.iter().all(|m| m.password_string() == "café"));
+    }
+
+    #[test]
+    fn test_hash_groups_split_by_salt() {
+        let mut salted = target("s", Algorithm::Md5, b"x");
+        salted.salt = "pepper".to_string();
+        let targets = vec![target("a", Algorithm::Md5, b"x"), salted, target("b", Algorithm::Md5, b"y")];
+
+        let groups = build_hash_groups(&targets, false);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].salt, b"");
+        assert_eq!(groups[0].targets.len(), 2);
+        assert_eq!(groups[1].salt, b"pepper");
+    }
+
+    #[test]
+    fn test_empty_salt_salted_or_unsalted() {
+        // same digest either way, but declared salted it's hashed as such
+        let mut salted = target("s", Algorithm::Md5, b"x");
+        salted.salted = Some(true);
+        let targets = vec![target("a", Algorithm::Md5, b"x"), salted.clone()];
+
+        let groups = build_hash_groups(&targets, false);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups.iter().map(|g| (g.salt.as_slice(), g.salted)).collect::<Vec<_>>(), [(&b""[..], false), (&b""[..], true)]);
+        assert!(!PreparedTargets::new(vec![salted]).is_indexed());
+
+        let mut engine = Engine::new(targets, Box::new(MaskGenerator::new("?l").unwrap()), 1, 64);
+        let result = engine.run(|_| {}).unwrap();
+        assert_eq!(result.matches.len(), 2);
+        assert!(result.matches.iter().all(|m| m.password == b"x"));
+    }
+
+    #[test]
+    fn test_fast_path_matches_general_path() {
+        // two users share a password, so one digest maps to both targets
+        let mut shared = target("shared", Algorithm::Md5, b"b2");
+        shared.priority = Some(5);
+        let targets = vec![
+            target("a", Algorithm::Md5, b"a1"),
+            target("b", Algorithm::Md5, b"b2"),
+            shared,
+            target("z", Algorithm::Md5, b"zzz"),
+        ];
+        assert!(PreparedTargets::new(targets.clone()).is_indexed());
+
+        let run = |engine: Engine| {
+            let mut engine = engine;
+            let result = engine.run(|_| {}).unwrap();
+            let found: Vec<(String, Vec<u8>)> = result.matches.iter()
+                .map(|m| (m.target_id.clone(), m.password.clone()))
+                .collect();
+            (found, result.statistics.hashes_computed)
+        };
+        let engine = |targets: Vec<Target>| {
+            Engine::new(targets, Box::new(MaskGenerator::new("?l?d").unwrap()), 2, 64)
+        };
+
+        let fast = run(engine(targets.clone()));
+        let general = run(engine(targets).without_fast_path());
+        assert_eq!(fast, general);
+        assert_eq!(fast.0.len(), 3);
+        assert_eq!(fast.0[1], ("shared".to_string(), b"b2".to_vec()));
+    }
+
+    #[test]
+    fn test_fast_path_needs_one_unsalted_group() {
+        let mut salted = target("s", Algorithm::Md5, b"x");
+        salted.salt = "pepper".to_string();
+        let mut kdf = target("k", Algorithm::Scrypt, b"");
+        kdf.hash = crate::core::hasher::hash_phc(Algorithm::Scrypt, b"x", b"demosalt").unwrap();
+
+        for targets in [
+            vec![target("a", Algorithm::Md5, b"x"), target("b", Algorithm::Sha1, b"x")],
+            vec![salted],
+            vec![kdf],
+        ] {
+            assert!(!PreparedTargets::new(targets).is_indexed());
+        }
+    }
+
+    #[test]
+    fn test_prepared_targets_serve_several_runs() {
+        let prepared = PreparedTargets::new(vec![
+            target("letters", Algorithm::Md5, b"ab"),
+            target("digits", Algorithm::Md5, b"42"),
+        ]);
+        assert!(prepared.is_indexed());
+        
+        // one generator per run, the digest index built only once
+        let run = |mask: &str| {
+            let mut engine = Engine::new(prepared.clone(), Box::new(MaskGenerator::new(mask).unwrap()), 2, 64);
+            let result = engine.run(|_| {}).unwrap();
+            result.matches.into_iter().map(|m| m.target_id).collect::<Vec<_>>()
+        };
+        assert_eq!(run("?l?l"), vec!["letters"]);
+        assert_eq!(run("?d?d"), vec!["digits"]);
+        assert_eq!(Arc::strong_count(&prepared.targets), 1);
+    }
+    
+    #[test]
+    fn test_near_misses_are_the_closest_by_prefix() {
+        // uppercase, so nothing in the keyspace matches
+        let wanted = create_hasher(Algorithm::BlitzHash).hash(b"QZ");
+        let targets = vec![target("q", Algorithm::BlitzHash, b"QZ")];
+        let mut engine = Engine::new(targets, Box::new(MaskGenerator::new("?l?l?l").unwrap()), 2, 256)
+            .with_near_misses(5);
+        let misses = engine.run(|_| {}).unwrap().near_misses;
+        
+        let shared = |digest: &[u8]| digest.iter().zip(&wanted).take_while(|(a, b)| a == b).count();
+        let mut best: Vec<usize> = MaskGenerator::new("?l?l?l").unwrap()
+            .next_batch(26 * 26 * 26)
+            .unwrap()
+            .iter()
+            .map(|c| shared(&create_hasher(Algorithm::BlitzHash).hash(c)))
+            .collect();
+        best.sort_unstable_by(|a, b| b.cmp(a));
+        
+        let found: Vec<usize> = misses.iter().map(|m| m.shared_bytes).collect();
+        assert_eq!(found, best[..5]);
+        assert!(found[0] > 0);
+        for miss in &misses {
+            assert_eq!(miss.target_id, "q");
+            assert_eq!(miss.digest, create_hasher(Algorithm::BlitzHash).hash(&miss.candidate));
+            assert_eq!(shared(&miss.digest), miss.shared_bytes);
+        }
+    }
+    
+    #[test]
+    fn test_range_rounds_cover_the_keyspace() {
+        for (total, size, parts) in [(0, 10, 4), (1, 10, 4), (100, 10, 4), (101, 7, 3), (5, 64, 8), (1000, 1000, 1)] {
+            let mut splitter = RangeSplitter::new(total);
+            let mut covered = 0u128;
+            loop {
+                let round = splitter.next_round(size, parts);
+                if round.is_empty() {
+                    break;
+                }
+                assert!(round.len() <= parts);
+                assert_eq!(round.iter().map(|&(_, len)| len).sum::<u128>(), size.min(total - covered));
+                // contiguous, disjoint and none empty
+                for (start, len) in round {
+                    assert_eq!(start, covered);
+                    assert!(len > 0);
+                    covered += len;
+                }
+            }
+            assert_eq!(covered, total);
+            assert_eq!(splitter.progress(), 1.0);
+        }
+    }
+    
+    #[test]
+    fn test_parallel_ranges_match_the_batch_path() {
+        let targets = vec![
+            target("first", Algorithm::Md5, b"aa0"),
+            target("middle", Algorithm::Sha1, b"mq7"),
+            target("last", Algorithm::Md5, b"zz9"),
+        ];
+        let run = |engine: Engine| {
+            let mut engine = engine;
+            let result = engine.run(|_| {}).unwrap();
+            let found: Vec<(String, Vec<u8>, u64)> = result.matches.iter()
+                .map(|m| (m.target_id.clone(), m.password.clone(), m.guesses_tried))
+                .collect();
+            (found, result.statistics.guesses_tried, result.statistics.progress)
+        };
+        let engine = |batch_size: usize| {
+            Engine::new(targets.clone(), Box::new(MaskGenerator::new("?l?l?d").unwrap()), 3, batch_size)
+        };
+        
+        for batch_size in [1, 100, 1000, 10_000] {
+            let ranges = run(engine(batch_size));
+            assert_eq!(ranges, run(engine(batch_size).without_parallel_ranges()), "batch size {}", batch_size);
+            assert_eq!(ranges.0.len(), 3);
+        }
+    }
+    
+    #[test]
+    fn test_duplicate_targets_collapse_and_fan_out() {
+        let salted = |id: &str| {
+            let mut t = target(id, Algorithm::Md5, b"");
+            t.salt = "pepper".to_string();
+            t.hash = hex::encode(create_hasher(Algorithm::Md5).hash_with_salt(b"a1", b"pepper"));
+            t
+        };
+        // same digest written in upper case is still the same target
+        let mut upper = target("upper", Algorithm::Md5, b"a1");
+        upper.hash = upper.hash.to_uppercase();
+        let targets = vec![
+            target("a", Algorithm::Md5, b"a1"),
+            salted("s1"),
+            upper,
+            salted("s2"),
+            target("sha", Algorithm::Sha1, b"a1"),
+        ];
+        
+        {
+            let (representatives, sharing) = collapse_duplicates(&targets);
+            let ids: Vec<&str> = representatives.iter().map(|&i| targets[i].id.as_str()).collect();
+            assert_eq!(ids, vec!["a", "s1", "sha"]);
+            assert_eq!(sharing["a"][0].id, "upper");
+            assert_eq!(sharing["s1"][0].id, "s2");
+        }
+        
+        let run = |engine: Engine| {
+            let mut engine = engine;
+            let result = engine.run(|_| {}).unwrap();
+            let mut found: Vec<(String, Vec<u8>)> = result.matches.iter()
+                .map(|m| (m.target_id.clone(), m.password.clone()))
+                .collect();
+            found.sort();
+            found
+        };
+        let engine = |targets: Vec<Target>| {
+            Engine::new(targets, Box::new(MaskGenerator::new("?l?d").unwrap()), 2, 64)
+        };
+        
+        let collapsed = engine(targets.clone()).with_duplicate_collapse();
+        assert_eq!(collapsed.duplicates_collapsed(), 2);
+        assert_eq!(engine(targets.clone()).duplicates_collapsed(), 0);
+        
+        let found = run(collapsed);
+        assert_eq!(found.len(), 5);
+        assert_eq!(found, run(engine(targets)));
+    }
+    
+    #[test]
+    fn test_hash_once_shares_crypt_settings_without_changing_results() {
+        let md5_crypt = |id: &str, setting: &str, password: &[u8]| {
+            let mut t = target(id, Algorithm::Md5Crypt, b"");
+            t.hash = crypt(Algorithm::Md5Crypt, setting, password).unwrap();
+            t
+        };
+        let targets = vec![
+            md5_crypt("a", "$1$pepper", b"a1"),
+            md5_crypt("b", "$1$pepper", b"b2"),
+            md5_crypt("c", "$1$pepper", b"not-in-keyspace"),
+            md5_crypt("d", "$1$nacl", b"a1"),
+        ];
+        assert_eq!(build_hash_groups(&targets, false).len(), 4);
+        assert_eq!(build_hash_groups(&targets, true).len(), 2);
+        
+        let run = |hash_once: bool| {
+            let generator = MaskGenerator::new("?l?d").unwrap();
+            let mut engine = Engine::new(targets.clone(), Box::new(generator), 2, 64);
+            if hash_once {
+                engine = engine.with_hash_once();
+            }
+            let result = engine.run(|_| {}).unwrap();
+            let mut found: Vec<(String, Vec<u8>)> = result.matches.iter()
+                .map(|m| (m.target_id.clone(), m.password.clone()))
+                .collect();
+            found.sort();
+            (found, result.statistics.hashes_computed)
+        };
+        
+        let (shared, shared_hashes) = run(true);
+        let (separate, separate_hashes) = run(false);
+        assert_eq!(shared.len(), 3);
+        assert_eq!(shared, separate);
+        assert!(shared_hashes < separate_hashes);
+    }
+    
+    #[test]
+    fn test_many_matches_stamped_with_batch_start() {
+        // every candidate cracks a target: a match on every hash, from every worker
+        let targets: Vec<Target> = (0..1000)
+            .map(|n| target(&format!("t{}", n), Algorithm::Md5, format!("{:03}", n).as_bytes()))
+            .collect();
+        
+        for fast_path in [true, false] {
+            let generator = MaskGenerator::new("?d?d?d").unwrap();
+            let mut engine = Engine::new(targets.clone(), Box::new(generator), 8, 64);
+            if !fast_path {
+                engine = engine.without_fast_path();
+            }
+            let result = engine.run(|_| {}).unwrap();
+            
+            assert_eq!(result.termination, TerminationReason::AllFound);
+            assert_eq!(result.matches.len(), 1000);
+            for m in &result.matches {
+                // "042" is the 43rd candidate, in the batch starting at 0
+                let index: u64 = m.password_string().parse().unwrap();
+                assert_eq!(m.guesses_tried, index / 64 * 64, "{}", m.target_id);
+            }
+        }
+    }
+    
+    #[test]
+    fn test_target_timeout_retires_slow_target() {
+        // bcrypt costs about a millisecond a candidate; md5 next to nothing
+        let mut slow = target("slow", Algorithm::Bcrypt, b"");
+        slow.hash = bcrypt::hash_with_salt(b"not-in-keyspace", 4, [7u8; 16]).unwrap()
+            .format_for_version(bcrypt::Version::TwoB);
+        let targets = vec![slow, target("early", Algorithm::Md5, b"ab1"), target("late", Algorithm::Md5, b"zz9")];
+        
+        let generator = MaskGenerator::new("?l?l?d").unwrap();
+        let mut engine = Engine::new(targets, Box::new(generator), 2, 64)
+            .with_target_timeout(Duration::from_millis(200));
+        let result = engine.run(|_| {}).unwrap();
+        
+        // bcrypt uses up its budget and retires; the md5 group has barely
+        // touched its own, so its targets still crack, the last one only
+        // once bcrypt stops taking the time
+        assert_eq!(result.given_up, vec!["slow".to_string()]);
+        let mut found: Vec<&str> = result.matches.iter().map(|m| m.target_id.as_str()).collect();
+        found.sort();
+        assert_eq!(found, vec!["early", "late"]);
+        assert_eq!(result.termination, TerminationReason::TimeLimit);
+        assert!(result.total_time >= 0.2 && result.total_time < 5.0, "{}", result.total_time);
+    }
+    
+    #[test]
+    fn test_success_threshold_stops_after_count() {
+        let passwords: [&[u8]; 5] = [b"a0", b"a5", b"b0", b"c9", b"z9"];
+        let targets: Vec<Target> = passwords.iter().enumerate()
+            .map(|(i, pw)| target(&format!("t{}", i), Algorithm::Md5, pw))
+            .collect();
+
+        // batches of 10 candidates: "a0".."a9" holds two, "b0".."b9" a third
+        let mut engine = Engine::new(targets.clone(), Box::new(MaskGenerator::new("?l?d").unwrap()), 1, 10)
+            .with_success_threshold(3);
+        let result = engine.run(|_| {}).unwrap();
+        assert_eq!(result.termination, TerminationReason::ThresholdReached);
+        assert_eq!(result.matches.len(), 3);
+        assert_eq!(result.statistics.guesses_tried, 20);
+
+        // a threshold the set can't reach runs to the end as usual
+        let mut engine = Engine::new(targets, Box::new(MaskGenerator::new("?l?d").unwrap()), 1, 10)
+            .with_success_threshold(5);
+        let result = engine.run(|_| {}).unwrap();
+        assert_eq!(result.termination, TerminationReason::AllFound);
+    }
+
+    #[test]
+    fn test_hints_go_ahead_of_ranges() {
+        // "a00!" is outside the mask: only a hint from cracking "a00" reaches it
+        let targets = vec![target("plain", Algorithm::Md5, b"a00"), target("hinted", Algorithm::Md5, b"a00!")];
+        let generator = HintGenerator::new(Box::new(MaskGenerator::new("?l?d?d").unwrap()));
+        let mut engine = Engine::new(targets, Box::new(generator), 2, 16);
+        let result = engine.run(|_| {}).unwrap();
+        assert_eq!(result.termination, TerminationReason::AllFound);
+        assert_eq!(result.matches.len(), 2);
+        // found within the hint batches, not at the end of the keyspace
+        assert!(result.statistics.guesses_tried < 2600, "{}", result.statistics.guesses_tried);
+    }
+
+    #[test]
+    fn test_termination_reasons() {
+        let run = |generator: Box<dyn Generator>, stop: bool| {
+            let targets = vec![target("a", Algorithm::Md5, b"42"), target("b", Algorithm::Md5, b"never")];
+            let mut engine = Engine::new(targets, generator, 2, 16)
+                .with_stop_flag(Arc::new(AtomicBool::new(stop)));
+            engine.run(|_| {}).unwrap()
+        };
+        let digits = || Box::new(MaskGenerator::new("?d?d").unwrap());
+
+        let exhausted = run(digits(), false);
+        assert_eq!(exhausted.termination, TerminationReason::Exhausted);
+        assert_eq!(exhausted.matches.len(), 1);
+
+        let timed_out = run(Box::new(crate::core::DeadlineGenerator::new(digits(), Duration::ZERO)), false);
+        assert_eq!(timed_out.termination, TerminationReason::TimeLimit);
+        assert_eq!(timed_out.statistics.guesses_tried, 0);
+
+        let interrupted = run(digits(), true);
+        assert_eq!(interrupted.termination, TerminationReason::Interrupted);
+        assert_eq!(interrupted.statistics.guesses_tried, 0);
+
+        let targets = vec![target("a", Algorithm::Md5, b"42")];
+        let mut engine = Engine::new(targets, digits(), 2, 16);
+        assert_eq!(engine.run(|_| {}).unwrap().termination, TerminationReason::AllFound);
+    }
+
+    #[test]
+    fn test_mixed_targets_count_one_hash_per_group() {
+        let mut salted = target("s", Algorithm::Sha1, b"");
+        salted.salt = "nacl".to_string();
+        salted.hash = hex::encode(create_hasher(Algorithm::Sha1).hash_with_salt(b"99", b"nacl"));
+        let targets = vec![
+            target("a", Algorithm::Md5, b"zz"),
+            target("b", Algorithm::Md5, b"yy"),
+            target("c", Algorithm::Sha256, b"xx"),
+            salted,
+        ];
+        let generator = MaskGenerator::new("?d?d").unwrap();
+        let mut engine = Engine::new(targets, Box::new(generator), 2, 100);
+        let result = engine.run(|_| {}).unwrap();
+
+        // "99" is found for the salted sha1 target; the other groups exhaust
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.matches[0].target_id, "s");
+        // md5 (unsalted), sha1 (salted), sha256: three hash calls per candidate
+        assert_eq!(result.statistics.hashes_computed, 100 * 3);
+    }
+
+    #[test]
+    fn test_salt_after_gets_its_own_group() {
+        let md5 = create_hasher(Algorithm::Md5);
+        let mut before = target("before", Algorithm::Md5, b"");
+        before.salt = "$HEX[00ff]".to_string();
+        before.hash = hex::encode(md5.hash_with_salt(b"42", &[0x00, 0xff]));
+        let mut after = before.clone();
+        after.id = "after".to_string();
+        after.salt_after = Some(true);
+        after.hash = hex::encode(md5.hash(b"17\x00\xff"));
+
+        let targets = vec![before, after];
+        let groups = build_hash_groups(&targets, false);
+        assert_eq!(groups.iter().map(|g| g.salt_after).collect::<Vec<_>>(), [false, true]);
+
+        let generator = MaskGenerator::new("?d?d").unwrap();
+        let mut engine = Engine::new(targets.clone(), Box::new(generator), 2, 100);
+        let result = engine.run(|_| {}).unwrap();
+        let mut found: Vec<(&str, &[u8])> = result.matches.iter()
+            .map(|m| (m.target_id.as_str(), m.password.as_slice()))
+            .collect();
+        found.sort();
+        assert_eq!(found, [("after", &b"17"[..]), ("before", &b"42"[..])]);
+    }
+
+    #[test]
+    fn test_cracks_scrypt_phc_target() {
+        let mut kdf = target("k", Algorithm::Scrypt, b"");
+        kdf.hash = crate::core::hasher::hash_phc(Algorithm::Scrypt, b"pw7", b"demosalt").unwrap();
+        let targets = vec![kdf, target("m", Algorithm::Md5, b"pw3")];
+
+        let generator = MaskGenerator::new("pw?d").unwrap();
+        let mut engine = Engine::new(targets, Box::new(generator), 2, 4);
+        let result = engine.run(|_| {}).unwrap();
+
+        let mut found: Vec<(String, String)> = result.matches.iter()
+            .map(|m| (m.target_id.clone(), m.password_string()))
+            .collect();
+        found.sort();
+        assert_eq!(found, vec![("k".to_string(), "pw7".to_string()), ("m".to_string(), "pw3".to_string())]);
+    }
+
+    #[test]
+    fn test_unfound_targets_complement_matches() {
+        let targets = vec![
+            target("a", Algorithm::Md5, b"42"),
+            target("b", Algorithm::Sha1, b"ab"),
+            target("c", Algorithm::Sha256, b"07"),
+        ];
+        let generator = MaskGenerator::new("?d?d").unwrap();
+        let mut engine = Engine::new(targets.clone(), Box::new(generator), 2, 16);
+        let result = engine.run(|_| {}).unwrap();
+
+        let unfound: Vec<&str> = engine.unfound_targets(&result).iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(unfound, vec!["b"]);
+
+        // matches and unfound partition the target set
+        let mut all: Vec<String> = result.matches.iter().map(|m| m.target_id.clone()).collect();
+        all.extend(unfound.iter().map(|id| id.to_string()));
+        all.sort();
+        assert_eq!(all, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_preloaded_targets_finish_without_guessing() {
+        let targets = vec![
+            target("a", Algorithm::Md5, b"a1"),
+            target("b", Algorithm::Sha256, b"b2"),
+        ];
+        let known = |t: &Target, password: &[u8]| TargetMatch {
+            target_id: t.id.clone(),
+            username: t.username.clone(),
+            password: password.to_vec(),
+            algorithm: t.algorithm,
+            guesses_tried: 0,
+            time_seconds: 0.0,
+            verified: None,
+            expected_password: None,
+        };
+        let preload = vec![known(&targets[0], b"a1"), known(&targets[1], b"b2")];
+        
+        let mut engine = Engine::new(targets, Box::new(MaskGenerator::new("?l?d").unwrap()), 2, 16)
+            .with_known_matches(preload);
+        let mut callbacks = 0;
+        let result = engine.run(|_| callbacks += 1).unwrap();
+        
+        assert_eq!(result.termination, TerminationReason::AllFound);
+        assert_eq!(result.statistics.guesses_tried, 0);
+        assert_eq!(result.statistics.targets_found, 2);
+        assert_eq!(result.matches.len(), 2);
+        assert_eq!(callbacks, 0);
+    }
+    
+    #[test]
+    fn test_algorithm_batch_sizes_follow_the_costliest_left() {
+        let targets = vec![
+            target("md5", Algorithm::Md5, b"a0"),
+            target("sha1", Algorithm::Sha1, b"z9"),
+        ];
+        let mut batch_sizes = Vec::new();
+        let mut engine = Engine::new(targets, Box::new(MaskGenerator::new("?l?d").unwrap()), 2, 100)
+            .with_algorithm_batch_sizes(vec![(Algorithm::Md5, 4), (Algorithm::Sha1, 50)]);
+        let mut last = 0;
+        let result = engine.run(|stats| {
+            batch_sizes.push(stats.guesses_tried - last);
+            last = stats.guesses_tried;
+        }).unwrap();
+        
+        assert_eq!(result.termination, TerminationReason::AllFound);
+        // md5 is cracked by the first batch, after which sha1's size
+        // applies: 256 candidates left make five full batches and the rest
+        assert_eq!(batch_sizes, [4, 50, 50, 50, 50, 50, 6]);
+    }
+    
+    #[test]
+    fn test_batch_timeout_shrinks_algorithm_batch_sizes() {
+        let targets = vec![
+            target("md5", Algorithm::Md5, b"a0"),
+            target("sha1", Algorithm::Sha1, b"z9"),
+        ];
+        let mut batch_sizes = Vec::new();
+        // every batch overruns a nanosecond, so each is cut to the least
+        let mut engine = Engine::new(targets, Box::new(MaskGenerator::new("?l?d").unwrap()), 2, 100)
+            .with_algorithm_batch_sizes(vec![(Algorithm::Md5, 4), (Algorithm::Sha1, 50)])
+            .with_batch_timeout(Duration::from_nanos(1));
+        let mut last = 0;
+        let result = engine.run(|stats| {
+            batch_sizes.push(stats.guesses_tried - last);
+            last = stats.guesses_tried;
+        }).unwrap();
+        
+        assert_eq!(result.termination, TerminationReason::AllFound);
+        assert_eq!(result.final_batch_size, 1);
+        // sha1's 50 no longer overrides the shrunk size
+        assert_eq!(batch_sizes[0], 4);
+        assert!(batch_sizes[1..].iter().all(|&size| size == 1), "{:?}", batch_sizes);
+    }
+    
+    #[test]
+    fn test_crack_one() {
+        let md5 = hex::encode(create_hasher(Algorithm::Md5).hash(b"cab"));
+        let brute = || Box::new(crate::core::BruteForceGenerator::new("abc", 1, 3));
+        
+        assert_eq!(crack_one(&md5, Algorithm::Md5, None, brute()), Some(b"cab".to_vec()));
+        // exhausted keyspace and malformed hashes both come back empty
+        let other = hex::encode(create_hasher(Algorithm::Md5).hash(b"dog"));
+        assert_eq!(crack_one(&other, Algorithm::Md5, None, brute()), None);
+        assert_eq!(crack_one("not hex", Algorithm::Md5, None, brute()), None);
+    }
+    
+    #[test]
+    fn test_pinned_threads_still_crack() {
+        // more workers than most cores, so the core ids wrap round
+        let targets = vec![target("a", Algorithm::Md5, b"42")];
+        let generator = MaskGenerator::new("?d?d").unwrap();
+        let mut engine = Engine::new(targets, Box::new(generator), 64, 16)
+            .with_pinned_threads();
+        let result = engine.run(|_| {}).unwrap();
+
+        assert_eq!(result.matches.len(), 1);
+        if core_affinity::get_core_ids().is_none() {
+            assert!(!result.threads_pinned);
+        }
+
+        let targets = vec![target("a", Algorithm::Md5, b"42")];
+        let generator = MaskGenerator::new("?d?d").unwrap();
+        let mut engine = Engine::new(targets, Box::new(generator), 2, 16);
+        assert!(!engine.run(|_| {}).unwrap().threads_pinned);
+    }
+
+    #[test]
+    fn test_transform_applies_before_hashing() {
+        let wordlist = std::env::temp_dir().join(format!("blitzforge_transform_{}.txt", std::process::id()));
+        std::fs::write(&wordlist, "hunter2\nletmein\n").unwrap();
+        let generator = crate::core::DictionaryGenerator::new(&wordlist).unwrap();
+
+        let targets = vec![target("a", Algorithm::Sha256, b"LETMEIN")];
+        let mut engine = Engine::new(targets, Box::new(generator), 2, 16)
+            .with_transform(Box::new(|c: &[u8]| c.to_ascii_uppercase()));
+        let result = engine.run(|_| {}).unwrap();
+        std::fs::remove_file(&wordlist).ok();
+
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.matches[0].password, b"LETMEIN");
+    }
+
+    #[test]
+    fn test_current_throughput_tracks_step_change() {
+        let mut stats = Statistics::new(1);
+        let start = stats.start_time;
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        // ten seconds warming up at 1000 H/s
+        for t in 1..=10 {
+            stats.hashes_computed += 1000;
+            stats.update_throughput_at(at(t));
+        }
+        assert!((stats.current_hashes_per_second - 1000.0).abs() < 1e-6);
+        assert!((stats.hashes_per_second - 1000.0).abs() < 1e-6);
+
+        // then three seconds at 5000 H/s
+        for t in 11..=13 {
+            stats.hashes_computed += 5000;
+            stats.update_throughput_at(at(t));
+        }
+        let cumulative_gap = 5000.0 - stats.hashes_per_second;
+        let current_gap = 5000.0 - stats.current_hashes_per_second;
+        assert!(current_gap < cumulative_gap / 2.0, "current {} vs cumulative {}",
+            stats.current_hashes_per_second, stats.hashes_per_second);
+        assert!(stats.current_hashes_per_second > 3500.0);
+        assert!(stats.current_hashes_per_second < 5000.0);
+    }
+
+    #[test]
+    fn test_worker_tuner_climbs_to_best_count() {
+        // throughput peaks at 5 of 8 workers
+        let rate = |workers: usize| 100.0 - (workers as f64 - 5.0).powi(2);
+        let mut tuner = WorkerTuner::new(8);
+        
+        for _ in 0..20 {
+            let current = tuner.workers();
+            tuner.observe(rate(current));
+        }
+        
+        assert!(tuner.settled());
+        assert!((4..=6).contains(&tuner.workers()), "{}", tuner.workers());
+    }
+    
+    #[test]
+    fn test_worker_tuner_ignores_noise_and_turns_at_edges() {
+        let mut tuner = WorkerTuner::new(3);
+        assert_eq!(tuner.observe(100.0), 2);
+        // within tolerance: keep stepping down
+        assert_eq!(tuner.observe(98.0), 1);
+        // can't go below one worker
+        assert_eq!(tuner.observe(99.0), 2);
+        assert_eq!(tuner.reversals, 1);
+        
+        // a single worker has nothing to tune
+        let mut single = WorkerTuner::new(1);
+        assert!(single.settled());
+        assert_eq!(single.observe(10.0), 1);
+    }
+    
+    #[test]
+    fn test_adapt_batch_size_within_budget() {
+        let timeout = Duration::from_millis(500);
+        assert_eq!(adapt_batch_size(4096, Duration::from_millis(200), timeout), 4096);
+        assert_eq!(adapt_batch_size(4096, timeout, timeout), 4096);
+    }
+
+    #[test]
+    fn test_adapt_batch_size_shrinks_proportionally() {
+        let timeout = Duration::from_millis(500);
+        assert_eq!(adapt_batch_size(4096, Duration::from_secs(2), timeout), 1024);
+        // never below one candidate
+        assert_eq!(adapt_batch_size(4, Duration::from_secs(3600), timeout), 1);
+        assert_eq!(adapt_batch_size(1, Duration::from_secs(3600), timeout), 1);
+        // a slight overrun still shrinks by at least one
+        assert_eq!(adapt_batch_size(10, Duration::from_millis(501), timeout), 9);
+    }
+}
@@ -1,52 +1,359 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet, VecDeque};
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{self, BufRead, BufReader};
 use std::path::Path;
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use anyhow::Result;
 
+use crate::core::CandidateBatch;
+
 pub trait Generator: Send {
     fn next_batch(&mut self, size: usize) -> Option<Vec<Vec<u8>>>;
-    fn estimated_size(&self) -> Option<u64>;
+    
+    /// candidates in the keyspace, if known. u128, since masks and
+    /// charsets easily pass u64 (95^10 does); saturates at `u128::MAX`
+    fn estimated_size(&self) -> Option<u128>;
     fn reset(&mut self);
+    
+    /// fraction of the keyspace consumed so far (0.0 to 1.0), if known
+    fn progress(&self) -> Option<f64> {
+        None
+    }
+    
+    /// called once `next_batch` returns `None`, to tell a clean end of the
+    /// keyspace from a candidate source that failed part way
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+    
+    /// whether the keyspace ended because time ran out rather than
+    /// because every candidate was produced
+    fn timed_out(&self) -> bool {
+        false
+    }
+    
+    /// a copy of this generator starting at candidate `index`, for keyspaces
+    /// whose candidates are numbered (mask, brute force). the engine cuts
+    /// those into ranges that workers generate on their own; `None` for
+    /// streams, which can only be read in order
+    fn seek_clone(&self, _index: u128) -> Option<Box<dyn Generator>> {
+        None
+    }
+    
+    /// append candidate `index` to `buf` without moving this generator,
+    /// for reading a numbered keyspace out of order; `false` past its end.
+    /// generators with `seek_clone` implement this too
+    fn candidate_at(&self, _index: u128, _buf: &mut CandidateBatch) -> bool {
+        false
+    }
+    
+    /// add candidates waiting ahead of the keyspace (`HintGenerator`'s
+    /// hints) to `buf`; `false` if there were none. the engine takes these
+    /// before splitting each round of a numbered keyspace between workers
+    fn take_queued(&mut self, _buf: &mut CandidateBatch) -> bool {
+        false
+    }
+    
+    /// the engine's stop flag (`Engine::with_stop_flag`), for generators
+    /// that block waiting for input (`--follow-wordlist`) to stop waiting
+    /// once it's set
+    fn watch_stop(&mut self, _stop: Arc<AtomicBool>) {}
+    
+    /// when `DeadlineGenerator` ends the keyspace, for the same generators
+    /// to stop waiting at
+    fn set_deadline(&mut self, _deadline: Instant) {}
+    
+    /// `password` just cracked a target. the engine reports every crack;
+    /// generators that adapt to them (`HintGenerator`) take note
+    fn found(&mut self, _password: &[u8]) {}
+    
+    /// add candidates to `buf` until it's full; `false` if none were left
+    /// to add. generators that can write candidates straight into the
+    /// buffer override this, the rest go through `next_batch`
+    fn fill_batch(&mut self, buf: &mut CandidateBatch) -> bool {
+        let wanted = buf.capacity().saturating_sub(buf.len());
+        if wanted == 0 {
+            return false;
+        }
+        match self.next_batch(wanted) {
+            Some(batch) => {
+                for candidate in &batch {
+                    buf.push(candidate);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// `next_batch` for generators whose native shape is `fill_batch`
+fn batch_via_fill<G: Generator + ?Sized>(generator: &mut G, size: usize) -> Option<Vec<Vec<u8>>> {
+    let mut buf = CandidateBatch::new(size);
+    generator.fill_batch(&mut buf).then(|| buf.to_vecs())
+}
+
+/// utf-8 byte order mark, stripped once from the start of a wordlist
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// times a transient read error is retried before the wordlist is given up on
+const READ_RETRIES: u32 = 3;
+
+/// wait before the first retry, doubled for each one after
+const READ_BACKOFF: Duration = Duration::from_millis(10);
+
+/// how often a followed wordlist is checked for new lines at eof
+const FOLLOW_POLL: Duration = Duration::from_millis(100);
+
+/// errors worth another attempt - a slow or briefly unavailable network
+/// filesystem, say. anything else won't fix itself by retrying
+fn is_transient(e: &io::Error) -> bool {
+    matches!(e.kind(), io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut)
+}
+
+/// `read_until(b'\n')` that retries transient errors with backoff. bytes
+/// read before a failed attempt stay in `line`, so a retry picks the line
+/// up where it left off; returns the bytes appended across all attempts
+fn read_line_retrying<R: BufRead>(reader: &mut R, line: &mut Vec<u8>) -> io::Result<usize> {
+    let start = line.len();
+    let mut attempt = 0;
+    
+    loop {
+        match reader.read_until(b'\n', line) {
+            Ok(_) => return Ok(line.len() - start),
+            Err(e) if is_transient(&e) && attempt < READ_RETRIES => {
+                std::thread::sleep(READ_BACKOFF * 2u32.pow(attempt));
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
 }
 
 // dictionary generator - reads from wordlist file
+//
+// normalization: a leading utf-8 bom is always stripped, and line endings
+// (`\n` or `\r\n`) are never part of a candidate. by default each line is
+// also trimmed of surrounding whitespace; disable with `with_trim(false)`
+// when the wordlist contains passwords with significant spaces.
 pub struct DictionaryGenerator {
     reader: BufReader<File>,
     path: std::path::PathBuf,
     total_lines: Option<u64>,
+    trim: bool,
+    /// `with_max_word_len`: longer lines are skipped
+    max_word_len: Option<usize>,
+    /// `with_weights`: lines are `word,weight`
+    weighted: bool,
+    at_start: bool,
+    lines_read: u64,
+    bytes_read: u64,
+    file_len: u64,
+    /// read error that ended the list early, reported by `finish()`
+    error: Option<io::Error>,
+    /// `with_follow`: how long eof may last before the list ends
+    follow: Option<Duration>,
+    /// when the followed file last had something new
+    last_growth: Instant,
+    /// a followed line cut short at eof, finished by a later read
+    partial: Vec<u8>,
+    /// `watch_stop` / `set_deadline`: either ends a wait for the file to grow
+    stop: Option<Arc<AtomicBool>>,
+    deadline: Option<Instant>,
 }
 
 impl DictionaryGenerator {
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path_buf = path.as_ref().to_path_buf();
         let file = File::open(&path_buf)?;
+        let file_len = file.metadata().map(|m| m.len()).unwrap_or(0);
         let reader = BufReader::new(file);
         
         Ok(Self {
             reader,
             path: path_buf,
             total_lines: None,
+            trim: true,
+            max_word_len: None,
+            weighted: false,
+            at_start: true,
+            lines_read: 0,
+            bytes_read: 0,
+            file_len,
+            error: None,
+            follow: None,
+            last_growth: Instant::now(),
+            partial: Vec::new(),
+            stop: None,
+            deadline: None,
         })
     }
+    
+    /// number of lines consumed so far (including skipped blank lines)
+    pub fn lines_read(&self) -> u64 {
+        self.lines_read
+    }
+    
+    /// enable or disable whitespace trimming (enabled by default)
+    pub fn with_trim(mut self, trim: bool) -> Self {
+        self.trim = trim;
+        self
+    }
+    
+    /// skip lines longer than `max` bytes (after trimming) as they're read -
+    /// pasted blobs and the like, which are never passwords. no limit by default
+    pub fn with_max_word_len(mut self, max: Option<usize>) -> Self {
+        self.max_word_len = max;
+        self
+    }
+    
+    /// the list has `word,weight` lines (see `split_weight`): lines are
+    /// still handed out whole, but `with_max_word_len` measures the word only
+    pub fn with_weights(mut self, weighted: bool) -> Self {
+        self.weighted = weighted;
+        self
+    }
+    
+    /// tail the file: at eof, wait for lines appended by someone else
+    /// instead of ending, until it has gone `idle` without growing. a line
+    /// without its newline yet is held back until the rest arrives
+    pub fn with_follow(mut self, idle: Duration) -> Self {
+        self.follow = Some(idle);
+        self
+    }
+    
+    /// one complete line read from the file
+    fn take_line(&mut self, line: &[u8], batch: &mut Vec<Vec<u8>>) {
+        self.lines_read += 1;
+        let candidate = self.normalize(line);
+        let word_len = match self.weighted {
+            true => Self::split_weight(candidate).0.len(),
+            false => candidate.len(),
+        };
+        if !candidate.is_empty() && self.max_word_len.is_none_or(|max| word_len <= max) {
+            batch.push(candidate.to_vec());
+        }
+    }
+    
+    /// split a weighted wordlist line, `password,9999`, into the word and
+    /// its weight. a line whose last comma isn't followed by a number -
+    /// no weight, or a password with a comma in it - is all word
+    pub fn split_weight(line: &[u8]) -> (&[u8], Option<u64>) {
+        let Some(comma) = line.iter().rposition(|&b| b == b',').filter(|&i| i > 0) else {
+            return (line, None);
+        };
+        let weight = std::str::from_utf8(&line[comma + 1..]).ok()
+            .and_then(|w| w.trim_ascii().parse().ok());
+        match weight {
+            Some(weight) => (&line[..comma], Some(weight)),
+            None => (line, None),
+        }
+    }
+    
+    /// count the lines in a wordlist without keeping them in memory
+    pub fn count_lines<P: AsRef<Path>>(path: P) -> Result<u64> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut count = 0u64;
+        let mut last = b'\n';
+        
+        loop {
+            let buf = reader.fill_buf()?;
+            if buf.is_empty() {
+                break;
+            }
+            count += buf.iter().filter(|&&b| b == b'\n').count() as u64;
+            last = buf[buf.len() - 1];
+            let len = buf.len();
+            reader.consume(len);
+        }
+        
+        // final line without a trailing newline
+        if last != b'\n' {
+            count += 1;
+        }
+        
+        Ok(count)
+    }
+    
+    /// normalize a raw line into a candidate
+    fn normalize<'a>(&mut self, mut line: &'a [u8]) -> &'a [u8] {
+        if self.at_start {
+            self.at_start = false;
+            line = line.strip_prefix(UTF8_BOM).unwrap_or(line);
+        }
+        
+        line = line.strip_suffix(b"\n").unwrap_or(line);
+        line = line.strip_suffix(b"\r").unwrap_or(line);
+        
+        if self.trim {
+            line.trim_ascii()
+        } else {
+            line
+        }
+    }
 }
 
 impl Generator for DictionaryGenerator {
     fn next_batch(&mut self, size: usize) -> Option<Vec<Vec<u8>>> {
+        if self.error.is_some() {
+            return None;
+        }
+        
         let mut batch = Vec::with_capacity(size);
+        let mut line = std::mem::take(&mut self.partial);
         
-        for _ in 0..size {
-            let mut line = String::new();
-            match self.reader.read_line(&mut line) {
-                Ok(0) => break,  // eof
-                Ok(_) => {
-                    let trimmed = line.trim();
-                    if !trimmed.is_empty() {
-                        batch.push(trimmed.as_bytes().to_vec());
+        // counts kept candidates: a run of skipped lines mustn't come back
+        // as an empty batch, which would end the list
+        while batch.len() < size {
+            match read_line_retrying(&mut self.reader, &mut line) {
+                // a whole line, or the unterminated last one when not following
+                Ok(n) if n > 0 && (line.ends_with(b"\n") || self.follow.is_none()) => {
+                    self.bytes_read += n as u64;
+                    self.last_growth = Instant::now();
+                    self.take_line(&line, &mut batch);
+                    line.clear();
+                }
+                Ok(n) => {
+                    // eof - unless following, wait for the file to grow
+                    self.bytes_read += n as u64;
+                    let Some(idle) = self.follow else {
+                        break;
+                    };
+                    if n > 0 {
+                        self.last_growth = Instant::now();
+                    }
+                    // hand over what's arrived rather than sit on it
+                    if !batch.is_empty() {
+                        break;
+                    }
+                    if self.last_growth.elapsed() >= idle {
+                        if !line.is_empty() {
+                            self.take_line(&line, &mut batch);
+                            line.clear();
+                        }
+                        break;
                     }
+                    // so does a stop or the deadline
+                    let now = Instant::now();
+                    let stopped = self.stop.as_ref().is_some_and(|stop| stop.load(Ordering::Relaxed));
+                    if stopped || self.deadline.is_some_and(|deadline| now >= deadline) {
+                        break;
+                    }
+                    std::thread::sleep(self.deadline.map_or(FOLLOW_POLL, |deadline| FOLLOW_POLL.min(deadline - now)));
+                }
+                Err(e) => {
+                    // checked in finish(), so the run can't pass a cut-short
+                    // list off as an exhausted one
+                    self.error = Some(e);
+                    break;
                 }
-                Err(_) => break,
             }
         }
+        self.partial = line;
         
         if batch.is_empty() {
             None
@@ -55,13 +362,222 @@ impl Generator for DictionaryGenerator {
         }
     }
     
-    fn estimated_size(&self) -> Option<u64> {
-        self.total_lines
+    fn estimated_size(&self) -> Option<u128> {
+        self.total_lines.map(u128::from)
     }
     
     fn reset(&mut self) {
         if let Ok(file) = File::open(&self.path) {
             self.reader = BufReader::new(file);
+            self.at_start = true;
+            self.lines_read = 0;
+            self.bytes_read = 0;
+            self.error = None;
+            self.last_growth = Instant::now();
+            self.partial.clear();
+        }
+    }
+    
+    fn watch_stop(&mut self, stop: Arc<AtomicBool>) {
+        self.stop = Some(stop);
+    }
+    
+    fn set_deadline(&mut self, deadline: Instant) {
+        self.deadline = Some(deadline);
+    }
+    
+    /// lines read / total lines when the line count is known, otherwise
+    /// approximated from bytes read / file size. unknown while following,
+    /// since the file can keep growing
+    fn progress(&self) -> Option<f64> {
+        if self.follow.is_some() {
+            return None;
+        }
+        match self.total_lines {
+            Some(0) => Some(1.0),
+            Some(total) => Some((self.lines_read as f64 / total as f64).min(1.0)),
+            None if self.file_len == 0 => None,
+            None => Some((self.bytes_read as f64 / self.file_len as f64).min(1.0)),
+        }
+    }
+    
+    fn finish(&mut self) -> Result<()> {
+        match self.error.take() {
+            Some(e) => anyhow::bail!("failed reading wordlist {} after {} lines: {}", self.path.display(), self.lines_read, e),
+            None => Ok(()),
+        }
+    }
+}
+
+// multi dictionary generator - chains several wordlists into one keyspace
+pub struct MultiDictionaryGenerator {
+    paths: Vec<std::path::PathBuf>,
+    /// the list the next batch comes from
+    index: usize,
+    /// open readers, one slot per list; a list is opened on first use
+    open: Vec<Option<DictionaryGenerator>>,
+    /// lists read to the end
+    done: Vec<bool>,
+    interleaved: bool,
+    trim: bool,
+    max_word_len: Option<usize>,
+    weighted: bool,
+    total_lines: Option<u64>,
+    lines_done: u64,
+    error: Option<anyhow::Error>,
+}
+
+impl MultiDictionaryGenerator {
+    pub fn new<P: AsRef<Path>>(paths: &[P]) -> Result<Self> {
+        let paths: Vec<std::path::PathBuf> = paths.iter()
+            .map(|p| p.as_ref().to_path_buf())
+            .collect();
+        
+        if paths.is_empty() {
+            anyhow::bail!("no wordlists specified");
+        }
+        
+        // counting also checks every list is readable before the run starts
+        let mut total = 0u64;
+        for path in &paths {
+            total += DictionaryGenerator::count_lines(path)
+                .map_err(|e| anyhow::anyhow!("cannot read wordlist {}: {}", path.display(), e))?;
+        }
+        
+        Ok(Self {
+            open: paths.iter().map(|_| None).collect(),
+            done: vec![false; paths.len()],
+            paths,
+            index: 0,
+            interleaved: false,
+            trim: true,
+            max_word_len: None,
+            weighted: false,
+            total_lines: Some(total),
+            lines_done: 0,
+            error: None,
+        })
+    }
+    
+    /// enable or disable whitespace trimming (enabled by default)
+    pub fn with_trim(mut self, trim: bool) -> Self {
+        self.trim = trim;
+        self
+    }
+    
+    /// skip lines longer than `max` bytes in every list (no limit by default)
+    pub fn with_max_word_len(mut self, max: Option<usize>) -> Self {
+        self.max_word_len = max;
+        self
+    }
+    
+    /// every list has `word,weight` lines (see `DictionaryGenerator::with_weights`)
+    pub fn with_weights(mut self, weighted: bool) -> Self {
+        self.weighted = weighted;
+        self
+    }
+    
+    /// take each batch from the next list in turn, round-robin, instead of
+    /// exhausting one list before starting the next (disabled by default)
+    pub fn with_interleaved(mut self, interleaved: bool) -> Self {
+        self.interleaved = interleaved;
+        self
+    }
+    
+    /// move on to the next list that isn't done, wrapping around; past the
+    /// end once every list is
+    fn advance(&mut self) {
+        let n = self.paths.len();
+        self.index = (1..=n)
+            .map(|step| (self.index + step) % n)
+            .find(|&i| !self.done[i])
+            .unwrap_or(n);
+    }
+}
+
+impl Generator for MultiDictionaryGenerator {
+    fn next_batch(&mut self, size: usize) -> Option<Vec<Vec<u8>>> {
+        if self.error.is_some() {
+            return None;
+        }
+        
+        let mut batch = Vec::with_capacity(size);
+        
+        while batch.len() < size && self.index < self.paths.len() {
+            let i = self.index;
+            if self.open[i].is_none() {
+                match DictionaryGenerator::new(&self.paths[i]) {
+                    Ok(gen) => self.open[i] = Some(gen.with_trim(self.trim)
+                        .with_max_word_len(self.max_word_len)
+                        .with_weights(self.weighted)),
+                    Err(e) => {
+                        // it was readable when counted; don't carry on as if
+                        // this list had been tried
+                        self.error = Some(anyhow::anyhow!("cannot open wordlist {}: {}", self.paths[i].display(), e));
+                        break;
+                    }
+                }
+            }
+            
+            let current = self.open[i].as_mut().unwrap();
+            match current.next_batch(size - batch.len()) {
+                Some(candidates) => {
+                    batch.extend(candidates);
+                    // interleaved: the next batch comes from the next list
+                    if self.interleaved {
+                        self.advance();
+                        break;
+                    }
+                }
+                None => {
+                    // this list is exhausted, advance to the next
+                    self.lines_done += current.lines_read();
+                    let finished = current.finish();
+                    self.open[i] = None;
+                    if let Err(e) = finished {
+                        // don't carry on into the next list as if this one completed
+                        self.error = Some(e);
+                        break;
+                    }
+                    self.done[i] = true;
+                    self.advance();
+                }
+            }
+        }
+        
+        if batch.is_empty() {
+            None
+        } else {
+            Some(batch)
+        }
+    }
+    
+    fn estimated_size(&self) -> Option<u128> {
+        self.total_lines.map(u128::from)
+    }
+    
+    fn reset(&mut self) {
+        self.index = 0;
+        self.open.iter_mut().for_each(|list| *list = None);
+        self.done.fill(false);
+        self.lines_done = 0;
+        self.error = None;
+    }
+    
+    fn progress(&self) -> Option<f64> {
+        let total = self.total_lines?;
+        if total == 0 || self.index >= self.paths.len() {
+            return Some(1.0);
+        }
+        
+        let open: u64 = self.open.iter().flatten().map(|c| c.lines_read()).sum();
+        Some(((self.lines_done + open) as f64 / total as f64).min(1.0))
+    }
+    
+    fn finish(&mut self) -> Result<()> {
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
         }
     }
 }
@@ -104,16 +620,20 @@ impl CharSet {
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct MaskGenerator {
     pattern: Vec<CharSet>,
     current: Vec<usize>,
     exhausted: bool,
+    /// characters per candidate as written, see `length`
+    length: usize,
 }
 
 impl MaskGenerator {
-    /// parse mask pattern like "?l?l?l?d?d"
+    /// parse mask pattern like "?l?l?l?d?d"; `??` stands for a literal '?'
     pub fn new(mask: &str) -> Result<Self> {
         let mut pattern = Vec::new();
+        let mut length = 0;
         let chars: Vec<char> = mask.chars().collect();
         let mut i = 0;
         
@@ -124,15 +644,20 @@ impl MaskGenerator {
                     'u' => CharSet::uppercase(),
                     'd' => CharSet::digits(),
                     's' => CharSet::special(),
+                    // `??` is a literal question mark
+                    '?' => CharSet { chars: vec![b'?'] },
                     _ => return Err(anyhow::anyhow!("unknown charset: ?{}", chars[i + 1])),
                 };
                 pattern.push(charset);
+                length += 1;
                 i += 2;
             } else {
-                // literal character
-                pattern.push(CharSet {
-                    chars: vec![chars[i] as u8],
-                });
+                // literal character, one position per utf-8 byte
+                let mut utf8 = [0; 4];
+                for &byte in chars[i].encode_utf8(&mut utf8).as_bytes() {
+                    pattern.push(CharSet { chars: vec![byte] });
+                }
+                length += 1;
                 i += 1;
             }
         }
@@ -143,9 +668,74 @@ impl MaskGenerator {
             pattern,
             current,
             exhausted: false,
+            length,
+        })
+    }
+    
+    /// brute force with some positions known: each `.` in `template` runs
+    /// through `charset`, every other byte is kept literally, so "admin.."
+    /// tries admin00 to admin99 with a digit charset
+    pub fn from_template(template: &str, charset: &str) -> Result<Self> {
+        if template.is_empty() {
+            anyhow::bail!("template is empty");
+        }
+        if charset.is_empty() {
+            anyhow::bail!("charset is empty");
+        }
+        
+        let pattern: Vec<CharSet> = template.bytes()
+            .map(|b| match b {
+                b'.' => CharSet::from_string(charset),
+                literal => CharSet { chars: vec![literal] },
+            })
+            .collect();
+        let current = vec![0; pattern.len()];
+        
+        Ok(Self {
+            length: pattern.len(),
+            pattern,
+            current,
+            exhausted: false,
         })
     }
     
+    /// candidate length in characters, as the mask was written: `??` and a
+    /// multi-byte literal like `é` count one each (`positions` counts bytes)
+    pub fn length(&self) -> usize {
+        self.length
+    }
+    
+    /// candidate length in bytes
+    pub fn positions(&self) -> usize {
+        self.pattern.len()
+    }
+    
+    /// the charset each position runs through, first position first
+    pub fn charsets(&self) -> &[CharSet] {
+        &self.pattern
+    }
+    
+    /// drop positions past `len`. for truncating hashes those positions never
+    /// reach the hash, so every prefix would otherwise be tried repeatedly
+    pub fn truncated(mut self, len: usize) -> Self {
+        self.pattern.truncate(len);
+        self.current.truncate(len);
+        self
+    }
+    
+    /// position at candidate `index`: the counters are its mixed-radix digits
+    fn seek(&mut self, mut index: u128) {
+        self.exhausted = index >= self.estimated_size().unwrap_or(0);
+        if self.exhausted {
+            return;
+        }
+        for (charset, idx) in self.pattern.iter().zip(&mut self.current).rev() {
+            let radix = charset.chars.len() as u128;
+            *idx = (index % radix) as usize;
+            index /= radix;
+        }
+    }
+    
     fn increment(&mut self) {
         for i in (0..self.current.len()).rev() {
             self.current[i] += 1;
@@ -160,34 +750,26 @@ impl MaskGenerator {
 
 impl Generator for MaskGenerator {
     fn next_batch(&mut self, size: usize) -> Option<Vec<Vec<u8>>> {
-        if self.exhausted {
-            return None;
-        }
-        
-        let mut batch = Vec::with_capacity(size);
+        batch_via_fill(self, size)
+    }
+    
+    fn fill_batch(&mut self, buf: &mut CandidateBatch) -> bool {
+        let start = buf.len();
         
-        while batch.len() < size && !self.exhausted {
-            let candidate: Vec<u8> = self.pattern.iter()
+        while !buf.is_full() && !self.exhausted {
+            buf.push_bytes(self.pattern.iter()
                 .zip(&self.current)
-                .map(|(charset, &idx)| charset.chars[idx])
-                .collect();
-            
-            batch.push(candidate);
+                .map(|(charset, &idx)| charset.chars[idx]));
             self.increment();
         }
         
-        if batch.is_empty() {
-            None
-        } else {
-            Some(batch)
-        }
+        buf.len() > start
     }
     
-    fn estimated_size(&self) -> Option<u64> {
+    fn estimated_size(&self) -> Option<u128> {
         Some(
             self.pattern.iter()
-                .map(|cs| cs.chars.len() as u64)
-                .product()
+                .fold(1u128, |total, cs| total.saturating_mul(cs.chars.len() as u128))
         )
     }
     
@@ -195,9 +777,47 @@ impl Generator for MaskGenerator {
         self.current = vec![0; self.pattern.len()];
         self.exhausted = false;
     }
+    
+    fn seek_clone(&self, index: u128) -> Option<Box<dyn Generator>> {
+        let mut copy = self.clone();
+        copy.seek(index);
+        Some(Box::new(copy))
+    }
+    
+    /// the digits of `index`, most significant first, pick each position
+    fn candidate_at(&self, mut index: u128, buf: &mut CandidateBatch) -> bool {
+        let mut place = self.estimated_size().unwrap_or(0);
+        if index >= place {
+            return false;
+        }
+        buf.push_bytes(self.pattern.iter().map(|charset| {
+            place /= charset.chars.len() as u128;
+            let digit = index / place;
+            index %= place;
+            charset.chars[digit as usize]
+        }));
+        true
+    }
+    
+    /// exact: the position counters read as a mixed-radix fraction
+    fn progress(&self) -> Option<f64> {
+        if self.exhausted {
+            return Some(1.0);
+        }
+        
+        let mut fraction = 0.0;
+        let mut scale = 1.0;
+        for (charset, &idx) in self.pattern.iter().zip(&self.current) {
+            scale /= charset.chars.len() as f64;
+            fraction += idx as f64 * scale;
+        }
+        
+        Some(fraction)
+    }
 }
 
 // brute force generator - exhaustive search
+#[derive(Debug, Clone)]
 pub struct BruteForceGenerator {
     charset: Vec<u8>,
     min_length: usize,
@@ -219,6 +839,26 @@ impl BruteForceGenerator {
         }
     }
     
+    /// position at candidate `index`, counting through the shorter lengths first
+    fn seek(&mut self, mut index: u128) {
+        let base = self.charset.len() as u128;
+        for len in self.min_length..=self.max_length {
+            let count = base.saturating_pow(len as u32);
+            if index < count {
+                self.current_length = len;
+                self.current = vec![0; len];
+                for idx in self.current.iter_mut().rev() {
+                    *idx = (index % base) as usize;
+                    index /= base;
+                }
+                self.exhausted = false;
+                return;
+            }
+            index -= count;
+        }
+        self.exhausted = true;
+    }
+    
     fn increment_current(&mut self) -> bool {
         for i in (0..self.current.len()).rev() {
             self.current[i] += 1;
@@ -233,23 +873,19 @@ impl BruteForceGenerator {
 
 impl Generator for BruteForceGenerator {
     fn next_batch(&mut self, size: usize) -> Option<Vec<Vec<u8>>> {
-        if self.exhausted {
-            return None;
-        }
-        
-        let mut batch = Vec::with_capacity(size);
+        batch_via_fill(self, size)
+    }
+    
+    fn fill_batch(&mut self, buf: &mut CandidateBatch) -> bool {
+        let start = buf.len();
         
-        while batch.len() < size {
+        while !buf.is_full() && !self.exhausted {
             if self.current_length > self.max_length {
                 self.exhausted = true;
                 break;
             }
             
-            let candidate: Vec<u8> = self.current.iter()
-                .map(|&idx| self.charset[idx])
-                .collect();
-            
-            batch.push(candidate);
+            buf.push_bytes(self.current.iter().map(|&idx| self.charset[idx]));
             
             if !self.increment_current() {
                 self.current_length += 1;
@@ -259,16 +895,12 @@ impl Generator for BruteForceGenerator {
             }
         }
         
-        if batch.is_empty() {
-            None
-        } else {
-            Some(batch)
-        }
+        buf.len() > start
     }
     
-    fn estimated_size(&self) -> Option<u64> {
-        let base = self.charset.len() as u64;
-        let mut total = 0u64;
+    fn estimated_size(&self) -> Option<u128> {
+        let base = self.charset.len() as u128;
+        let mut total = 0u128;
         
         for len in self.min_length..=self.max_length {
             total = total.saturating_add(base.saturating_pow(len as u32));
@@ -282,4 +914,1510 @@ impl Generator for BruteForceGenerator {
         self.current = vec![0; self.min_length];
         self.exhausted = false;
     }
-}
\ No newline at end of file
+    
+    fn seek_clone(&self, index: u128) -> Option<Box<dyn Generator>> {
+        let mut copy = self.clone();
+        copy.seek(index);
+        Some(Box::new(copy))
+    }
+    
+    /// as `seek`: find the length, then the digits within it
+    fn candidate_at(&self, mut index: u128, buf: &mut CandidateBatch) -> bool {
+        let base = self.charset.len() as u128;
+        for len in self.min_length..=self.max_length {
+            let mut place = base.saturating_pow(len as u32);
+            if index < place {
+                buf.push_bytes((0..len).map(|_| {
+                    place /= base;
+                    let digit = index / place;
+                    index %= place;
+                    self.charset[digit as usize]
+                }));
+                return true;
+            }
+            index -= place;
+        }
+        false
+    }
+    
+    /// exact: candidates of completed lengths plus the index within the
+    /// current length, over the total keyspace
+    fn progress(&self) -> Option<f64> {
+        if self.exhausted || self.current_length > self.max_length {
+            return Some(1.0);
+        }
+        
+        let base = self.charset.len() as f64;
+        let total: f64 = (self.min_length..=self.max_length)
+            .map(|len| base.powi(len as i32))
+            .sum();
+        if total == 0.0 {
+            return Some(1.0);
+        }
+        
+        let completed: f64 = (self.min_length..self.current_length)
+            .map(|len| base.powi(len as i32))
+            .sum();
+        let index_in_length = self.current.iter()
+            .fold(0.0, |acc, &idx| acc * base + idx as f64);
+        
+        Some(((completed + index_in_length) / total).min(1.0))
+    }
+}
+// process generator - candidates are the stdout lines of an external
+// command (crunch, maskprocessor, princeprocessor, ...), run through the
+// shell. lines are taken as-is apart from the line ending. eof with a zero
+// exit status is the end of the keyspace; a nonzero status is an error
+pub struct ProcessGenerator {
+    command: String,
+    child: Child,
+    stdout: BufReader<ChildStdout>,
+    /// counted for the error when the command fails
+    lines_read: u64,
+    /// a failed read of the command's output, reported by `finish`
+    read_error: Option<io::Error>,
+}
+
+impl ProcessGenerator {
+    pub fn new(command: &str) -> Result<Self> {
+        let (child, stdout) = Self::spawn(command)?;
+        Ok(Self {
+            command: command.to_string(),
+            child,
+            stdout,
+            lines_read: 0,
+            read_error: None,
+        })
+    }
+    
+    fn spawn(command: &str) -> Result<(Child, BufReader<ChildStdout>)> {
+        #[cfg(unix)]
+        let mut cmd = {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c").arg(command);
+            cmd
+        };
+        #[cfg(windows)]
+        let mut cmd = {
+            let mut cmd = Command::new("cmd");
+            cmd.arg("/C").arg(command);
+            cmd
+        };
+        
+        let mut child = cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("failed to start generator command `{}`: {}", command, e))?;
+        let stdout = child.stdout.take().expect("stdout is piped");
+        
+        Ok((child, BufReader::new(stdout)))
+    }
+}
+
+impl Generator for ProcessGenerator {
+    fn next_batch(&mut self, size: usize) -> Option<Vec<Vec<u8>>> {
+        let mut batch = Vec::with_capacity(size);
+        let mut line = Vec::new();
+        
+        while batch.len() < size && self.read_error.is_none() {
+            line.clear();
+            match self.stdout.read_until(b'\n', &mut line) {
+                Ok(0) => break,  // eof, checked in finish()
+                Err(e) => self.read_error = Some(e),
+                Ok(_) => {
+                    self.lines_read += 1;
+                    let candidate = line.strip_suffix(b"\n").unwrap_or(&line);
+                    let candidate = candidate.strip_suffix(b"\r").unwrap_or(candidate);
+                    if !candidate.is_empty() {
+                        batch.push(candidate.to_vec());
+                    }
+                }
+            }
+        }
+        
+        if batch.is_empty() {
+            None
+        } else {
+            Some(batch)
+        }
+    }
+    
+    fn estimated_size(&self) -> Option<u128> {
+        None
+    }
+    
+    /// restart the command from the beginning
+    fn reset(&mut self) {
+        self.child.kill().ok();
+        self.child.wait().ok();
+        if let Ok((child, stdout)) = Self::spawn(&self.command) {
+            self.child = child;
+            self.stdout = stdout;
+            self.lines_read = 0;
+            self.read_error = None;
+        }
+    }
+    
+    fn finish(&mut self) -> Result<()> {
+        if let Some(e) = self.read_error.take() {
+            // unread output would leave the command blocked on the pipe
+            self.child.kill().ok();
+            self.child.wait().ok();
+            anyhow::bail!("reading from generator command `{}` failed after {} lines: {}", self.command, self.lines_read, e);
+        }
+        let status = self.child.wait()?;
+        if !status.success() {
+            anyhow::bail!("generator command `{}` failed ({}) after {} lines", self.command, status, self.lines_read);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for ProcessGenerator {
+    fn drop(&mut self) {
+        // stop a generator we quit early (e.g. every target cracked)
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+// memory generator - candidates held in memory, e.g. a wordlist sorted
+// before the attack (`--sort-wordlist`). costs the whole list's size in ram,
+// once: clones share the candidates, each with its own position
+#[derive(Debug, Clone)]
+pub struct MemoryGenerator {
+    candidates: Arc<Vec<Vec<u8>>>,
+    position: usize,
+}
+
+impl MemoryGenerator {
+    pub fn new(candidates: Vec<Vec<u8>>) -> Self {
+        Self { candidates: Arc::new(candidates), position: 0 }
+    }
+    
+    /// drain `source` into memory, failing once the candidates would take
+    /// more than `max_bytes`
+    pub fn load(source: &mut dyn Generator, max_bytes: Option<u64>) -> Result<Self> {
+        let mut candidates = Vec::new();
+        Self::drain(source, max_bytes, |candidate| {
+            let size = candidate.len() + std::mem::size_of::<Vec<u8>>();
+            candidates.push(candidate);
+            size
+        })?;
+        
+        Ok(Self::new(candidates))
+    }
+    
+    /// `load` a wordlist of `word,weight` lines (see
+    /// `DictionaryGenerator::split_weight`), heaviest first with the weights
+    /// stripped off. lines without one weigh 0, and equal weights keep file
+    /// order. only the words count against `max_bytes`
+    pub fn load_by_weight(source: &mut dyn Generator, max_bytes: Option<u64>) -> Result<Self> {
+        let mut weighted = Vec::new();
+        Self::drain(source, max_bytes, |mut line| {
+            let (word, weight) = DictionaryGenerator::split_weight(&line);
+            let (len, weight) = (word.len(), weight.unwrap_or(0));
+            line.truncate(len);
+            line.shrink_to_fit();
+            weighted.push((weight, line));
+            len + std::mem::size_of::<(u64, Vec<u8>)>()
+        })?;
+        
+        weighted.sort_by_key(|&(weight, _)| std::cmp::Reverse(weight));
+        Ok(Self::new(weighted.into_iter().map(|(_, word)| word).collect()))
+    }
+    
+    /// hand every candidate of `source` to `keep`, which returns the bytes it
+    /// holds on to, failing once those add up to more than `max_bytes`
+    fn drain(source: &mut dyn Generator, max_bytes: Option<u64>, mut keep: impl FnMut(Vec<u8>) -> usize) -> Result<()> {
+        let mut bytes = 0u64;
+        
+        while let Some(batch) = source.next_batch(65536) {
+            for candidate in batch {
+                bytes += keep(candidate) as u64;
+                if let Some(max) = max_bytes.filter(|&max| bytes > max) {
+                    anyhow::bail!("candidates need more than {} bytes of memory (limit {})", bytes, max);
+                }
+            }
+        }
+        source.finish()
+    }
+    
+    /// shortest first; equal lengths keep their original order
+    pub fn sort_by_length(&mut self) {
+        Arc::make_mut(&mut self.candidates).sort_by_key(|c| c.len());
+    }
+    
+    /// byte-wise lexicographic order
+    pub fn sort_lex(&mut self) {
+        Arc::make_mut(&mut self.candidates).sort_unstable();
+    }
+}
+
+impl Generator for MemoryGenerator {
+    fn next_batch(&mut self, size: usize) -> Option<Vec<Vec<u8>>> {
+        let end = (self.position + size).min(self.candidates.len());
+        if end == self.position {
+            return None;
+        }
+        
+        let batch = self.candidates[self.position..end].to_vec();
+        self.position = end;
+        Some(batch)
+    }
+    
+    fn estimated_size(&self) -> Option<u128> {
+        Some(self.candidates.len() as u128)
+    }
+    
+    fn reset(&mut self) {
+        self.position = 0;
+    }
+    
+    fn progress(&self) -> Option<f64> {
+        match self.candidates.len() {
+            0 => Some(1.0),
+            len => Some(self.position as f64 / len as f64),
+        }
+    }
+}
+
+// range generator - a contiguous slice of another generator's keyspace,
+// used to shard one attack across several machines (`--skip` / `--limit`).
+// a numbered keyspace (mask, brute force) jumps straight to the skip; a
+// stream is drained up to it, which costs candidate generation but no
+// hashing
+pub struct RangeGenerator {
+    inner: Box<dyn Generator>,
+    skip: u64,
+    limit: Option<u64>,
+    skipped: bool,
+    emitted: u64,
+}
+
+impl RangeGenerator {
+    pub fn new(inner: Box<dyn Generator>, skip: u64, limit: Option<u64>) -> Self {
+        Self {
+            inner,
+            skip,
+            limit,
+            skipped: false,
+            emitted: 0,
+        }
+    }
+    
+    fn skip_ahead(&mut self) {
+        self.skipped = true;
+        if let Some(inner) = self.inner.seek_clone(u128::from(self.skip)) {
+            self.inner = inner;
+            return;
+        }
+        
+        let mut remaining = self.skip;
+        let mut buf = CandidateBatch::new(0);
+        while remaining > 0 {
+            buf.clear();
+            buf.set_capacity(remaining.min(65536) as usize);
+            match self.inner.fill_batch(&mut buf) {
+                true => remaining -= buf.len() as u64,
+                false => break,
+            }
+        }
+    }
+    
+    /// inner keyspace left after the skip
+    fn inner_size(&self) -> Option<u128> {
+        self.inner.estimated_size().map(|size| size.saturating_sub(u128::from(self.skip)))
+    }
+}
+
+impl Generator for RangeGenerator {
+    fn next_batch(&mut self, size: usize) -> Option<Vec<Vec<u8>>> {
+        batch_via_fill(self, size)
+    }
+    
+    fn fill_batch(&mut self, buf: &mut CandidateBatch) -> bool {
+        if !self.skipped {
+            self.skip_ahead();
+        }
+        
+        let capacity = buf.capacity();
+        let start = buf.len();
+        let size = capacity.saturating_sub(start) as u64;
+        let size = match self.limit {
+            Some(limit) => (limit - self.emitted).min(size),
+            None => size,
+        };
+        if size == 0 {
+            return false;
+        }
+        
+        // the inner generator stops at the limit, not the caller's capacity
+        buf.set_capacity(start + size as usize);
+        let filled = self.inner.fill_batch(buf);
+        buf.set_capacity(capacity);
+        self.emitted += (buf.len() - start) as u64;
+        filled
+    }
+    
+    fn estimated_size(&self) -> Option<u128> {
+        let remaining = self.inner_size()?;
+        Some(self.limit.map_or(remaining, |limit| remaining.min(u128::from(limit))))
+    }
+    
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.skipped = false;
+        self.emitted = 0;
+    }
+    
+    /// the window's candidate `index` is the inner one `skip + index`. the
+    /// copy is a window of its own, from there to the same end, so the
+    /// engine can split a `--skip` / `--limit` shard between workers
+    fn seek_clone(&self, index: u128) -> Option<Box<dyn Generator>> {
+        let offset = u64::try_from(index).unwrap_or(u64::MAX);
+        let skip = self.skip.saturating_add(offset);
+        Some(Box::new(Self {
+            inner: self.inner.seek_clone(u128::from(skip))?,
+            skip,
+            limit: self.limit.map(|limit| limit.saturating_sub(offset)),
+            skipped: true,
+            emitted: 0,
+        }))
+    }
+    
+    fn candidate_at(&self, index: u128, buf: &mut CandidateBatch) -> bool {
+        if self.limit.is_some_and(|limit| index >= u128::from(limit)) {
+            return false;
+        }
+        self.inner.candidate_at(u128::from(self.skip) + index, buf)
+    }
+    
+    fn found(&mut self, password: &[u8]) {
+        self.inner.found(password);
+    }
+    
+    fn watch_stop(&mut self, stop: Arc<AtomicBool>) {
+        self.inner.watch_stop(stop);
+    }
+    
+    fn set_deadline(&mut self, deadline: Instant) {
+        self.inner.set_deadline(deadline);
+    }
+    
+    fn finish(&mut self) -> Result<()> {
+        self.inner.finish()
+    }
+    
+    fn timed_out(&self) -> bool {
+        self.inner.timed_out()
+    }
+    
+    fn progress(&self) -> Option<f64> {
+        match self.estimated_size()? {
+            0 => Some(1.0),
+            size => Some((self.emitted as f64 / size as f64).min(1.0)),
+        }
+    }
+}
+
+/// feistel rounds in `KeyspacePermutation`
+const FEISTEL_ROUNDS: usize = 4;
+
+/// splitmix64's finaliser: a cheap, well-mixed 64-bit hash
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// a seeded pseudo-random permutation of `0..size`, computed one index at a
+/// time without a table: a balanced feistel network over the smallest even
+/// number of bits covering `size`, cycle-walking indices that land past it
+struct KeyspacePermutation {
+    size: u128,
+    half_bits: u32,
+    keys: [u64; FEISTEL_ROUNDS],
+}
+
+impl KeyspacePermutation {
+    fn new(size: u128, seed: u64) -> Self {
+        let bits = (128 - size.saturating_sub(1).leading_zeros()).max(2);
+        let mut state = seed;
+        let keys = std::array::from_fn(|_| {
+            state = splitmix64(state);
+            state
+        });
+        Self { size, half_bits: bits.div_ceil(2), keys }
+    }
+    
+    /// one pass of the network over the full power-of-two domain
+    fn feistel(&self, index: u128) -> u128 {
+        let mask = (1u128 << self.half_bits) - 1;
+        let (mut left, mut right) = ((index >> self.half_bits) & mask, index & mask);
+        for &key in &self.keys {
+            let f = u128::from(splitmix64(right as u64 ^ key)) & mask;
+            (left, right) = (right, left ^ f);
+        }
+        (left << self.half_bits) | right
+    }
+    
+    /// where `index` goes; the domain is under 4x `size`, so the walk is short
+    fn permute(&self, index: u128) -> u128 {
+        let mut permuted = self.feistel(index);
+        while permuted >= self.size {
+            permuted = self.feistel(permuted);
+        }
+        permuted
+    }
+}
+
+// sample generator - `count` distinct candidates drawn at random from a
+// numbered keyspace (mask, brute force) instead of all of them, for
+// `run --sample-keyspace`. the draw is a permutation of the candidate
+// indices, so nothing repeats and the same seed draws the same sample
+pub struct SampleGenerator {
+    inner: Box<dyn Generator>,
+    permutation: KeyspacePermutation,
+    count: u128,
+    emitted: u128,
+}
+
+impl SampleGenerator {
+    /// at most `count` candidates of `inner`, which must be able to seek
+    pub fn new(inner: Box<dyn Generator>, count: u64, seed: u64) -> Result<Self> {
+        let size = match inner.estimated_size() {
+            Some(u128::MAX) => anyhow::bail!("keyspace is too large to sample"),
+            Some(size) if inner.seek_clone(0).is_some() => size,
+            _ => anyhow::bail!("only a mask or brute-force keyspace can be sampled"),
+        };
+        Ok(Self {
+            inner,
+            permutation: KeyspacePermutation::new(size, seed),
+            count: u128::from(count).min(size),
+            emitted: 0,
+        })
+    }
+}
+
+impl Generator for SampleGenerator {
+    fn next_batch(&mut self, size: usize) -> Option<Vec<Vec<u8>>> {
+        batch_via_fill(self, size)
+    }
+    
+    fn fill_batch(&mut self, buf: &mut CandidateBatch) -> bool {
+        let start = buf.len();
+        while !buf.is_full() && self.emitted < self.count {
+            let index = self.permutation.permute(self.emitted);
+            self.emitted += 1;
+            self.inner.candidate_at(index, buf);
+        }
+        buf.len() > start
+    }
+    
+    fn estimated_size(&self) -> Option<u128> {
+        Some(self.count)
+    }
+    
+    fn reset(&mut self) {
+        self.emitted = 0;
+    }
+    
+    fn progress(&self) -> Option<f64> {
+        match self.count {
+            0 => Some(1.0),
+            count => Some(self.emitted as f64 / count as f64),
+        }
+    }
+}
+
+// deadline generator - ends another generator's keyspace at a wall-clock
+// deadline, for time-boxed runs. the batch in flight still completes
+pub struct DeadlineGenerator {
+    inner: Box<dyn Generator>,
+    time_limit: Duration,
+    deadline: Instant,
+    expired: bool,
+}
+
+impl DeadlineGenerator {
+    /// the clock starts now, and again on `reset`
+    pub fn new(mut inner: Box<dyn Generator>, time_limit: Duration) -> Self {
+        let deadline = Instant::now() + time_limit;
+        inner.set_deadline(deadline);
+        Self {
+            inner,
+            time_limit,
+            deadline,
+            expired: false,
+        }
+    }
+}
+
+impl Generator for DeadlineGenerator {
+    fn next_batch(&mut self, size: usize) -> Option<Vec<Vec<u8>>> {
+        batch_via_fill(self, size)
+    }
+    
+    fn fill_batch(&mut self, buf: &mut CandidateBatch) -> bool {
+        if Instant::now() >= self.deadline {
+            self.expired = true;
+            return false;
+        }
+        // the inner generator may have stopped waiting for input at it
+        let filled = self.inner.fill_batch(buf);
+        self.expired = !filled && Instant::now() >= self.deadline;
+        filled
+    }
+    
+    fn estimated_size(&self) -> Option<u128> {
+        self.inner.estimated_size()
+    }
+    
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.deadline = Instant::now() + self.time_limit;
+        self.inner.set_deadline(self.deadline);
+        self.expired = false;
+    }
+    
+    fn found(&mut self, password: &[u8]) {
+        self.inner.found(password);
+    }
+    
+    fn watch_stop(&mut self, stop: Arc<AtomicBool>) {
+        self.inner.watch_stop(stop);
+    }
+    
+    /// running out of time isn't a failure of the source
+    fn finish(&mut self) -> Result<()> {
+        match self.expired {
+            true => Ok(()),
+            false => self.inner.finish(),
+        }
+    }
+    
+    fn progress(&self) -> Option<f64> {
+        self.inner.progress()
+    }
+    
+    fn timed_out(&self) -> bool {
+        self.expired
+    }
+    
+    /// copies share this generator's deadline, and each stops at it on
+    /// its own
+    fn seek_clone(&self, index: u128) -> Option<Box<dyn Generator>> {
+        Some(Box::new(Self {
+            inner: self.inner.seek_clone(index)?,
+            time_limit: self.time_limit,
+            deadline: self.deadline,
+            expired: false,
+        }))
+    }
+    
+    fn candidate_at(&self, index: u128, buf: &mut CandidateBatch) -> bool {
+        Instant::now() < self.deadline && self.inner.candidate_at(index, buf)
+    }
+    
+    fn take_queued(&mut self, buf: &mut CandidateBatch) -> bool {
+        Instant::now() < self.deadline && self.inner.take_queued(buf)
+    }
+}
+
+/// longest digit run a hint counts through; a longer one (a year, a phone
+/// number) would flood the queue
+const HINT_MAX_DIGIT_RUN: usize = 3;
+
+/// guesses a cracked password suggests, each with a rank - lower is more
+/// alike. each digit run is counted away from its value (admin1 → admin2,
+/// admin0, admin3, ...), the letters' case is changed, and a digit or `!`
+/// is appended
+fn hint_variants(password: &[u8]) -> Vec<(u32, Vec<u8>)> {
+    let mut variants = Vec::new();
+    
+    // the mask of the password with its digit runs left open
+    let mut start = 0;
+    while start < password.len() {
+        if !password[start].is_ascii_digit() {
+            start += 1;
+            continue;
+        }
+        let end = password[start..].iter().position(|b| !b.is_ascii_digit()).map_or(password.len(), |n| start + n);
+        let width = end - start;
+        if width <= HINT_MAX_DIGIT_RUN {
+            let value: u32 = std::str::from_utf8(&password[start..end]).unwrap().parse().unwrap();
+            for n in (0..10u32.pow(width as u32)).filter(|&n| n != value) {
+                let mut variant = password[..start].to_vec();
+                variant.extend(format!("{:0width$}", n, width = width).bytes());
+                variant.extend_from_slice(&password[end..]);
+                variants.push((n.abs_diff(value), variant));
+            }
+        }
+        start = end;
+    }
+    
+    if password.iter().any(u8::is_ascii_alphabetic) {
+        let lower = password.to_ascii_lowercase();
+        let mut capitalized = lower.clone();
+        if let Some(first) = capitalized.iter_mut().find(|b| b.is_ascii_alphabetic()) {
+            first.make_ascii_uppercase();
+        }
+        for cased in [lower, capitalized, password.to_ascii_uppercase()] {
+            if cased != password {
+                variants.push((1, cased));
+            }
+        }
+    }
+    
+    for suffix in (b'0'..=b'9').chain([b'!']) {
+        let mut variant = password.to_vec();
+        variant.push(suffix);
+        variants.push((2, variant));
+    }
+    
+    variants
+}
+
+// hint generator - passes another generator's candidates through, but
+// after each crack tries variants of the cracked password first
+// (`run --adaptive-hints`). passwords in one set tend to share a shape,
+// so admin1 being cracked makes admin2 worth trying next. hints wait in a
+// queue, most alike first, and go ahead of the base stream in every batch
+pub struct HintGenerator {
+    inner: Box<dyn Generator>,
+    /// (rank, order queued, candidate): lowest rank first, then oldest
+    hints: BinaryHeap<Reverse<(u32, u64, Vec<u8>)>>,
+    /// every hint queued so far, so none is queued twice
+    queued: HashSet<Vec<u8>>,
+}
+
+impl HintGenerator {
+    pub fn new(inner: Box<dyn Generator>) -> Self {
+        Self {
+            inner,
+            hints: BinaryHeap::new(),
+            queued: HashSet::new(),
+        }
+    }
+}
+
+impl Generator for HintGenerator {
+    fn next_batch(&mut self, size: usize) -> Option<Vec<Vec<u8>>> {
+        batch_via_fill(self, size)
+    }
+    
+    fn fill_batch(&mut self, buf: &mut CandidateBatch) -> bool {
+        let hinted = self.take_queued(buf);
+        (!buf.is_full() && self.inner.fill_batch(buf)) || hinted
+    }
+    
+    fn take_queued(&mut self, buf: &mut CandidateBatch) -> bool {
+        let start = buf.len();
+        while !buf.is_full() {
+            let Some(Reverse((_, _, hint))) = self.hints.pop() else {
+                break;
+            };
+            buf.push(&hint);
+        }
+        buf.len() > start
+    }
+    
+    /// the base keyspace's, once every hint queued has been taken
+    fn seek_clone(&self, index: u128) -> Option<Box<dyn Generator>> {
+        match self.hints.is_empty() {
+            true => self.inner.seek_clone(index),
+            false => None,
+        }
+    }
+    
+    fn candidate_at(&self, index: u128, buf: &mut CandidateBatch) -> bool {
+        self.inner.candidate_at(index, buf)
+    }
+    
+    fn found(&mut self, password: &[u8]) {
+        self.queued.insert(password.to_vec());
+        for (rank, variant) in hint_variants(password) {
+            if self.queued.insert(variant.clone()) {
+                self.hints.push(Reverse((rank, self.queued.len() as u64, variant)));
+            }
+        }
+        self.inner.found(password);
+    }
+    
+    fn watch_stop(&mut self, stop: Arc<AtomicBool>) {
+        self.inner.watch_stop(stop);
+    }
+    
+    fn set_deadline(&mut self, deadline: Instant) {
+        self.inner.set_deadline(deadline);
+    }
+    
+    /// the base keyspace's; hints come on top
+    fn estimated_size(&self) -> Option<u128> {
+        self.inner.estimated_size()
+    }
+    
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.hints.clear();
+        self.queued.clear();
+    }
+    
+    fn finish(&mut self) -> Result<()> {
+        self.inner.finish()
+    }
+    
+    fn timed_out(&self) -> bool {
+        self.inner.timed_out()
+    }
+    
+    fn progress(&self) -> Option<f64> {
+        self.inner.progress()
+    }
+}
+
+/// most candidates `common_variants` makes of one word: itself, three
+/// casings and eleven suffixes
+const COMMON_VARIANTS: u128 = 15;
+
+/// the word, then its common mangles: lowercased, capitalized, uppercased,
+/// and with a digit or `!` appended. casings equal to an earlier candidate
+/// are left out
+fn common_variants(word: &[u8]) -> Vec<Vec<u8>> {
+    let mut variants = vec![word.to_vec()];
+    
+    let lower = word.to_ascii_lowercase();
+    let mut capitalized = lower.clone();
+    if let Some(first) = capitalized.first_mut() {
+        first.make_ascii_uppercase();
+    }
+    for cased in [lower, capitalized, word.to_ascii_uppercase()] {
+        if !variants.contains(&cased) {
+            variants.push(cased);
+        }
+    }
+    
+    for suffix in (b'0'..=b'9').chain([b'!']) {
+        let mut variant = word.to_vec();
+        variant.push(suffix);
+        variants.push(variant);
+    }
+    
+    variants
+}
+
+// rules generator - each of another generator's candidates followed by
+// its `common_variants` (`run --common-rules`), the mangles people make
+// to dictionary words to pass a password policy
+pub struct RulesGenerator {
+    inner: Box<dyn Generator>,
+    /// variants of the words already read, not yet handed out
+    pending: VecDeque<Vec<u8>>,
+}
+
+impl RulesGenerator {
+    pub fn new(inner: Box<dyn Generator>) -> Self {
+        Self { inner, pending: VecDeque::new() }
+    }
+}
+
+impl Generator for RulesGenerator {
+    fn next_batch(&mut self, size: usize) -> Option<Vec<Vec<u8>>> {
+        let mut batch = Vec::with_capacity(size);
+        
+        while batch.len() < size {
+            if let Some(candidate) = self.pending.pop_front() {
+                batch.push(candidate);
+                continue;
+            }
+            let words = (size - batch.len()).div_ceil(COMMON_VARIANTS as usize);
+            let Some(words) = self.inner.next_batch(words) else {
+                break;
+            };
+            self.pending.extend(words.iter().flat_map(|word| common_variants(word)));
+        }
+        
+        (!batch.is_empty()).then_some(batch)
+    }
+    
+    /// at most; casings that repeat an earlier one are skipped
+    fn estimated_size(&self) -> Option<u128> {
+        self.inner.estimated_size().map(|size| size.saturating_mul(COMMON_VARIANTS))
+    }
+    
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.pending.clear();
+    }
+    
+    fn finish(&mut self) -> Result<()> {
+        self.inner.finish()
+    }
+    
+    fn timed_out(&self) -> bool {
+        self.inner.timed_out()
+    }
+    
+    fn progress(&self) -> Option<f64> {
+        self.inner.progress()
+    }
+    
+    fn watch_stop(&mut self, stop: Arc<AtomicBool>) {
+        self.inner.watch_stop(stop);
+    }
+    
+    fn set_deadline(&mut self, deadline: Instant) {
+        self.inner.set_deadline(deadline);
+    }
+    
+    fn found(&mut self, password: &[u8]) {
+        self.inner.found(password);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn write_wordlist(name: &str, contents: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("blitzforge_{}_{}", std::process::id(), name));
+        File::create(&path).unwrap().write_all(contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_dictionary_strips_bom() {
+        let path = write_wordlist("bom.txt", b"\xEF\xBB\xBFpassword\r\nletmein\r\n");
+        let mut gen = DictionaryGenerator::new(&path).unwrap();
+        let batch = gen.next_batch(10).unwrap();
+        assert_eq!(batch, vec![b"password".to_vec(), b"letmein".to_vec()]);
+
+        // bom is stripped again after a reset
+        gen.reset();
+        assert_eq!(gen.next_batch(1).unwrap()[0], b"password");
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_dictionary_no_trim_keeps_spaces() {
+        let path = write_wordlist("spaces.txt", b" pass\r\nword \n");
+
+        let mut gen = DictionaryGenerator::new(&path).unwrap();
+        assert_eq!(gen.next_batch(10).unwrap(), vec![b"pass".to_vec(), b"word".to_vec()]);
+
+        let mut gen = DictionaryGenerator::new(&path).unwrap().with_trim(false);
+        assert_eq!(gen.next_batch(10).unwrap(), vec![b" pass".to_vec(), b"word ".to_vec()]);
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_seek_clone_continues_from_any_index() {
+        fn drain(mut generator: Box<dyn Generator>) -> Vec<Vec<u8>> {
+            let mut all = Vec::new();
+            while let Some(batch) = generator.next_batch(7) {
+                all.extend(batch);
+            }
+            all
+        }
+
+        let generators: Vec<Box<dyn Generator>> = vec![
+            Box::new(MaskGenerator::new("?d?l?d").unwrap()),
+            Box::new(BruteForceGenerator::new("abc", 0, 3)),
+        ];
+        for generator in generators {
+            let all = drain(generator.seek_clone(0).unwrap());
+            assert_eq!(all.len() as u128, generator.estimated_size().unwrap());
+            for start in [1, 3, 4, 12, 39, all.len() - 1, all.len(), all.len() + 5] {
+                let rest = drain(generator.seek_clone(start as u128).unwrap());
+                assert_eq!(rest, all[start.min(all.len())..], "from {}", start);
+            }
+        }
+
+        let mut dictionary = MemoryGenerator::new(vec![b"a".to_vec()]);
+        assert!(dictionary.seek_clone(0).is_none());
+        assert!(dictionary.next_batch(1).is_some());
+    }
+
+    #[test]
+    fn test_dictionary_skips_overlong_lines() {
+        let blob = "QUFB".repeat(64);
+        let path = write_wordlist("overlong.txt", format!("short\n{}\n  padded  \nlast\n", blob).as_bytes());
+
+        let mut gen = DictionaryGenerator::new(&path).unwrap().with_max_word_len(Some(6));
+        assert_eq!(gen.next_batch(10).unwrap(), vec![b"short".to_vec(), b"padded".to_vec(), b"last".to_vec()]);
+
+        // a batch taken up by the skipped line still carries on past it
+        let mut gen = DictionaryGenerator::new(&path).unwrap().with_max_word_len(Some(6));
+        assert_eq!(gen.next_batch(1).unwrap(), vec![b"short".to_vec()]);
+        assert_eq!(gen.next_batch(1).unwrap(), vec![b"padded".to_vec()]);
+        assert_eq!(gen.lines_read(), 3);
+
+        let mut gen = DictionaryGenerator::new(&path).unwrap();
+        assert_eq!(gen.next_batch(10).unwrap().len(), 4);
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_dictionary_follows_appended_lines() {
+        let path = write_wordlist("follow.txt", b"first\nsecond\n");
+        let mut gen = DictionaryGenerator::new(&path).unwrap().with_follow(Duration::from_millis(500));
+        assert_eq!(gen.next_batch(10).unwrap(), vec![b"first".to_vec(), b"second".to_vec()]);
+        
+        // appended mid-stream, the line split across two writes
+        let writer = {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                let mut file = std::fs::OpenOptions::new().append(true).open(path).unwrap();
+                std::thread::sleep(Duration::from_millis(150));
+                file.write_all(b"la").unwrap();
+                std::thread::sleep(Duration::from_millis(150));
+                file.write_all(b"te\n").unwrap();
+            })
+        };
+        
+        let waited = Instant::now();
+        assert_eq!(gen.next_batch(10).unwrap(), vec![b"late".to_vec()]);
+        assert!(waited.elapsed() >= Duration::from_millis(300));
+        writer.join().unwrap();
+        
+        // nothing more arrives, so the list ends once it's been idle
+        assert_eq!(gen.next_batch(10), None);
+        assert_eq!(gen.lines_read(), 3);
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_dictionary_follow_stops_waiting_at_stop_or_deadline() {
+        let path = write_wordlist("follow_stop.txt", b"first
+");
+        let following = || DictionaryGenerator::new(&path).unwrap().with_follow(Duration::from_secs(3600));
+
+        // --max-time: the wait ends at the deadline, which reads as timed out
+        let mut gen = DeadlineGenerator::new(Box::new(following()), Duration::from_millis(200));
+        assert_eq!(gen.next_batch(10).unwrap(), vec![b"first".to_vec()]);
+        let waited = Instant::now();
+        assert_eq!(gen.next_batch(10), None);
+        assert!(waited.elapsed() < Duration::from_secs(5));
+        assert!(gen.timed_out());
+
+        // a stop requested by ctrl-c or sigterm
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut gen = following();
+        gen.watch_stop(Arc::clone(&stop));
+        assert_eq!(gen.next_batch(10).unwrap(), vec![b"first".to_vec()]);
+        let stopper = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(150));
+            stop.store(true, Ordering::Relaxed);
+        });
+        let waited = Instant::now();
+        assert_eq!(gen.next_batch(10), None);
+        assert!(waited.elapsed() < Duration::from_secs(5));
+        stopper.join().unwrap();
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_multi_dictionary_chains_wordlists() {
+        let first = write_wordlist("multi1.txt", b"alpha\nbravo\ncharlie\n");
+        let second = write_wordlist("multi2.txt", b"delta\necho");
+
+        let mut gen = MultiDictionaryGenerator::new(&[&first, &second]).unwrap();
+        assert_eq!(gen.estimated_size(), Some(5));
+
+        // batches straddle the boundary between the two lists
+        let mut all = Vec::new();
+        while let Some(batch) = gen.next_batch(2) {
+            assert!(batch.len() <= 2);
+            all.extend(batch);
+        }
+        let expected: Vec<Vec<u8>> = ["alpha", "bravo", "charlie", "delta", "echo"]
+            .iter()
+            .map(|w| w.as_bytes().to_vec())
+            .collect();
+        assert_eq!(all, expected);
+
+        gen.reset();
+        assert_eq!(gen.next_batch(1).unwrap()[0], b"alpha");
+        assert_eq!(gen.progress(), Some(0.2));
+
+        std::fs::remove_file(first).ok();
+        std::fs::remove_file(second).ok();
+    }
+
+    #[test]
+    fn test_multi_dictionary_orderings() {
+        let first = write_wordlist("order1.txt", b"a1\na2\na3\na4\na5\n");
+        let second = write_wordlist("order2.txt", b"b1\nb2\n");
+
+        let all = |mut gen: MultiDictionaryGenerator| {
+            let mut words = Vec::new();
+            while let Some(batch) = gen.next_batch(2) {
+                words.extend(batch.into_iter().map(|w| String::from_utf8(w).unwrap()));
+            }
+            words
+        };
+
+        let sequential = MultiDictionaryGenerator::new(&[&first, &second]).unwrap();
+        assert_eq!(all(sequential), ["a1", "a2", "a3", "a4", "a5", "b1", "b2"]);
+
+        // one batch from each list in turn, then what's left of the longer one
+        let interleaved = MultiDictionaryGenerator::new(&[&first, &second]).unwrap().with_interleaved(true);
+        assert_eq!(all(interleaved), ["a1", "a2", "b1", "b2", "a3", "a4", "a5"]);
+
+        std::fs::remove_file(first).ok();
+        std::fs::remove_file(second).ok();
+    }
+
+    #[test]
+    fn test_keyspaces_past_u64() {
+        // 26^14 is about 6.5e19, more than u64::MAX
+        let mask = MaskGenerator::new(&"?l".repeat(14)).unwrap();
+        assert_eq!(mask.estimated_size(), Some(26u128.pow(14)));
+        assert!(mask.estimated_size().unwrap() > u128::from(u64::MAX));
+        
+        // all 95 printable ascii characters, lengths 1 to 10
+        let printable: String = (b' '..=b'~').map(char::from).collect();
+        let brute = BruteForceGenerator::new(&printable, 1, 10);
+        assert_eq!(brute.estimated_size(), Some((1..=10).map(|len| 95u128.pow(len)).sum()));
+        
+        let range = RangeGenerator::new(Box::new(mask), 5, None);
+        assert_eq!(range.estimated_size(), Some(26u128.pow(14) - 5));
+        let range = RangeGenerator::new(Box::new(brute), 0, Some(1000));
+        assert_eq!(range.estimated_size(), Some(1000));
+        
+        // far past u128 too: saturates rather than wrapping
+        let huge = MaskGenerator::new(&"?l".repeat(40)).unwrap();
+        assert_eq!(huge.estimated_size(), Some(u128::MAX));
+        assert_eq!(BruteForceGenerator::new(&printable, 1, 40).estimated_size(), Some(u128::MAX));
+    }
+
+    #[test]
+    fn test_mask_progress() {
+        let mut gen = MaskGenerator::new("?d").unwrap();
+        assert_eq!(gen.progress(), Some(0.0));
+        gen.next_batch(5);
+        assert_eq!(gen.progress(), Some(0.5));
+        gen.next_batch(5);
+        assert_eq!(gen.progress(), Some(1.0));
+    }
+
+    #[test]
+    fn test_mask_escaped_question_mark() {
+        let mut gen = MaskGenerator::new("a??b").unwrap();
+        assert_eq!(gen.positions(), 3);
+        assert_eq!(gen.next_batch(10).unwrap(), vec![b"a?b".to_vec()]);
+        assert!(gen.next_batch(10).is_none());
+
+        // escapes and classes mix
+        let mut gen = MaskGenerator::new("???d").unwrap();
+        let batch = gen.next_batch(20).unwrap();
+        assert_eq!(batch.len(), 10);
+        assert_eq!(batch[0], b"?0".to_vec());
+
+        assert!(MaskGenerator::new("?q").is_err());
+    }
+
+    #[test]
+    fn test_mask_length_counts_characters() {
+        assert_eq!(MaskGenerator::new("?l?l?d?d").unwrap().length(), 4);
+        assert_eq!(MaskGenerator::new("pw??").unwrap().length(), 3);
+        // two utf-8 bytes, one character
+        let mask = MaskGenerator::new("caf\u{e9}?d").unwrap();
+        assert_eq!((mask.length(), mask.positions()), (5, 6));
+    }
+
+    #[test]
+    fn test_template_fixed_prefix() {
+        let mut gen = MaskGenerator::from_template("admin..", "01").unwrap();
+        assert_eq!(gen.estimated_size(), Some(4));
+        let batch = gen.next_batch(10).unwrap();
+        assert_eq!(batch, vec![
+            b"admin00".to_vec(), b"admin01".to_vec(), b"admin10".to_vec(), b"admin11".to_vec(),
+        ]);
+    }
+
+    #[test]
+    fn test_template_fixed_suffix() {
+        let mut gen = MaskGenerator::from_template("..2024", "ab").unwrap();
+        let batch = gen.next_batch(10).unwrap();
+        assert_eq!(batch.len(), 4);
+        assert_eq!(batch[0], b"aa2024");
+        assert_eq!(batch[3], b"bb2024");
+        assert!(batch.iter().all(|c| c.ends_with(b"2024")));
+
+        assert!(MaskGenerator::from_template("", "ab").is_err());
+    }
+
+    #[test]
+    fn test_brute_force_progress() {
+        // a, b, aa, ab, ba, bb
+        let mut gen = BruteForceGenerator::new("ab", 1, 2);
+        assert_eq!(gen.progress(), Some(0.0));
+        gen.next_batch(3);
+        assert_eq!(gen.progress(), Some(0.5));
+        gen.next_batch(3);
+        assert_eq!(gen.progress(), Some(1.0));
+    }
+
+    #[test]
+    fn test_dictionary_progress() {
+        let path = write_wordlist("progress.txt", b"aaaa\nbbbb\ncccc\ndddd\n");
+        let mut gen = DictionaryGenerator::new(&path).unwrap();
+        assert_eq!(gen.progress(), Some(0.0));
+        gen.next_batch(2);
+        assert_eq!(gen.progress(), Some(0.5));
+        gen.next_batch(2);
+        assert_eq!(gen.progress(), Some(1.0));
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_candidate_at_follows_the_generated_order() {
+        let generators: Vec<Box<dyn Generator>> = vec![
+            Box::new(MaskGenerator::new("?dx?l").unwrap()),
+            Box::new(BruteForceGenerator::new("abc", 1, 3)),
+        ];
+        for mut gen in generators {
+            let mut expected = Vec::new();
+            while let Some(batch) = gen.next_batch(100) {
+                expected.extend(batch);
+            }
+
+            let mut buf = CandidateBatch::new(expected.len());
+            for index in 0..expected.len() as u128 {
+                assert!(gen.candidate_at(index, &mut buf));
+            }
+            assert!(!gen.candidate_at(expected.len() as u128, &mut buf));
+            assert_eq!(buf.iter().map(|c| c.to_vec()).collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_sample_draws_distinct_candidates() {
+        let mask = || Box::new(MaskGenerator::new("?d?d?d").unwrap());
+        let drawn = |count, seed| {
+            let mut gen = SampleGenerator::new(mask(), count, seed).unwrap();
+            let mut all = Vec::new();
+            while let Some(batch) = gen.next_batch(64) {
+                all.extend(batch);
+            }
+            all
+        };
+
+        let sample = drawn(100, 7);
+        assert_eq!(sample.len(), 100);
+        assert_eq!(sample.iter().collect::<std::collections::HashSet<_>>().len(), 100);
+        assert_eq!(drawn(100, 7), sample);
+        assert_ne!(drawn(100, 8), sample);
+        // not simply the first hundred
+        assert_ne!(sample, (0..100).map(|n| format!("{:03}", n).into_bytes()).collect::<Vec<_>>());
+
+        // asking for more than there is takes the whole keyspace once
+        let mut everything = drawn(5000, 1);
+        everything.sort();
+        assert_eq!(everything, (0..1000).map(|n| format!("{:03}", n).into_bytes()).collect::<Vec<_>>());
+
+        // a stream has no numbered keyspace to draw from
+        let words = Box::new(MemoryGenerator::new(vec![b"a".to_vec()]));
+        assert!(SampleGenerator::new(words, 1, 0).is_err());
+        // nor one too big to number
+        let huge = Box::new(MaskGenerator::new(&"?s".repeat(30)).unwrap());
+        assert!(SampleGenerator::new(huge, 1, 0).is_err());
+    }
+
+    #[test]
+    fn test_rules_follow_each_word_with_its_variants() {
+        let words = MemoryGenerator::new(vec![b"Dragon".to_vec(), b"42".to_vec()]);
+        let mut gen = RulesGenerator::new(Box::new(words));
+        assert_eq!(gen.estimated_size(), Some(30));
+
+        let mut candidates = Vec::new();
+        while let Some(batch) = gen.next_batch(4) {
+            assert!(batch.len() <= 4);
+            candidates.extend(batch);
+        }
+        let text: Vec<String> = candidates.iter().map(|c| String::from_utf8_lossy(c).to_string()).collect();
+        assert_eq!(text[..5], ["Dragon", "dragon", "DRAGON", "Dragon0", "Dragon1"]);
+        assert_eq!(text[13], "Dragon!");
+        // digits have no case, so only the suffixes follow
+        assert_eq!(text[14..17], ["42", "420", "421"]);
+        assert_eq!(text.len(), 14 + 12);
+
+        gen.reset();
+        assert_eq!(gen.next_batch(1).unwrap(), [b"Dragon".to_vec()]);
+    }
+
+    #[test]
+    fn test_hint_variants_count_away_from_the_digits() {
+        let variants = hint_variants(b"admin1");
+        let mut ranked: Vec<&(u32, Vec<u8>)> = variants.iter().collect();
+        ranked.sort_by_key(|(rank, _)| *rank);
+        let closest: Vec<&[u8]> = ranked.iter().take(4).map(|(_, v)| v.as_slice()).collect();
+        assert_eq!(closest, [&b"admin0"[..], b"admin2", b"Admin1", b"ADMIN1"]);
+        assert!(variants.iter().any(|(_, v)| v == b"admin1!"));
+        assert!(!variants.iter().any(|(_, v)| v == b"admin1"));
+
+        // a long number is left alone
+        assert!(!hint_variants(b"bob19990101").iter().any(|(_, v)| v == b"bob19990102"));
+    }
+
+    #[test]
+    fn test_cracked_admin1_gets_admin2_tried_soon_after() {
+        use crate::core::hasher::create_hasher;
+        use crate::core::{Algorithm, Engine, Target};
+
+        let target = |id: &str, password: &[u8]| Target {
+            id: id.to_string(),
+            username: id.to_string(),
+            algorithm: Algorithm::Md5,
+            hash: hex::encode(create_hasher(Algorithm::Md5).hash(password)),
+            ..Default::default()
+        };
+        // admin1 comes early, admin2 only after thousands of other words
+        let mut words: Vec<Vec<u8>> = vec![b"admin1".to_vec()];
+        words.extend((0..5000).map(|n| format!("filler{}", n).into_bytes()));
+        words.push(b"admin2".to_vec());
+        let targets = vec![target("first", b"admin1"), target("second", b"admin2")];
+
+        let run = |generator: Box<dyn Generator>| {
+            let mut engine = Engine::new(targets.clone(), generator, 1, 8);
+            let result = engine.run(|_| {}).unwrap();
+            assert_eq!(result.matches.len(), 2);
+            result.matches.iter().find(|m| m.target_id == "second").unwrap().guesses_tried
+        };
+
+        let plain = run(Box::new(MemoryGenerator::new(words.clone())));
+        let hinted = run(Box::new(HintGenerator::new(Box::new(MemoryGenerator::new(words)))));
+        assert!(plain >= 5000, "{}", plain);
+        // the batch after admin1's is made of its variants, admin2 among the first
+        assert!(hinted <= 16, "{}", hinted);
+    }
+
+    #[test]
+    fn test_range_generator_window() {
+        let inner = Box::new(MaskGenerator::new("?d?d").unwrap());
+        let mut gen = RangeGenerator::new(inner, 15, Some(10));
+        assert_eq!(gen.estimated_size(), Some(10));
+
+        let mut seen = Vec::new();
+        while let Some(batch) = gen.next_batch(3) {
+            seen.extend(batch);
+        }
+        assert_eq!(seen.first().unwrap(), b"15");
+        assert_eq!(seen.last().unwrap(), b"24");
+        assert_eq!(seen.len(), 10);
+        assert_eq!(gen.progress(), Some(1.0));
+
+        // a mask seeks to the skip rather than generating its way there
+        let inner = Box::new(MaskGenerator::new("?d?d?d?d?d?d?d?d?d?d?d?d?d?d?d?d").unwrap());
+        let mut gen = RangeGenerator::new(inner, 1_000_000_000_000_000, Some(2));
+        assert_eq!(gen.next_batch(5).unwrap(), vec![b"1000000000000000".to_vec(), b"1000000000000001".to_vec()]);
+    }
+
+    #[test]
+    fn test_range_generator_seeks_within_window() {
+        let window = || RangeGenerator::new(Box::new(MaskGenerator::new("?d?d").unwrap()), 15, Some(10));
+        let mut all = Vec::new();
+        let mut gen = window();
+        while let Some(batch) = gen.next_batch(4) {
+            all.extend(batch);
+        }
+
+        // a copy from any index runs to the window's end, and reports
+        // what's left of it
+        for start in [0, 1, 6, 9, 10, 12] {
+            let mut copy = window().seek_clone(start as u128).unwrap();
+            assert_eq!(copy.estimated_size(), Some(10u128.saturating_sub(start as u128)));
+            let mut rest = Vec::new();
+            while let Some(batch) = copy.next_batch(3) {
+                rest.extend(batch);
+            }
+            assert_eq!(rest, all[start.min(all.len())..], "from {}", start);
+        }
+
+        let mut buf = CandidateBatch::new(all.len());
+        for index in 0..all.len() as u128 {
+            assert!(window().candidate_at(index, &mut buf));
+        }
+        assert!(!window().candidate_at(10, &mut buf));
+        assert_eq!(buf.iter().map(|c| c.to_vec()).collect::<Vec<_>>(), all);
+
+        // a stream window still can't be split
+        let words = Box::new(MemoryGenerator::new(vec![b"a".to_vec(), b"b".to_vec()]));
+        assert!(RangeGenerator::new(words, 1, None).seek_clone(0).is_none());
+    }
+
+    #[test]
+    fn test_memory_generator_sorts() {
+        let path = write_wordlist("sort.txt", b"charlie\nab\nbravo\nzz\nalpha\n");
+        let mut dict = DictionaryGenerator::new(&path).unwrap();
+        let mut gen = MemoryGenerator::load(&mut dict, None).unwrap();
+
+        gen.sort_by_length();
+        let batch = gen.next_batch(10).unwrap();
+        let words: Vec<&[u8]> = batch.iter().map(|c| c.as_slice()).collect();
+        // equal lengths stay in file order
+        assert_eq!(words, [&b"ab"[..], b"zz", b"bravo", b"alpha", b"charlie"]);
+        assert!(gen.next_batch(10).is_none());
+
+        gen.sort_lex();
+        gen.reset();
+        assert_eq!(gen.next_batch(2).unwrap(), vec![b"ab".to_vec(), b"alpha".to_vec()]);
+        assert_eq!(gen.progress(), Some(0.4));
+
+        // over the memory limit
+        dict.reset();
+        assert!(MemoryGenerator::load(&mut dict, Some(64)).is_err());
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_weighted_wordlist_heaviest_first() {
+        assert_eq!(DictionaryGenerator::split_weight(b"password,9999"), (&b"password"[..], Some(9999)));
+        assert_eq!(DictionaryGenerator::split_weight(b"a,b"), (&b"a,b"[..], None));
+        assert_eq!(DictionaryGenerator::split_weight(b",5"), (&b",5"[..], None));
+        assert_eq!(DictionaryGenerator::split_weight(b"x,y,3"), (&b"x,y"[..], Some(3)));
+
+        let path = write_wordlist("weighted.txt", b"letmein,10\nhunter2\npassword,9999\nq,w,e\n123456,500\ndragon,10\n");
+        let mut dict = DictionaryGenerator::new(&path).unwrap().with_weights(true);
+        let mut gen = MemoryGenerator::load_by_weight(&mut dict, None).unwrap();
+
+        let batch = gen.next_batch(10).unwrap();
+        let words: Vec<&[u8]> = batch.iter().map(|c| c.as_slice()).collect();
+        // unweighted lines last; ties stay in file order
+        assert_eq!(words, [&b"password"[..], b"123456", b"letmein", b"dragon", b"hunter2", b"q,w,e"]);
+
+        // the length limit measures the word, not its weight
+        let mut dict = DictionaryGenerator::new(&path).unwrap().with_weights(true).with_max_word_len(Some(6));
+        let mut gen = MemoryGenerator::load_by_weight(&mut dict, None).unwrap();
+        std::fs::remove_file(path).ok();
+        let batch = gen.next_batch(10).unwrap();
+        let words: Vec<&[u8]> = batch.iter().map(|c| c.as_slice()).collect();
+        assert_eq!(words, [&b"123456"[..], b"dragon", b"q,w,e"]);
+    }
+
+    /// hands out `failures` errors of `kind` before reading normally
+    struct FlakyReader {
+        inner: std::io::Cursor<Vec<u8>>,
+        kind: io::ErrorKind,
+        failures: u32,
+    }
+
+    impl io::Read for FlakyReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err(io::Error::new(self.kind, "flaky"));
+            }
+            self.inner.read(buf)
+        }
+    }
+
+    fn flaky(kind: io::ErrorKind, failures: u32) -> BufReader<FlakyReader> {
+        let inner = std::io::Cursor::new(b"alpha\nbeta\n".to_vec());
+        BufReader::new(FlakyReader { inner, kind, failures })
+    }
+
+    #[test]
+    fn test_transient_read_error_is_retried() {
+        let mut reader = flaky(io::ErrorKind::TimedOut, READ_RETRIES);
+        let mut line = Vec::new();
+        assert_eq!(read_line_retrying(&mut reader, &mut line).unwrap(), 6);
+        assert_eq!(line, b"alpha\n");
+
+        // one failure too many and the error comes back
+        let mut reader = flaky(io::ErrorKind::TimedOut, READ_RETRIES + 1);
+        let err = read_line_retrying(&mut reader, &mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+        // permanent errors aren't retried at all
+        let mut reader = flaky(io::ErrorKind::PermissionDenied, 1);
+        assert!(read_line_retrying(&mut reader, &mut Vec::new()).is_err());
+        assert_eq!(reader.get_ref().failures, 0);
+    }
+
+    #[test]
+    fn test_dictionary_read_error_reported_by_finish() {
+        let path = write_wordlist("dict_read_error.txt", b"alpha\n");
+        let mut gen = DictionaryGenerator::new(&path).unwrap();
+        gen.error = Some(io::Error::other("disk went away"));
+        assert!(gen.next_batch(10).is_none());
+        let err = gen.finish().unwrap_err().to_string();
+        assert!(err.contains("disk went away"), "{}", err);
+
+        gen.reset();
+        assert_eq!(gen.next_batch(10).unwrap(), vec![b"alpha".to_vec()]);
+        assert!(gen.next_batch(10).is_none());
+        assert!(gen.finish().is_ok());
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_deadline_generator_stops_at_deadline() {
+        let inner = Box::new(MaskGenerator::new("?d?d").unwrap());
+        let mut gen = DeadlineGenerator::new(inner, Duration::from_secs(3600));
+        assert_eq!(gen.next_batch(5).unwrap().len(), 5);
+        assert!(!gen.timed_out());
+
+        gen.deadline = Instant::now();
+        assert!(gen.next_batch(5).is_none());
+        assert!(gen.timed_out());
+        assert!(gen.finish().is_ok());
+
+        // reset restarts both the keyspace and the clock
+        gen.reset();
+        assert_eq!(gen.next_batch(1).unwrap()[0], b"00");
+
+        // copies from an index keep the deadline
+        let mut buf = CandidateBatch::new(2);
+        let mut copy = gen.seek_clone(42).unwrap();
+        assert_eq!(copy.next_batch(1).unwrap()[0], b"42");
+        assert!(gen.candidate_at(7, &mut buf));
+        gen.deadline = Instant::now();
+        let mut copy = gen.seek_clone(42).unwrap();
+        assert!(copy.next_batch(1).is_none());
+        assert!(copy.timed_out());
+        assert!(!gen.candidate_at(7, &mut buf));
+        assert_eq!(buf.get(0), b"07");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_process_generator_reads_stdout() {
+        let mut gen = ProcessGenerator::new("printf 'alpha\\nbeta\\r\\n\\ngamma'").unwrap();
+        assert_eq!(gen.next_batch(2).unwrap(), vec![b"alpha".to_vec(), b"beta".to_vec()]);
+        assert_eq!(gen.next_batch(10).unwrap(), vec![b"gamma".to_vec()]);
+        assert!(gen.next_batch(10).is_none());
+        assert!(gen.finish().is_ok());
+
+        // reset reruns the command
+        gen.reset();
+        assert_eq!(gen.next_batch(1).unwrap()[0], b"alpha");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_process_generator_nonzero_exit_is_error() {
+        let mut gen = ProcessGenerator::new("printf 'a\\n'; exit 3").unwrap();
+        assert_eq!(gen.next_batch(10).unwrap(), vec![b"a".to_vec()]);
+        assert!(gen.next_batch(10).is_none());
+        let err = gen.finish().unwrap_err().to_string();
+        assert!(err.contains("failed"), "{}", err);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_process_generator_read_error_is_error() {
+        // the command would exit cleanly; the read failing is what counts
+        let mut gen = ProcessGenerator::new("yes | head -n 100000").unwrap();
+        assert_eq!(gen.next_batch(1).unwrap(), vec![b"y".to_vec()]);
+        gen.read_error = Some(io::Error::new(io::ErrorKind::InvalidData, "stream corrupted"));
+        assert!(gen.next_batch(10).is_none());
+        let err = gen.finish().unwrap_err().to_string();
+        assert!(err.contains("stream corrupted") && err.contains("after 1 lines"), "{}", err);
+    }
+}
@@ -2,17 +2,68 @@ use md5::{Md5, Digest as Md5Digest};
 use sha1::{Sha1, Digest as Sha1Digest};
 use sha2::{Sha256, Digest as Sha256Digest};
 use serde::{Deserialize, Serialize};
+use scrypt::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
 
 use super::blitzhash;
+use super::crypt;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Algorithm {
     BlitzHash,  // custom ultra-fast hash (demo only - not cryptographically secure)
+    #[default]
     Md5,
     Sha1,
     Sha256,
     Md4,  // for ntlm hashes
+    Scrypt,  // memory-hard kdf - targets store a phc string
+    Argon2,  // argon2id kdf - targets store a phc string
+    Bcrypt,  // cpu-hard kdf - targets store a `$2b$` modular crypt string
+    Md5Crypt,  // `$1$` unix crypt - targets store the crypt string
+    Sha256Crypt,  // `$5$` unix crypt
+    Sha512Crypt,  // `$6$` unix crypt (the linux /etc/shadow default)
+}
+
+impl Algorithm {
+    /// every supported algorithm, fastest family first
+    pub const ALL: [Algorithm; 11] = [
+        Algorithm::BlitzHash,
+        Algorithm::Md5,
+        Algorithm::Md4,
+        Algorithm::Sha1,
+        Algorithm::Sha256,
+        Algorithm::Md5Crypt,
+        Algorithm::Sha256Crypt,
+        Algorithm::Sha512Crypt,
+        Algorithm::Bcrypt,
+        Algorithm::Scrypt,
+        Algorithm::Argon2,
+    ];
+    
+    /// key derivation functions carry their salt and cost parameters in a
+    /// phc string (`$scrypt$ln=..,r=..,p=..$salt$hash`, or bcrypt's similar
+    /// `$2b$cost$saltdigest` and the unix `$1$`/`$5$`/`$6$` crypt strings)
+    /// and are matched by verifying each candidate against it rather than
+    /// comparing raw digests
+    pub fn is_kdf(&self) -> bool {
+        matches!(
+            self,
+            Algorithm::Scrypt
+                | Algorithm::Argon2
+                | Algorithm::Bcrypt
+                | Algorithm::Md5Crypt
+                | Algorithm::Sha256Crypt
+                | Algorithm::Sha512Crypt
+        )
+    }
+    
+    /// schemes whose hash is a crypt string, parsed by `crypt::parse_crypt`
+    pub fn is_crypt(&self) -> bool {
+        matches!(
+            self,
+            Algorithm::Bcrypt | Algorithm::Md5Crypt | Algorithm::Sha256Crypt | Algorithm::Sha512Crypt
+        )
+    }
 }
 
 impl std::fmt::Display for Algorithm {
@@ -23,6 +74,12 @@ impl std::fmt::Display for Algorithm {
             Algorithm::Sha1 => write!(f, "sha1"),
             Algorithm::Sha256 => write!(f, "sha256"),
             Algorithm::Md4 => write!(f, "md4"),
+            Algorithm::Scrypt => write!(f, "scrypt"),
+            Algorithm::Argon2 => write!(f, "argon2"),
+            Algorithm::Bcrypt => write!(f, "bcrypt"),
+            Algorithm::Md5Crypt => write!(f, "md5crypt"),
+            Algorithm::Sha256Crypt => write!(f, "sha256crypt"),
+            Algorithm::Sha512Crypt => write!(f, "sha512crypt"),
         }
     }
 }
@@ -37,6 +94,12 @@ impl std::str::FromStr for Algorithm {
             "sha1" => Ok(Algorithm::Sha1),
             "sha256" => Ok(Algorithm::Sha256),
             "md4" => Ok(Algorithm::Md4),
+            "scrypt" => Ok(Algorithm::Scrypt),
+            "argon2" | "argon2id" => Ok(Algorithm::Argon2),
+            "bcrypt" => Ok(Algorithm::Bcrypt),
+            "md5crypt" | "md5-crypt" => Ok(Algorithm::Md5Crypt),
+            "sha256crypt" | "sha256-crypt" => Ok(Algorithm::Sha256Crypt),
+            "sha512crypt" | "sha512-crypt" => Ok(Algorithm::Sha512Crypt),
             _ => Err(format!("unknown algorithm: {}", s)),
         }
     }
@@ -168,6 +231,263 @@ impl Hasher for Md4Hasher {
     }
 }
 
+// scrypt hasher - raw derived key with fixed demo parameters.
+// memory per hash is 128 * r * 2^log_n bytes, and every worker hashes
+// concurrently, so total memory scales with the worker count
+pub struct ScryptHasher {
+    params: scrypt::Params,
+}
+
+/// low-cost demo parameters: n = 2^10, r = 8, p = 1 (1 MiB per hash)
+pub const SCRYPT_DEMO_LOG_N: u8 = 10;
+
+impl ScryptHasher {
+    pub fn new(log_n: u8, r: u32, p: u32) -> anyhow::Result<Self> {
+        let params = scrypt::Params::new(log_n, r, p, 32)
+            .map_err(|e| anyhow::anyhow!("invalid scrypt parameters: {}", e))?;
+        Ok(Self { params })
+    }
+}
+
+impl Hasher for ScryptHasher {
+    fn hash(&self, input: &[u8]) -> Vec<u8> {
+        self.hash_with_salt(input, &[])
+    }
+    
+    fn hash_with_salt(&self, password: &[u8], salt: &[u8]) -> Vec<u8> {
+        let mut output = vec![0u8; 32];
+        scrypt::scrypt(password, salt, &self.params, &mut output)
+            .expect("output length is valid");
+        output
+    }
+    
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::Scrypt
+    }
+}
+
+// argon2id hasher - raw derived key with configurable costs.
+// memory per hash is m_cost kib; like scrypt it is paid once per worker
+pub struct Argon2Hasher {
+    argon2: argon2::Argon2<'static>,
+}
+
+/// low-cost demo parameters: 1 mib memory, 2 passes, 1 lane
+pub const ARGON2_DEMO_M_COST: u32 = 1024;
+pub const ARGON2_DEMO_T_COST: u32 = 2;
+pub const ARGON2_DEMO_P_COST: u32 = 1;
+
+/// argon2 rejects salts under 8 bytes; unsalted hashing uses zeros instead
+const ARGON2_EMPTY_SALT: [u8; 16] = [0u8; 16];
+
+impl Argon2Hasher {
+    /// m_cost in kib, t_cost in passes, p_cost in lanes
+    pub fn new(m_cost: u32, t_cost: u32, p_cost: u32) -> anyhow::Result<Self> {
+        let params = argon2::Params::new(m_cost, t_cost, p_cost, Some(32))
+            .map_err(|e| anyhow::anyhow!("invalid argon2 parameters: {}", e))?;
+        Ok(Self {
+            argon2: argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params),
+        })
+    }
+}
+
+impl Hasher for Argon2Hasher {
+    fn hash(&self, input: &[u8]) -> Vec<u8> {
+        self.hash_with_salt(input, &ARGON2_EMPTY_SALT)
+    }
+    
+    fn hash_with_salt(&self, password: &[u8], salt: &[u8]) -> Vec<u8> {
+        let salt = if salt.is_empty() { &ARGON2_EMPTY_SALT[..] } else { salt };
+        let mut output = vec![0u8; 32];
+        self.argon2.hash_password_into(password, salt, &mut output)
+            .expect("argon2 salt must be at least 8 bytes");
+        output
+    }
+    
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::Argon2
+    }
+}
+
+// bcrypt hasher - raw 24-byte blowfish output with a fixed demo cost.
+// cpu-hard rather than memory-hard: each hash does 2^cost key setups
+pub struct BcryptHasher {
+    cost: u32,
+}
+
+/// lowest cost bcrypt allows (2^4 rounds) - keeps demo cracking quick
+pub const BCRYPT_DEMO_COST: u32 = 4;
+
+/// bcrypt reads the password plus a nul terminator, truncated to 72 bytes
+const BCRYPT_MAX_INPUT: usize = 72;
+
+impl BcryptHasher {
+    pub fn new(cost: u32) -> anyhow::Result<Self> {
+        if !(4..=31).contains(&cost) {
+            anyhow::bail!("invalid bcrypt cost {} (expected 4-31)", cost);
+        }
+        Ok(Self { cost })
+    }
+}
+
+impl Hasher for BcryptHasher {
+    fn hash(&self, input: &[u8]) -> Vec<u8> {
+        self.hash_with_salt(input, &[])
+    }
+    
+    /// salts are zero-padded or cut to bcrypt's fixed 16 bytes
+    fn hash_with_salt(&self, password: &[u8], salt: &[u8]) -> Vec<u8> {
+        let mut fixed_salt = [0u8; 16];
+        let n = salt.len().min(16);
+        fixed_salt[..n].copy_from_slice(&salt[..n]);
+        
+        let mut input = Vec::with_capacity(password.len() + 1);
+        input.extend_from_slice(password);
+        input.push(0);
+        input.truncate(BCRYPT_MAX_INPUT);
+        
+        bcrypt::bcrypt(self.cost, fixed_salt, &input).to_vec()
+    }
+    
+    fn algorithm(&self) -> Algorithm {
+        Algorithm::Bcrypt
+    }
+}
+
+// unix crypt hasher ($1$, $5$, $6$) - the crypt string's bytes, under the
+// salt and demo rounds from `crypt::demo_setting`
+pub struct CryptHasher {
+    algorithm: Algorithm,
+}
+
+impl Hasher for CryptHasher {
+    fn hash(&self, input: &[u8]) -> Vec<u8> {
+        self.hash_with_salt(input, &[])
+    }
+    
+    fn hash_with_salt(&self, password: &[u8], salt: &[u8]) -> Vec<u8> {
+        let setting = crypt::demo_setting(self.algorithm, salt).unwrap_or_default();
+        crypt::crypt(self.algorithm, &setting, password)
+            .unwrap_or_default()
+            .into_bytes()
+    }
+    
+    fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+}
+
+/// longest input that affects the hash, for schemes that silently ignore
+/// the rest. candidates agreeing up to this length hash identically, so
+/// generators can stop there. of the supported algorithms only bcrypt
+/// truncates (72 bytes); the others read the whole input
+pub fn effective_max_len(algorithm: Algorithm) -> Option<usize> {
+    match algorithm {
+        Algorithm::Bcrypt => Some(BCRYPT_MAX_INPUT),
+        Algorithm::BlitzHash
+        | Algorithm::Md5
+        | Algorithm::Sha1
+        | Algorithm::Sha256
+        | Algorithm::Md4
+        | Algorithm::Scrypt
+        | Algorithm::Argon2
+        | Algorithm::Md5Crypt
+        | Algorithm::Sha256Crypt
+        | Algorithm::Sha512Crypt => None,
+    }
+}
+
+/// memory a single kdf hash needs, in bytes, read from its phc string
+pub fn phc_memory_bytes(algorithm: Algorithm, phc: &str) -> Option<u64> {
+    let parsed = PasswordHash::new(phc).ok()?;
+    
+    match algorithm {
+        Algorithm::Argon2 => {
+            let m_kib = parsed.params.get_decimal("m")?;
+            Some(m_kib as u64 * 1024)
+        }
+        Algorithm::Scrypt => {
+            let log_n = parsed.params.get_decimal("ln")?;
+            let r = parsed.params.get_decimal("r")?;
+            Some(128 * r as u64 * (1u64 << log_n))
+        }
+        _ => None,
+    }
+}
+
+/// raw digest size in bytes, or `None` for kdfs whose targets are phc strings
+pub fn expected_digest_len(algorithm: Algorithm) -> Option<usize> {
+    match algorithm {
+        Algorithm::BlitzHash => Some(32),
+        Algorithm::Md5 | Algorithm::Md4 => Some(16),
+        Algorithm::Sha1 => Some(20),
+        Algorithm::Sha256 => Some(32),
+        Algorithm::Scrypt
+        | Algorithm::Argon2
+        | Algorithm::Bcrypt
+        | Algorithm::Md5Crypt
+        | Algorithm::Sha256Crypt
+        | Algorithm::Sha512Crypt => None,
+    }
+}
+
+/// hash a password into a phc string for a kdf algorithm
+/// (bcrypt's modular crypt string for bcrypt, which needs a 16-byte salt)
+pub fn hash_phc(algorithm: Algorithm, password: &[u8], salt: &[u8]) -> anyhow::Result<String> {
+    if algorithm == Algorithm::Bcrypt {
+        let salt: [u8; 16] = salt.try_into()
+            .map_err(|_| anyhow::anyhow!("bcrypt salt must be 16 bytes, got {}", salt.len()))?;
+        let parts = bcrypt::hash_with_salt(password, BCRYPT_DEMO_COST, salt)
+            .map_err(|e| anyhow::anyhow!("bcrypt failed: {}", e))?;
+        return Ok(parts.format_for_version(bcrypt::Version::TwoB));
+    }
+    
+    if let Some(setting) = crypt::demo_setting(algorithm, salt) {
+        return crypt::crypt(algorithm, &setting, password)
+            .ok_or_else(|| anyhow::anyhow!("{} failed", algorithm));
+    }
+    
+    let salt = SaltString::encode_b64(salt)
+        .map_err(|e| anyhow::anyhow!("invalid salt: {}", e))?;
+    
+    match algorithm {
+        Algorithm::Scrypt => {
+            let params = scrypt::Params::new(SCRYPT_DEMO_LOG_N, 8, 1, 32)
+                .map_err(|e| anyhow::anyhow!("invalid scrypt parameters: {}", e))?;
+            let hash = scrypt::Scrypt
+                .hash_password_customized(password, None, None, params, &salt)
+                .map_err(|e| anyhow::anyhow!("scrypt failed: {}", e))?;
+            Ok(hash.to_string())
+        }
+        Algorithm::Argon2 => {
+            let hasher = Argon2Hasher::new(ARGON2_DEMO_M_COST, ARGON2_DEMO_T_COST, ARGON2_DEMO_P_COST)?;
+            let hash = hasher.argon2
+                .hash_password(password, &salt)
+                .map_err(|e| anyhow::anyhow!("argon2 failed: {}", e))?;
+            Ok(hash.to_string())
+        }
+        _ => anyhow::bail!("{} does not use phc strings", algorithm),
+    }
+}
+
+/// verify a candidate against a phc string; malformed strings never match
+pub fn verify_phc(algorithm: Algorithm, password: &[u8], phc: &str) -> bool {
+    if algorithm.is_crypt() {
+        return crypt::verify_crypt(password, phc);
+    }
+    
+    let Ok(parsed) = PasswordHash::new(phc) else {
+        return false;
+    };
+    
+    match algorithm {
+        Algorithm::Scrypt => scrypt::Scrypt.verify_password(password, &parsed).is_ok(),
+        // costs come from the phc string, not the default instance
+        Algorithm::Argon2 => argon2::Argon2::default().verify_password(password, &parsed).is_ok(),
+        _ => false,
+    }
+}
+
 /// create hasher for algorithm
 pub fn create_hasher(algorithm: Algorithm) -> Box<dyn Hasher> {
     match algorithm {
@@ -176,5 +496,85 @@ pub fn create_hasher(algorithm: Algorithm) -> Box<dyn Hasher> {
         Algorithm::Sha1 => Box::new(Sha1Hasher),
         Algorithm::Sha256 => Box::new(Sha256Hasher),
         Algorithm::Md4 => Box::new(Md4Hasher),
+        Algorithm::Scrypt => Box::new(
+            ScryptHasher::new(SCRYPT_DEMO_LOG_N, 8, 1).expect("demo parameters are valid"),
+        ),
+        Algorithm::Argon2 => Box::new(
+            Argon2Hasher::new(ARGON2_DEMO_M_COST, ARGON2_DEMO_T_COST, ARGON2_DEMO_P_COST)
+                .expect("demo parameters are valid"),
+        ),
+        Algorithm::Bcrypt => Box::new(
+            BcryptHasher::new(BCRYPT_DEMO_COST).expect("demo cost is valid"),
+        ),
+        Algorithm::Md5Crypt | Algorithm::Sha256Crypt | Algorithm::Sha512Crypt => {
+            Box::new(CryptHasher { algorithm })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bcrypt_truncates_at_72_bytes() {
+        let long = [b'x'; 80];
+        let mcf = hash_phc(Algorithm::Bcrypt, &long, &[7u8; 16]).unwrap();
+        assert!(mcf.starts_with("$2b$04$"));
+
+        // anything past 72 bytes is ignored
+        assert!(verify_phc(Algorithm::Bcrypt, &long[..72], &mcf));
+        assert!(verify_phc(Algorithm::Bcrypt, &[b'x'; 100], &mcf));
+        assert!(!verify_phc(Algorithm::Bcrypt, &long[..71], &mcf));
+        assert_eq!(effective_max_len(Algorithm::Bcrypt), Some(72));
+        assert_eq!(effective_max_len(Algorithm::Sha256), None);
+
+        // the raw hasher agrees
+        let hasher = create_hasher(Algorithm::Bcrypt);
+        assert_eq!(hasher.hash(&long[..72]), hasher.hash(&long));
+        assert_ne!(hasher.hash(&long[..71]), hasher.hash(&long));
+    }
+
+    #[test]
+    fn test_scrypt_phc_round_trip() {
+        let phc = hash_phc(Algorithm::Scrypt, b"hunter2", b"saltsalt").unwrap();
+        assert!(phc.starts_with("$scrypt$ln=10,r=8,p=1$"));
+        assert!(verify_phc(Algorithm::Scrypt, b"hunter2", &phc));
+        assert!(!verify_phc(Algorithm::Scrypt, b"hunter3", &phc));
+        assert!(!verify_phc(Algorithm::Scrypt, b"hunter2", "not a phc string"));
+    }
+
+    #[test]
+    fn test_argon2_phc_round_trip() {
+        let phc = hash_phc(Algorithm::Argon2, b"hunter2", b"saltsalt").unwrap();
+        assert!(phc.starts_with("$argon2id$v=19$m=1024,t=2,p=1$"));
+        assert!(verify_phc(Algorithm::Argon2, b"hunter2", &phc));
+        assert!(!verify_phc(Algorithm::Argon2, b"hunter3", &phc));
+    }
+
+    #[test]
+    fn test_expected_digest_len_matches_hashers() {
+        for algo in [Algorithm::BlitzHash, Algorithm::Md5, Algorithm::Sha1, Algorithm::Sha256, Algorithm::Md4] {
+            let digest = create_hasher(algo).hash(b"x");
+            assert_eq!(expected_digest_len(algo), Some(digest.len()), "{}", algo);
+        }
+        assert_eq!(expected_digest_len(Algorithm::Argon2), None);
+    }
+
+    #[test]
+    fn test_phc_memory_bytes() {
+        let scrypt = hash_phc(Algorithm::Scrypt, b"x", b"saltsalt").unwrap();
+        assert_eq!(phc_memory_bytes(Algorithm::Scrypt, &scrypt), Some(1024 * 1024));
+        let argon2 = hash_phc(Algorithm::Argon2, b"x", b"saltsalt").unwrap();
+        assert_eq!(phc_memory_bytes(Algorithm::Argon2, &argon2), Some(1024 * 1024));
+        assert_eq!(phc_memory_bytes(Algorithm::Md5, "abc"), None);
+    }
+
+    #[test]
+    fn test_scrypt_known_vector() {
+        // rfc 7914 test vector: password "password", salt "NaCl", n=1024, r=8, p=16
+        let hasher = ScryptHasher::new(10, 8, 16).unwrap();
+        let key = hasher.hash_with_salt(b"password", b"NaCl");
+        assert_eq!(hex::encode(key), "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b373162");
     }
 }
\ No newline at end of file
@@ -1,12 +1,23 @@
 // core cracking engine modules
 
 pub mod blitzhash;
+pub mod candidates;
+pub mod crypt;
+pub mod digest_table;
+pub mod encoding;
 pub mod engine;
 pub mod hasher;
 pub mod generator;
 pub mod target;
+pub mod results;
+pub mod run_report;
+pub mod targets_file;
 
-pub use engine::{Engine, CrackingResult};
+pub use candidates::{CandidateBatch, CandidateSource};
+pub use digest_table::DigestTable;
+pub use encoding::{CaseFold, PasswordEncoding};
+pub use engine::{Engine, CandidateTransform, CrackingResult, NearMiss, Statistics, TerminationReason};
 pub use hasher::{Algorithm, Hasher};
-pub use generator::{Generator, DictionaryGenerator, MaskGenerator, BruteForceGenerator};
-pub use target::{Target, TargetMatch};
\ No newline at end of file
+pub use generator::{Generator, DictionaryGenerator, MultiDictionaryGenerator, MaskGenerator, CharSet, BruteForceGenerator, RangeGenerator, SampleGenerator, HintGenerator, RulesGenerator, DeadlineGenerator, ProcessGenerator, MemoryGenerator};
+pub use target::{Target, TargetError, TargetMatch};
+pub use targets_file::load_targets;
\ No newline at end of file
@@ -0,0 +1,455 @@
+// cracked-credential files: the `run --results` json and hashcat-style
+// `hash:password` potfiles, plus merging them after sharded runs. `run
+// --sqlite` keeps a database of them instead, built up across sessions
+
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::core::{Algorithm, Target, TargetMatch};
+
+/// write `contents` to a temp file beside `path`, then rename it over
+/// `path`, so a run killed mid-write (sigterm's `TERM_GRACE` running out)
+/// leaves the old file or the new one, never half of one
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    // same directory, so the rename can't cross filesystems
+    let temp = path.with_file_name(format!(".{}.tmp", name));
+    fs::write(&temp, contents)?;
+    fs::rename(&temp, path).inspect_err(|_| {
+        fs::remove_file(&temp).ok();
+    })
+}
+
+/// one cracked hash. potfile lines only carry `hash` and `password`,
+/// so everything else is optional
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrackedEntry {
+    #[serde(rename = "hash_hex")]
+    pub hash: String,
+    pub password: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, rename = "hash_algo", skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<Algorithm>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub salt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_seconds: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guesses_tried: Option<u64>,
+}
+
+/// pair each match with its target's hash
+pub fn entries_from_matches(matches: &[TargetMatch], targets: &[Target]) -> Vec<CrackedEntry> {
+    let by_id: HashMap<&str, &Target> = targets.iter().map(|t| (t.id.as_str(), t)).collect();
+
+    matches.iter()
+        .filter_map(|m| {
+            let target = by_id.get(m.target_id.as_str())?;
+            Some(CrackedEntry {
+                hash: target.hash.clone(),
+                password: m.password_string(),
+                target_id: Some(m.target_id.clone()),
+                username: Some(m.username.clone()),
+                algorithm: Some(m.algorithm),
+                salt: Some(target.salt.clone()).filter(|salt| !salt.is_empty()),
+                time_seconds: Some(m.time_seconds),
+                guesses_tried: Some(m.guesses_tried),
+            })
+        })
+        .collect()
+}
+
+/// load a results json (array) or a potfile, detected from the contents
+pub fn load_entries(path: &Path) -> Result<Vec<CrackedEntry>> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+
+    if text.trim_start().starts_with('[') {
+        Ok(serde_json::from_str(&text)?)
+    } else {
+        parse_potfile(&text).with_context(|| format!("in potfile {}", path.display()))
+    }
+}
+
+/// `hash:password` per line. hashes never contain ':' so the password is
+/// everything after the first one; `$HEX[..]` passwords are decoded
+pub fn parse_potfile(text: &str) -> Result<Vec<CrackedEntry>> {
+    let mut entries = Vec::new();
+
+    for (n, line) in text.lines().enumerate() {
+        if line.is_empty() {
+            continue;
+        }
+        let Some((hash, password)) = line.split_once(':') else {
+            bail!("line {}: expected hash:password", n + 1);
+        };
+
+        let password = match password.strip_prefix("$HEX[").and_then(|p| p.strip_suffix(']')) {
+            Some(encoded) => {
+                let bytes = hex::decode(encoded)
+                    .with_context(|| format!("line {}: bad $HEX[] password", n + 1))?;
+                String::from_utf8_lossy(&bytes).to_string()
+            }
+            None => password.to_string(),
+        };
+
+        entries.push(CrackedEntry {
+            hash: hash.to_string(),
+            password,
+            target_id: None,
+            username: None,
+            algorithm: None,
+            salt: None,
+            time_seconds: None,
+            guesses_tried: None,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// potfile lines, hex-encoding passwords that would break the line format
+pub fn to_potfile(entries: &[CrackedEntry]) -> String {
+    entries.iter()
+        .map(|e| {
+            let unsafe_chars = e.password.contains(['\n', '\r']) || e.password.starts_with("$HEX[");
+            if unsafe_chars {
+                format!("{}:$HEX[{}]\n", e.hash, hex::encode(e.password.as_bytes()))
+            } else {
+                format!("{}:{}\n", e.hash, e.password)
+            }
+        })
+        .collect()
+}
+
+/// deduplicate by hash, keeping the fastest find. entries without a time
+/// (potfile lines) only win when nothing else cracked that hash.
+/// output is sorted by hash so merges are reproducible
+pub fn merge_entries(sources: Vec<Vec<CrackedEntry>>) -> Vec<CrackedEntry> {
+    let mut by_hash: HashMap<String, CrackedEntry> = HashMap::new();
+
+    for entry in sources.into_iter().flatten() {
+        match by_hash.get(&entry.hash) {
+            Some(existing) if !is_faster(&entry, existing) => {}
+            _ => {
+                by_hash.insert(entry.hash.clone(), entry);
+            }
+        }
+    }
+
+    let mut merged: Vec<CrackedEntry> = by_hash.into_values().collect();
+    merged.sort_by(|a, b| a.hash.cmp(&b.hash));
+    merged
+}
+
+/// `run --potfile`: a match for each target whose hash was already
+/// cracked, with the same algorithm and salt when the entry says which.
+/// hex digests match in either case, and each password is re-hashed
+/// (encoded and with `pepper`, as the run would) before it's trusted - a
+/// shared unsalted digest or a secondary-hash collision doesn't count
+pub fn known_matches(entries: &[CrackedEntry], targets: &[Target], pepper: &[u8]) -> Vec<TargetMatch> {
+    let mut by_hash: HashMap<String, Vec<&CrackedEntry>> = HashMap::new();
+    for entry in entries {
+        by_hash.entry(entry.hash.to_ascii_lowercase()).or_default().push(entry);
+    }
+
+    targets.iter()
+        .filter_map(|target| {
+            let candidates = by_hash.get(&target.hash.to_ascii_lowercase())?;
+            candidates.iter()
+                .filter(|e| e.algorithm.is_none_or(|algorithm| algorithm == target.algorithm))
+                .filter(|e| e.salt.as_deref().is_none_or(|salt| salt == target.salt))
+                .find_map(|entry| {
+                    let mut encoded = target.encoding().encode(&entry.password)?;
+                    encoded.extend_from_slice(pepper);
+                    let verified = target.verify(&encoded);
+                    (target.hashes_to(&encoded) && verified != Some(false)).then(|| TargetMatch {
+                        target_id: target.id.clone(),
+                        username: target.username.clone(),
+                        password: entry.password.clone().into_bytes(),
+                        algorithm: target.algorithm,
+                        guesses_tried: 0,
+                        time_seconds: 0.0,
+                        verified,
+                        expected_password: target.expected_password.clone(),
+                    })
+                })
+        })
+        .collect()
+}
+
+fn is_faster(candidate: &CrackedEntry, existing: &CrackedEntry) -> bool {
+    match (candidate.time_seconds, existing.time_seconds) {
+        (Some(new), Some(old)) => new < old,
+        (Some(_), None) => true,
+        _ => false,
+    }
+}
+
+/// a password for a text column: as-is when it's utf-8, otherwise (or
+/// when it could be read as one) in the potfile's `$HEX[..]` form, so the
+/// exact bytes survive
+fn text_or_hex(password: &[u8]) -> String {
+    match std::str::from_utf8(password) {
+        Ok(text) if !text.starts_with("$HEX[") => text.to_string(),
+        _ => format!("$HEX[{}]", hex::encode(password)),
+    }
+}
+
+/// `run --sqlite`: each match goes into a `cracked` table as it's found.
+/// a target id is only ever inserted once, so re-cracking it in a later
+/// session keeps the original row
+pub struct SqliteSink {
+    conn: Connection,
+}
+
+impl SqliteSink {
+    /// open or create the database and its table
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)
+            .with_context(|| format!("failed to open database {}", path.display()))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS cracked (
+                target_id  TEXT PRIMARY KEY,
+                username   TEXT NOT NULL,
+                password   TEXT NOT NULL,
+                algorithm  TEXT NOT NULL,
+                cracked_at TEXT NOT NULL
+            )",
+        )?;
+        Ok(Self { conn })
+    }
+
+    /// insert `matches` in one transaction, returning how many were new
+    pub fn record(&mut self, matches: &[TargetMatch]) -> Result<usize> {
+        if matches.is_empty() {
+            return Ok(0);
+        }
+
+        let cracked_at = chrono::Utc::now().to_rfc3339();
+        let tx = self.conn.transaction()?;
+        let mut inserted = 0;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT OR IGNORE INTO cracked (target_id, username, password, algorithm, cracked_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for m in matches {
+                inserted += insert.execute(params![
+                    m.target_id,
+                    m.username,
+                    text_or_hex(&m.password),
+                    m.algorithm.to_string(),
+                    cracked_at,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(inserted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(hash: &str, password: &str, time: Option<f64>) -> CrackedEntry {
+        CrackedEntry {
+            hash: hash.to_string(),
+            password: password.to_string(),
+            target_id: time.map(|_| format!("id_{}", hash)),
+            username: None,
+            algorithm: time.map(|_| Algorithm::Md5),
+            salt: None,
+            time_seconds: time,
+            guesses_tried: None,
+        }
+    }
+
+    #[test]
+    fn test_write_atomic_replaces_whole_file() {
+        let path = std::env::temp_dir().join(format!("blitzforge_atomic_{}.json", std::process::id()));
+        fs::write(&path, "old contents, longer than the new").unwrap();
+
+        write_atomic(&path, "new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        // no temp file left beside it
+        let temp = path.with_file_name(format!(".{}.tmp", path.file_name().unwrap().to_string_lossy()));
+        assert!(!temp.exists());
+        fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_merge_drops_duplicates() {
+        let shard1 = vec![entry("aa", "one", Some(1.0)), entry("bb", "two", Some(2.0))];
+        let shard2 = vec![entry("aa", "one", Some(1.0))];
+
+        let merged = merge_entries(vec![shard1, shard2]);
+        let hashes: Vec<&str> = merged.iter().map(|e| e.hash.as_str()).collect();
+        assert_eq!(hashes, vec!["aa", "bb"]);
+    }
+
+    #[test]
+    fn test_merge_keeps_fastest_find() {
+        let slow = vec![entry("aa", "slow", Some(9.5))];
+        let fast = vec![entry("aa", "fast", Some(0.5))];
+        let potfile = vec![entry("aa", "pot", None)];
+
+        let merged = merge_entries(vec![slow, potfile.clone(), fast]);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].password, "fast");
+        assert_eq!(merged[0].time_seconds, Some(0.5));
+
+        // a timed find beats a potfile line regardless of order
+        let merged = merge_entries(vec![potfile.clone(), vec![entry("aa", "timed", Some(3.0))]]);
+        assert_eq!(merged[0].password, "timed");
+
+        // but a potfile-only hash is still kept
+        let merged = merge_entries(vec![potfile]);
+        assert_eq!(merged[0].password, "pot");
+    }
+
+    #[test]
+    fn test_potfile_round_trip() {
+        let entries = vec![
+            entry("aa", "pass:with:colons", None),
+            entry("bb", "line\nbreak", None),
+        ];
+
+        let text = to_potfile(&entries);
+        assert!(text.starts_with("aa:pass:with:colons\n"));
+        assert!(text.contains("bb:$HEX["));
+        assert_eq!(parse_potfile(&text).unwrap(), entries);
+    }
+
+    #[test]
+    fn test_potfile_rejects_malformed_line() {
+        let err = parse_potfile("aa:ok\nnocolon\n").unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{}", err);
+    }
+
+    #[test]
+    fn test_known_matches_pair_entries_with_targets() {
+        let digest = |algorithm, password: &[u8], salt: &[u8]| {
+            let hasher = crate::core::hasher::create_hasher(algorithm);
+            match salt {
+                [] => hex::encode(hasher.hash(password)),
+                salt => hex::encode(hasher.hash_with_salt(password, salt)),
+            }
+        };
+        let target = |id: &str, algorithm, password: &[u8], salt: &str| Target {
+            id: id.to_string(),
+            username: format!("user_{}", id),
+            algorithm,
+            hash: digest(algorithm, password, salt.as_bytes()),
+            salt: salt.to_string(),
+            ..Default::default()
+        };
+        let targets = vec![
+            target("t1", Algorithm::Md5, b"one", ""),
+            target("t2", Algorithm::Sha1, b"two", ""),
+            target("t3", Algorithm::Md5, b"three", "s1"),
+            target("t4", Algorithm::Md5, b"four", ""),
+            target("t5", Algorithm::Md5, b"five", "s2"),
+        ];
+        let mut salted = entry(&targets[4].hash, "five", Some(1.0));
+        salted.salt = Some("other".to_string());
+        let entries = vec![
+            // a results entry names its algorithm; hex case doesn't matter
+            entry(&targets[0].hash.to_uppercase(), "one", Some(1.0)),
+            // a potfile line matches any algorithm and salt
+            entry(&targets[1].hash, "two", None),
+            entry(&targets[2].hash, "three", None),
+            // re-hashing catches a wrong password
+            entry(&targets[3].hash, "nope", None),
+            // and a results entry only matches its own salt
+            salted,
+        ];
+
+        let known = known_matches(&entries, &targets, b"");
+        let found: Vec<(&str, &[u8])> = known.iter()
+            .map(|m| (m.target_id.as_str(), m.password.as_slice()))
+            .collect();
+        assert_eq!(found, vec![("t1", &b"one"[..]), ("t2", &b"two"[..]), ("t3", &b"three"[..])]);
+        assert!(known.iter().all(|m| m.guesses_tried == 0));
+
+        // peppered targets are re-hashed with the run's pepper
+        let peppered = vec![target("p1", Algorithm::Md5, b"onePEPPER", "")];
+        let entries = vec![entry(&peppered[0].hash, "one", None)];
+        assert!(known_matches(&entries, &peppered, b"").is_empty());
+        assert_eq!(known_matches(&entries, &peppered, b"PEPPER").len(), 1);
+    }
+
+    #[test]
+    fn test_sqlite_sink_inserts_once_per_target() {
+        let path = std::env::temp_dir().join(format!("blitzforge_cracked_{}.db", std::process::id()));
+        fs::remove_file(&path).ok();
+
+        let found = TargetMatch {
+            target_id: "t1".to_string(),
+            username: "alice".to_string(),
+            password: b"hunter2".to_vec(),
+            algorithm: Algorithm::Sha256,
+            guesses_tried: 10,
+            time_seconds: 0.5,
+            verified: None,
+            expected_password: None,
+        };
+        let mut sink = SqliteSink::open(&path).unwrap();
+        assert_eq!(sink.record(std::slice::from_ref(&found)).unwrap(), 1);
+        drop(sink);
+
+        // a later session cracking it again doesn't add a row
+        let mut sink = SqliteSink::open(&path).unwrap();
+        let mut again = found.clone();
+        again.password = b"collision".to_vec();
+        assert_eq!(sink.record(&[again]).unwrap(), 0);
+
+        let row: (String, String, String, usize) = sink.conn
+            .query_row(
+                "SELECT username, password, algorithm, (SELECT COUNT(*) FROM cracked) FROM cracked WHERE target_id = 't1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        drop(sink);
+        fs::remove_file(&path).ok();
+
+        assert_eq!(row, ("alice".to_string(), "hunter2".to_string(), "sha256".to_string(), 1));
+    }
+
+    #[test]
+    fn test_sqlite_sink_keeps_non_utf8_passwords() {
+        let path = std::env::temp_dir().join(format!("blitzforge_bytes_{}.db", std::process::id()));
+        fs::remove_file(&path).ok();
+
+        let found = |id: &str, password: &[u8]| TargetMatch {
+            target_id: id.to_string(),
+            username: String::new(),
+            password: password.to_vec(),
+            algorithm: Algorithm::Md5,
+            guesses_tried: 0,
+            time_seconds: 0.0,
+            verified: None,
+            expected_password: None,
+        };
+        let mut sink = SqliteSink::open(&path).unwrap();
+        sink.record(&[found("latin1", b"caf\xe9"), found("literal", b"$HEX[41]")]).unwrap();
+
+        let stored = |id: &str| -> String {
+            sink.conn.query_row("SELECT password FROM cracked WHERE target_id = ?1", [id], |row| row.get(0)).unwrap()
+        };
+        assert_eq!(stored("latin1"), "$HEX[636166e9]");
+        assert_eq!(stored("literal"), "$HEX[244845585b34315d]");
+        drop(sink);
+        fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,328 @@
+// `run --report-out`: one self-contained json record of a run - its
+// configuration, every target's outcome, statistics and a sha-256 of the
+// targets file so the report can be traced back to its input
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::core::results::write_atomic;
+use crate::core::{Algorithm, CrackingResult, Target, TargetError, TargetMatch};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunReport {
+    pub blitzforge_version: String,
+    /// rfc 3339 time the report was written
+    pub generated_at: String,
+    pub targets_file: String,
+    /// hex sha-256 of the targets file, taken when the run loaded it
+    pub targets_sha256: String,
+    pub config: RunConfig,
+    pub statistics: RunStatistics,
+    pub targets: Vec<TargetOutcome>,
+    /// targets set aside by `--skip-invalid` rather than attacked
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errored: Vec<TargetError>,
+}
+
+/// the options the run was started with, after presets were applied.
+/// `run --save-config` writes one on its own and `run --config` replays it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunConfig {
+    /// stage names, comma-separated for a pipeline
+    pub strategy: String,
+    /// algorithms of the targets attacked - informational, since the
+    /// targets file decides them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub algorithms: Vec<Algorithm>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wordlists: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_trim: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mask: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub charset: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    pub min_len: usize,
+    pub max_len: usize,
+    pub skip: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generator_cmd: Option<String>,
+    pub workers: usize,
+    pub batch_size: usize,
+    /// `--batch-size` entries for single algorithms
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub algorithm_batch_sizes: Vec<(Algorithm, usize)>,
+    pub repeat: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunStatistics {
+    pub guesses_tried: u64,
+    pub hashes_per_second: f64,
+    pub targets_found: usize,
+    pub targets_total: usize,
+    pub total_time_seconds: f64,
+    pub threads_pinned: bool,
+}
+
+/// one target and what the run learned about it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TargetOutcome {
+    pub id: String,
+    pub username: String,
+    #[serde(rename = "hash_algo")]
+    pub algorithm: Algorithm,
+    #[serde(rename = "hash_hex")]
+    pub hash: String,
+    pub found: bool,
+    /// retired uncracked by `--target-timeout`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub given_up: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_seconds: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guesses_tried: Option<u64>,
+    /// secondary-check result, for targets that have one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified: Option<bool>,
+}
+
+impl RunReport {
+    /// report `result`, listing targets in file order, found or not.
+    /// `targets_sha256` is of the bytes the targets were parsed from, so
+    /// edits made to the file during the run can't slip into the report
+    pub fn new(
+        targets_path: &Path,
+        targets_sha256: String,
+        targets: &[Target],
+        config: RunConfig,
+        result: &CrackingResult,
+    ) -> Self {
+        let by_id: HashMap<&str, &TargetMatch> = result.matches.iter()
+            .map(|m| (m.target_id.as_str(), m))
+            .collect();
+        let given_up: HashSet<&str> = result.given_up.iter().map(String::as_str).collect();
+
+        let outcomes = targets.iter()
+            .map(|target| {
+                let found = by_id.get(target.id.as_str());
+                TargetOutcome {
+                    id: target.id.clone(),
+                    username: target.username.clone(),
+                    algorithm: target.algorithm,
+                    hash: target.hash.clone(),
+                    found: found.is_some(),
+                    given_up: given_up.contains(target.id.as_str()),
+                    password: found.map(|m| m.password_string()),
+                    time_seconds: found.map(|m| m.time_seconds),
+                    guesses_tried: found.map(|m| m.guesses_tried),
+                    verified: found.and_then(|m| m.verified),
+                }
+            })
+            .collect();
+
+        Self {
+            blitzforge_version: env!("CARGO_PKG_VERSION").to_string(),
+            generated_at: chrono::Utc::now().to_rfc3339(),
+            targets_file: targets_path.display().to_string(),
+            targets_sha256,
+            config,
+            statistics: RunStatistics {
+                guesses_tried: result.statistics.guesses_tried,
+                hashes_per_second: result.statistics.hashes_per_second,
+                targets_found: result.statistics.targets_found,
+                targets_total: result.statistics.targets_total,
+                total_time_seconds: result.total_time,
+                threads_pinned: result.threads_pinned,
+            },
+            targets: outcomes,
+            errored: Vec::new(),
+        }
+    }
+    
+    /// list the targets that errored instead of being attacked
+    pub fn with_errored(mut self, errored: Vec<TargetError>) -> Self {
+        self.errored = errored;
+        self
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        write_atomic(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write report {}", path.display()))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read report {}", path.display()))?;
+        Ok(serde_json::from_str(&text)?)
+    }
+}
+
+impl RunConfig {
+    pub fn write(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write config {}", path.display()))
+    }
+
+    /// read a config saved by `--save-config`, or the one inside a run report
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read config {}", path.display()))?;
+        match serde_json::from_str(&text) {
+            Ok(config) => Ok(config),
+            Err(e) => serde_json::from_str::<RunReport>(&text)
+                .map(|report| report.config)
+                .map_err(|_| anyhow::anyhow!("invalid config {}: {}", path.display(), e)),
+        }
+    }
+}
+
+/// hex sha-256 of some bytes - a targets file as loaded, or targets given
+/// without a file
+pub fn sha256_bytes(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Statistics;
+
+    fn target(id: &str) -> Target {
+        Target {
+            id: id.to_string(),
+            username: format!("user_{}", id),
+            algorithm: Algorithm::Md5,
+            hash: "00".repeat(16),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_report_round_trip() {
+        let dir = std::env::temp_dir();
+        let targets_path = dir.join(format!("blitzforge_report_targets_{}.json", std::process::id()));
+        let report_path = dir.join(format!("blitzforge_report_{}.json", std::process::id()));
+
+        let targets = vec![target("a"), target("b")];
+        fs::write(&targets_path, serde_json::to_string(&targets).unwrap()).unwrap();
+
+        let result = CrackingResult {
+            matches: vec![TargetMatch {
+                target_id: "b".to_string(),
+                username: "user_b".to_string(),
+                password: b"hunter2".to_vec(),
+                algorithm: Algorithm::Md5,
+                guesses_tried: 42,
+                time_seconds: 0.5,
+                verified: None,
+                expected_password: None,
+            }],
+            statistics: Statistics::new(2),
+            total_time: 1.25,
+            final_batch_size: 16,
+            threads_pinned: false,
+            termination: crate::core::TerminationReason::Exhausted,
+            adaptive_workers: None,
+            given_up: Vec::new(),
+            near_misses: Vec::new(),
+        };
+        let config = RunConfig {
+            strategy: "mask".to_string(),
+            algorithms: vec![Algorithm::Md5],
+            wordlists: Vec::new(),
+            no_trim: false,
+            mask: Some("?l?l?l?l?l?l?d".to_string()),
+            charset: None,
+            template: None,
+            min_len: 1,
+            max_len: 8,
+            skip: 0,
+            limit: None,
+            generator_cmd: None,
+            workers: 2,
+            batch_size: 16,
+            algorithm_batch_sizes: Vec::new(),
+            repeat: 1,
+        };
+
+        let sha = sha256_bytes(&fs::read(&targets_path).unwrap());
+        let report = RunReport::new(&targets_path, sha, &targets, config, &result);
+        report.write(&report_path).unwrap();
+        let loaded = RunReport::load(&report_path).unwrap();
+
+        fs::remove_file(&targets_path).ok();
+        fs::remove_file(&report_path).ok();
+
+        assert_eq!(loaded, report);
+        assert_eq!(loaded.targets_sha256, hex::encode(Sha256::digest(serde_json::to_vec(&targets).unwrap())));
+        assert_eq!(loaded.targets.len(), 2);
+        assert!(!loaded.targets[0].found);
+        assert_eq!(loaded.targets[0].password, None);
+        assert_eq!(loaded.targets[1].password.as_deref(), Some("hunter2"));
+        assert_eq!(loaded.statistics.total_time_seconds, 1.25);
+    }
+
+    #[test]
+    fn test_config_round_trip() {
+        let dir = std::env::temp_dir();
+        let config_path = dir.join(format!("blitzforge_config_{}.json", std::process::id()));
+        let report_path = dir.join(format!("blitzforge_config_report_{}.json", std::process::id()));
+
+        let config = RunConfig {
+            strategy: "dictionary,brute".to_string(),
+            algorithms: vec![Algorithm::Sha256, Algorithm::Md5],
+            wordlists: vec!["wordlists/common-10k.txt".to_string()],
+            no_trim: true,
+            mask: None,
+            charset: Some("abc123".to_string()),
+            template: None,
+            min_len: 2,
+            max_len: 5,
+            skip: 100,
+            limit: Some(5000),
+            generator_cmd: None,
+            workers: 3,
+            batch_size: 1024,
+            algorithm_batch_sizes: vec![(Algorithm::Md5, 65536)],
+            repeat: 2,
+        };
+        config.write(&config_path).unwrap();
+        let loaded = RunConfig::load(&config_path).unwrap();
+        assert_eq!(loaded, config);
+
+        // a report's embedded config loads the same way
+        let targets_path = Path::new("targets.json");
+        let result = CrackingResult {
+            matches: Vec::new(),
+            statistics: Statistics::new(1),
+            total_time: 0.0,
+            final_batch_size: 1024,
+            threads_pinned: false,
+            termination: crate::core::TerminationReason::Exhausted,
+            adaptive_workers: None,
+            given_up: Vec::new(),
+            near_misses: Vec::new(),
+        };
+        RunReport::new(targets_path, String::new(), &[target("a")], config.clone(), &result)
+            .write(&report_path)
+            .unwrap();
+        assert_eq!(RunConfig::load(&report_path).unwrap(), config);
+
+        fs::write(&config_path, "{\"strategy\": 3}").unwrap();
+        assert!(RunConfig::load(&config_path).is_err());
+
+        fs::remove_file(&config_path).ok();
+        fs::remove_file(&report_path).ok();
+    }
+}
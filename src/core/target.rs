@@ -1,7 +1,12 @@
 use serde::{Deserialize, Serialize};
-use crate::core::Algorithm;
+use crate::core::{Algorithm, PasswordEncoding};
+use crate::core::crypt::parse_crypt;
+use crate::core::hasher::{create_hasher, expected_digest_len, verify_phc, Hasher};
+use scrypt::password_hash::PasswordHash;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// one hash to crack. `Default` fills every optional field, for building
+/// targets as `Target { id, algorithm, hash, ..Default::default() }`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Target {
     pub id: String,
     pub username: String,
@@ -11,23 +16,252 @@ pub struct Target {
     pub hash: String,
     #[serde(default)]
     pub salt: String,
+    /// whether the scheme hashes with `salt` even when it's empty; `false`
+    /// means plain unsalted (default: salted exactly when `salt` isn't empty)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub salted: Option<bool>,
+    /// hash `password + salt` instead of `salt + password`, as ldap's
+    /// `{SSHA}` does (default: salt first)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub salt_after: Option<bool>,
+    /// optional secondary algorithm used only to confirm matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify_algo: Option<Algorithm>,
+    /// hex digest of the same password under `verify_algo`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify_hash: Option<String>,
+    /// higher values are checked first within their hash group
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u32>,
+    /// known answer for teaching/demo datasets; never used while cracking,
+    /// only compared against what was found afterwards
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_password: Option<String>,
+    /// bytes the password was hashed as; candidates are re-encoded to
+    /// match (default utf-8)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_encoding: Option<PasswordEncoding>,
+    /// leading bytes of the digest compared, for dumps that keep only part
+    /// of it; `hash` then holds just those bytes (default: the whole digest)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash_bytes: Option<usize>,
 }
 
 impl Target {
-    /// check if a computed hash matches this target
+    /// check if a computed hash matches this target, or its first
+    /// `hash_bytes` bytes do
     pub fn matches(&self, computed_hash: &[u8]) -> bool {
         let target_bytes = hex::decode(&self.hash).unwrap_or_default();
-        computed_hash == target_bytes.as_slice()
+        let compared = match self.hash_bytes {
+            Some(n) => &computed_hash[..n.min(computed_hash.len())],
+            None => computed_hash,
+        };
+        compared == target_bytes.as_slice()
     }
     
-    /// get salt as bytes
+    /// check the stored hash is well-formed for the algorithm, catching e.g.
+    /// an md5-length digest declared as sha256
+    pub fn validate(&self) -> Result<(), String> {
+        if self.salted == Some(false) && !self.salt.is_empty() {
+            return Err(format!("salted is false but the salt is '{}'", self.salt));
+        }
+        
+        if let Some(encoded) = hex_salt(&self.salt) {
+            hex::decode(encoded).map_err(|e| format!("$HEX[] salt is not valid hex: {}", e))?;
+        }
+        
+        if self.salt_after.is_some() && self.algorithm.is_kdf() {
+            return Err(format!("salt_after only applies to raw digests, not {} hashes", self.algorithm));
+        }
+        
+        // a malformed secondary digest would flag every match as a collision
+        if let (Some(algo), Some(verify_hash)) = (self.verify_algo, &self.verify_hash) {
+            self.validate_verify_hash(algo, verify_hash)?;
+        }
+        
+        if self.hash_bytes.is_some() && self.algorithm.is_kdf() {
+            return Err(format!("hash_bytes only applies to raw digests, not {} hashes", self.algorithm));
+        }
+        
+        if self.algorithm.is_crypt() {
+            let parsed = parse_crypt(&self.hash)
+                .map_err(|e| format!("invalid {} hash: {}", self.algorithm, e))?;
+            if parsed.algorithm != self.algorithm {
+                return Err(format!("{} target holds a {} crypt string", self.algorithm, parsed.algorithm));
+            }
+            // the salt is read from the hash; a redundant salt field must agree
+            if !self.salt.is_empty() && self.salt != parsed.salt {
+                return Err(format!("salt '{}' differs from the salt embedded in the hash", self.salt));
+            }
+            return Ok(());
+        }
+        
+        if self.algorithm.is_kdf() {
+            return PasswordHash::new(&self.hash)
+                .map(|_| ())
+                .map_err(|e| format!("invalid {} phc string: {}", self.algorithm, e));
+        }
+        
+        let bytes = hex::decode(&self.hash)
+            .map_err(|e| format!("hash is not valid hex: {}", e))?;
+        
+        if let Some(n) = self.hash_bytes {
+            let full = expected_digest_len(self.algorithm).unwrap_or(usize::MAX);
+            if n == 0 || n > full {
+                return Err(format!("hash_bytes {} is outside 1..={} for {}", n, full, self.algorithm));
+            }
+            if bytes.len() != n {
+                return Err(format!("hash_bytes is {} but the hash holds {} bytes", n, bytes.len()));
+            }
+            return Ok(());
+        }
+        
+        match expected_digest_len(self.algorithm) {
+            Some(expected) if bytes.len() != expected => Err(format!(
+                "{} digest should be {} bytes ({} hex chars), got {} bytes",
+                self.algorithm,
+                expected,
+                expected * 2,
+                bytes.len(),
+            )),
+            _ => Ok(()),
+        }
+    }
+    
+    /// `verify_hash` is well-formed for `verify_algo`, as `hash` is for
+    /// `algorithm`
+    fn validate_verify_hash(&self, algo: Algorithm, verify_hash: &str) -> Result<(), String> {
+        if algo.is_crypt() {
+            return parse_crypt(verify_hash)
+                .map(|_| ())
+                .map_err(|e| format!("invalid {} verify_hash: {}", algo, e));
+        }
+        if algo.is_kdf() {
+            return PasswordHash::new(verify_hash)
+                .map(|_| ())
+                .map_err(|e| format!("invalid {} verify_hash phc string: {}", algo, e));
+        }
+        
+        let bytes = hex::decode(verify_hash)
+            .map_err(|e| format!("verify_hash is not valid hex: {}", e))?;
+        match expected_digest_len(algo) {
+            Some(expected) if bytes.len() != expected => Err(format!(
+                "{} verify_hash should be {} bytes ({} hex chars), got {} bytes",
+                algo,
+                expected,
+                expected * 2,
+                bytes.len(),
+            )),
+            _ => Ok(()),
+        }
+    }
+    
+    /// verify-style match for kdf targets, whose `hash` is a phc string
+    /// embedding the salt and cost parameters
+    pub fn verify_kdf(&self, password: &[u8]) -> bool {
+        verify_phc(self.algorithm, password, &self.hash)
+    }
+    
+    /// whether `encoded` (a candidate's bytes as they're hashed, after any
+    /// pepper) hashes to this target - the engine's check, for passwords
+    /// cracked elsewhere
+    pub fn hashes_to(&self, encoded: &[u8]) -> bool {
+        if self.algorithm.is_kdf() {
+            return self.verify_kdf(encoded);
+        }
+        self.matches(&self.digest(create_hasher(self.algorithm).as_ref(), encoded))
+    }
+    
+    /// confirm a matched password against the secondary digest, if provided.
+    /// returns `None` when the target has no secondary check and
+    /// `Some(false)` when the primary match was a collision
+    pub fn verify(&self, password: &[u8]) -> Option<bool> {
+        let algo = self.verify_algo?;
+        let verify_hash = self.verify_hash.as_ref()?;
+        
+        if algo.is_kdf() {
+            return Some(verify_phc(algo, password, verify_hash));
+        }
+        
+        let expected = hex::decode(verify_hash).unwrap_or_default();
+        
+        let computed = self.digest(create_hasher(algo).as_ref(), password);
+        Some(computed == expected)
+    }
+    
+    /// `hasher`'s digest of `encoded` with this target's salt, where it goes
+    fn digest(&self, hasher: &dyn Hasher, encoded: &[u8]) -> Vec<u8> {
+        match (self.is_salted(), self.is_salt_after()) {
+            (false, _) => hasher.hash(encoded),
+            (true, false) => hasher.hash_with_salt(encoded, &self.salt_bytes()),
+            (true, true) => hasher.hash(&[encoded, &self.salt_bytes()].concat()),
+        }
+    }
+    
+    /// the salt field, or for crypt-format hashes the salt embedded in the hash
+    pub fn effective_salt(&self) -> &str {
+        if self.salt.is_empty() && self.algorithm.is_crypt() {
+            if let Ok(parsed) = parse_crypt(&self.hash) {
+                return parsed.salt;
+            }
+        }
+        &self.salt
+    }
+    
+    pub fn encoding(&self) -> PasswordEncoding {
+        self.password_encoding.unwrap_or_default()
+    }
+    
+    /// whether candidates are hashed with the salt - an empty salt counts
+    /// as none unless `salted` says otherwise
+    pub fn is_salted(&self) -> bool {
+        self.salted.unwrap_or(!self.salt.is_empty())
+    }
+    
+    /// whether the salt goes after the password (`salt_after`)
+    pub fn is_salt_after(&self) -> bool {
+        self.salt_after.unwrap_or(false)
+    }
+    
+    /// get salt as bytes. a binary salt is written `$HEX[..]`, as potfiles
+    /// write passwords
     pub fn salt_bytes(&self) -> Vec<u8> {
         if self.salt.is_empty() {
             vec![]
+        } else if let Some(bytes) = hex_salt(&self.salt).and_then(|encoded| hex::decode(encoded).ok()) {
+            bytes
         } else {
             self.salt.as_bytes().to_vec()
         }
     }
+    
+    /// `validate`, handing back the target when it passes
+    pub fn checked(self) -> Result<Target, TargetError> {
+        match self.validate() {
+            Ok(()) => Ok(self),
+            Err(reason) => Err(TargetError { id: self.id, reason }),
+        }
+    }
+}
+
+/// the hex inside a `$HEX[..]` salt
+fn hex_salt(salt: &str) -> Option<&str> {
+    salt.strip_prefix("$HEX[")?.strip_suffix(']')
+}
+
+/// a target set aside instead of attacked: its entry didn't parse (an
+/// unknown algorithm, a missing field) or its hash doesn't fit its algorithm
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TargetError {
+    /// the target's id, or `#n` for the nth entry when even that is unreadable
+    pub id: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for TargetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {}", self.id, self.reason)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -38,10 +272,208 @@ pub struct TargetMatch {
     pub algorithm: Algorithm,
     pub guesses_tried: u64,
     pub time_seconds: f64,
+    /// result of the secondary check (`None` if the target has none)
+    pub verified: Option<bool>,
+    /// the target's `expected_password`, if the dataset recorded one
+    pub expected_password: Option<String>,
 }
 
 impl TargetMatch {
     pub fn password_string(&self) -> String {
         String::from_utf8_lossy(&self.password).to_string()
     }
-}
\ No newline at end of file
+    
+    /// the password as shown on screen: `password_string`, or with
+    /// `masked` its `mask_password` shape. the stored bytes are untouched
+    pub fn display_password(&self, masked: bool) -> String {
+        match masked {
+            true => mask_password(&self.password_string()),
+            false => self.password_string(),
+        }
+    }
+    
+    /// compare the found password with the dataset's answer. `Some(false)`
+    /// means a hash collision or a bug in the tool. `None` if there's no answer
+    pub fn matches_expected(&self) -> Option<bool> {
+        self.expected_password.as_ref()
+            .map(|expected| expected.as_bytes() == self.password.as_slice())
+    }
+}
+/// `password` → `p******d`: first and last character and the length, for
+/// screen-shared demos. two characters or fewer are starred completely
+pub fn mask_password(password: &str) -> String {
+    let chars: Vec<char> = password.chars().collect();
+    match chars.as_slice() {
+        [first, middle @ .., last] if !middle.is_empty() => {
+            format!("{}{}{}", first, "*".repeat(middle.len()), last)
+        }
+        _ => "*".repeat(chars.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn md5_target(password: &[u8]) -> Target {
+        Target {
+            id: "t1".to_string(),
+            username: "alice".to_string(),
+            algorithm: Algorithm::Md5,
+            hash: hex::encode(create_hasher(Algorithm::Md5).hash(password)),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_digest_length() {
+        let mut target = md5_target(b"password");
+        assert!(target.validate().is_ok());
+
+        // md5-length digest declared as sha256
+        target.algorithm = Algorithm::Sha256;
+        let err = target.validate().unwrap_err();
+        assert!(err.contains("sha256 digest should be 32 bytes"), "{}", err);
+
+        target.hash = "zz".to_string();
+        assert!(target.validate().unwrap_err().contains("not valid hex"));
+    }
+
+    #[test]
+    fn test_salted_defaults_to_a_nonempty_salt() {
+        let mut target = md5_target(b"password");
+        assert!(!target.is_salted());
+        target.salted = Some(true);
+        assert!(target.is_salted() && target.validate().is_ok());
+
+        target.salted = None;
+        target.salt = "nacl".to_string();
+        assert!(target.is_salted());
+
+        // unsalted with a salt is a contradiction
+        target.salted = Some(false);
+        assert!(target.validate().unwrap_err().contains("salted is false"));
+    }
+
+    #[test]
+    fn test_binary_salt_after_the_password() {
+        let mut target = md5_target(b"");
+        target.hash = hex::encode(create_hasher(Algorithm::Md5).hash(b"hunter2\x00\xff"));
+        target.salt = "$HEX[00ff]".to_string();
+        assert_eq!(target.salt_bytes(), [0x00, 0xff]);
+        assert!(!target.hashes_to(b"hunter2"));
+
+        target.salt_after = Some(true);
+        assert!(target.validate().is_ok());
+        assert!(target.hashes_to(b"hunter2"));
+
+        target.salt = "$HEX[0g]".to_string();
+        assert!(target.validate().unwrap_err().contains("not valid hex"));
+    }
+
+    #[test]
+    fn test_truncated_digest() {
+        let full = create_hasher(Algorithm::Sha256).hash(b"password");
+        let mut target = md5_target(b"");
+        target.algorithm = Algorithm::Sha256;
+        target.hash = hex::encode(&full[..8]);
+        assert!(target.validate().unwrap_err().contains("should be 32 bytes"));
+
+        target.hash_bytes = Some(8);
+        assert!(target.validate().is_ok());
+        assert!(target.matches(&full));
+        assert!(!target.matches(&create_hasher(Algorithm::Sha256).hash(b"passwore")));
+
+        target.hash_bytes = Some(33);
+        assert!(target.validate().unwrap_err().contains("outside 1..=32"));
+        target.hash_bytes = Some(4);
+        assert!(target.validate().unwrap_err().contains("holds 8 bytes"));
+    }
+
+    #[test]
+    fn test_crypt_salt_read_from_hash() {
+        let mut target = md5_target(b"");
+        target.algorithm = Algorithm::Sha512Crypt;
+        target.hash = crate::core::crypt::crypt(Algorithm::Sha512Crypt, "$6$nacl", b"hunter2").unwrap();
+
+        assert!(target.validate().is_ok());
+        assert_eq!(target.effective_salt(), "nacl");
+        assert!(target.verify_kdf(b"hunter2"));
+
+        // a redundant salt field has to agree with the hash
+        target.salt = "nacl".to_string();
+        assert!(target.validate().is_ok());
+        target.salt = "pepper".to_string();
+        assert!(target.validate().unwrap_err().contains("differs"));
+
+        // and the declared algorithm with the scheme id
+        target.salt.clear();
+        target.algorithm = Algorithm::Md5Crypt;
+        assert!(target.validate().unwrap_err().contains("sha512crypt crypt string"));
+    }
+
+    #[test]
+    fn test_verify_without_secondary_check() {
+        let target = md5_target(b"password");
+        assert_eq!(target.verify(b"password"), None);
+    }
+
+    #[test]
+    fn test_verify_confirms_true_password() {
+        let mut target = md5_target(b"password");
+        target.verify_algo = Some(Algorithm::Sha256);
+        target.verify_hash = Some(hex::encode(create_hasher(Algorithm::Sha256).hash(b"password")));
+        assert_eq!(target.verify(b"password"), Some(true));
+    }
+
+    #[test]
+    fn test_verify_flags_collision() {
+        // constructed collision: the primary digest matches "password" but
+        // the secondary digest belongs to a different password
+        let mut target = md5_target(b"password");
+        target.verify_algo = Some(Algorithm::Sha256);
+        target.verify_hash = Some(hex::encode(create_hasher(Algorithm::Sha256).hash(b"hunter2")));
+
+        assert!(target.matches(&create_hasher(Algorithm::Md5).hash(b"password")));
+        assert_eq!(target.verify(b"password"), Some(false));
+    }
+
+    #[test]
+    fn test_malformed_verify_hash_is_invalid() {
+        let mut target = md5_target(b"password");
+        target.verify_algo = Some(Algorithm::Sha256);
+        target.verify_hash = Some(hex::encode(create_hasher(Algorithm::Sha256).hash(b"password")));
+        assert!(target.validate().is_ok());
+
+        // rather than flagging the true password as a collision
+        target.verify_hash = Some("not hex".to_string());
+        assert!(target.validate().unwrap_err().contains("verify_hash is not valid hex"));
+        target.verify_hash = Some("abcd".to_string());
+        assert!(target.validate().unwrap_err().contains("sha256 verify_hash should be 32 bytes"));
+        target.verify_algo = Some(Algorithm::Bcrypt);
+        assert!(target.validate().unwrap_err().contains("invalid bcrypt verify_hash"));
+    }
+
+    #[test]
+    fn test_masked_password_display() {
+        let m = TargetMatch {
+            target_id: "t1".to_string(),
+            username: "alice".to_string(),
+            password: b"password".to_vec(),
+            algorithm: Algorithm::Md5,
+            guesses_tried: 1,
+            time_seconds: 0.0,
+            verified: None,
+            expected_password: None,
+        };
+        assert_eq!(m.display_password(true), "p******d");
+        assert_eq!(m.display_password(false), "password");
+        assert_eq!(m.password, b"password");
+        assert_eq!(m.password_string(), "password");
+
+        assert_eq!(mask_password("café"), "c**é");
+        assert_eq!(mask_password("abc"), "a*c");
+        assert_eq!(mask_password("ab"), "**");
+        assert_eq!(mask_password(""), "");
+    }
+}
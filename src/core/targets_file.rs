@@ -0,0 +1,677 @@
+// target file loading - json (default, human-editable), the compact
+// length-prefixed binary `.bft` layout for very large target sets, or a
+// bare hash list (one `hash` or `hash<sep>salt` per line) from a dump, or
+// a list of hashes pasted with their scheme prefix (`{SHA}...`, `sha256:...`).
+// `run --target` specs are parsed here too
+
+use anyhow::{bail, Context, Result};
+use base64::Engine as _;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::core::{Algorithm, Target, TargetError};
+use crate::core::hasher::expected_digest_len;
+
+/// magic + format version at the start of every `.bft` file written
+pub const BFT_MAGIC: &[u8; 4] = b"BFT\x02";
+
+/// the first `.bft` layout, still read: it has no `salted` or `hash_bytes`
+const BFT_V1_MAGIC: &[u8; 4] = b"BFT\x01";
+
+fn is_binary(bytes: &[u8]) -> bool {
+    bytes.starts_with(BFT_MAGIC) || bytes.starts_with(BFT_V1_MAGIC)
+}
+
+/// load targets, detecting the binary format by its magic bytes
+pub fn load_targets(path: &Path) -> Result<Vec<Target>> {
+    let bytes = fs::read(path)
+        .with_context(|| format!("failed to read targets file {}", path.display()))?;
+    parse_targets(&bytes)
+}
+
+/// targets from a file's contents, json or `.bft`
+pub fn parse_targets(bytes: &[u8]) -> Result<Vec<Target>> {
+    if is_binary(bytes) {
+        decode_binary(bytes)
+    } else {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+/// parse targets one entry at a time, so an entry that doesn't parse - an
+/// unknown algorithm, a missing field - comes back as that entry's error
+/// rather than failing the file. `.bft` files are all or nothing
+pub fn parse_targets_each(bytes: &[u8]) -> Result<Vec<Result<Target, TargetError>>> {
+    if is_binary(bytes) {
+        return Ok(decode_binary(bytes)?.into_iter().map(Ok).collect());
+    }
+
+    let entries: Vec<serde_json::Value> = serde_json::from_slice(bytes)?;
+    Ok(entries.into_iter()
+        .enumerate()
+        .map(|(n, entry)| {
+            let id = match entry.get("id").and_then(|id| id.as_str()) {
+                Some(id) => id.to_string(),
+                None => format!("#{}", n + 1),
+            };
+            serde_json::from_value(entry).map_err(|e| TargetError { id, reason: e.to_string() })
+        })
+        .collect())
+}
+
+/// parse a bare hash list, every line one target of `algorithm`. with a
+/// separator each line must split into exactly `hash` and `salt`
+pub fn parse_hash_list(text: &str, algorithm: Algorithm, separator: Option<char>) -> Result<Vec<Target>> {
+    if separator.is_some() && algorithm.is_kdf() {
+        bail!("{} hashes carry their own salt; a salt separator only applies to raw digests", algorithm);
+    }
+
+    let mut targets = Vec::new();
+
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let (hash, salt) = match separator {
+            Some(sep) => {
+                let parts: Vec<&str> = line.split(sep).collect();
+                let [hash, salt] = parts[..] else {
+                    bail!("line {}: expected hash{}salt, got {} part(s)", n + 1, sep, parts.len());
+                };
+                (hash, salt)
+            }
+            None => (line, ""),
+        };
+
+        targets.push(Target {
+            id: format!("line{}", n + 1),
+            username: String::new(),
+            algorithm,
+            hash: hash.to_string(),
+            salt: salt.to_string(),
+            ..Default::default()
+        });
+    }
+
+    Ok(targets)
+}
+
+/// how the digest after a scheme prefix is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestEncoding {
+    Hex,
+    Base64,
+    /// base64 of the digest with the salt appended (ldap `{SSHA}` and kin),
+    /// the salt having been hashed after the password
+    SaltedBase64,
+}
+
+/// recognise the scheme prefix on a hash pasted from a dump - ldap's
+/// `{SHA}`, `{SSHA}`, `{MD5}`, `{SMD5}`, `{SHA256}`, `{SSHA256}`, or
+/// `algo:` before a hex digest - and return its algorithm, how the rest is
+/// encoded, and the rest. `None` for anything else
+pub fn parse_prefixed_hash(hash: &str) -> Option<(Algorithm, DigestEncoding, String)> {
+    let hash = hash.trim();
+    
+    if let Some((scheme, digest)) = hash.strip_prefix('{').and_then(|rest| rest.split_once('}')) {
+        let (algorithm, encoding) = match scheme.to_ascii_uppercase().as_str() {
+            "SHA" => (Algorithm::Sha1, DigestEncoding::Base64),
+            "SSHA" => (Algorithm::Sha1, DigestEncoding::SaltedBase64),
+            "MD5" => (Algorithm::Md5, DigestEncoding::Base64),
+            "SMD5" => (Algorithm::Md5, DigestEncoding::SaltedBase64),
+            "SHA256" => (Algorithm::Sha256, DigestEncoding::Base64),
+            "SSHA256" => (Algorithm::Sha256, DigestEncoding::SaltedBase64),
+            _ => return None,
+        };
+        return Some((algorithm, encoding, digest.to_string()));
+    }
+    
+    let (scheme, digest) = hash.split_once(':')?;
+    let algorithm: Algorithm = scheme.parse().ok()?;
+    if algorithm.is_kdf() || digest.is_empty() || !digest.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some((algorithm, DigestEncoding::Hex, digest.to_string()))
+}
+
+/// parse a list of prefixed hashes, one per line, each target taking its
+/// algorithm from the prefix
+pub fn parse_prefixed_list(text: &str) -> Result<Vec<Target>> {
+    let mut targets = Vec::new();
+
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let Some((algorithm, encoding, digest)) = parse_prefixed_hash(line) else {
+            bail!("line {}: no recognised scheme prefix (e.g. {{SHA}} or sha256:)", n + 1);
+        };
+        let mut target = Target {
+            id: format!("line{}", n + 1),
+            username: String::new(),
+            algorithm,
+            ..Default::default()
+        };
+        match encoding {
+            DigestEncoding::Hex => target.hash = digest.to_ascii_lowercase(),
+            DigestEncoding::Base64 => {
+                let bytes = base64::engine::general_purpose::STANDARD.decode(&digest)
+                    .with_context(|| format!("line {}: invalid base64", n + 1))?;
+                target.hash = hex::encode(bytes);
+            }
+            // the digest then the salt, which was hashed after the password
+            // and is usually raw bytes
+            DigestEncoding::SaltedBase64 => {
+                let bytes = base64::engine::general_purpose::STANDARD.decode(&digest)
+                    .with_context(|| format!("line {}: invalid base64", n + 1))?;
+                let len = expected_digest_len(algorithm).expect("ldap schemes are raw digests");
+                if bytes.len() <= len {
+                    bail!("line {}: {} bytes hold no salt after the {}-byte {} digest", n + 1, bytes.len(), len, algorithm);
+                }
+                let (hash, salt) = bytes.split_at(len);
+                target.hash = hex::encode(hash);
+                target.salt = format!("$HEX[{}]", hex::encode(salt));
+                target.salt_after = Some(true);
+            }
+        }
+
+        targets.push(target);
+    }
+
+    Ok(targets)
+}
+
+/// parse a `run --target` spec, `id:algo:hash[:salt]`. the salt is the
+/// rest of the spec, so it may hold colons of its own
+pub fn parse_inline_target(spec: &str) -> Result<Target> {
+    let parts: Vec<&str> = spec.splitn(4, ':').collect();
+    let [id, algo, hash, ref rest @ ..] = parts[..] else {
+        bail!("expected id:algo:hash[:salt], got '{}'", spec);
+    };
+    if id.is_empty() || hash.is_empty() {
+        bail!("expected id:algo:hash[:salt] with a non-empty id and hash, got '{}'", spec);
+    }
+    let algorithm: Algorithm = algo.parse().map_err(anyhow::Error::msg)?;
+
+    let target = Target {
+        id: id.to_string(),
+        username: id.to_string(),
+        algorithm,
+        hash: hash.to_string(),
+        salt: rest.first().copied().unwrap_or_default().to_string(),
+        ..Default::default()
+    };
+    target.validate().map_err(|reason| anyhow::anyhow!("target {}: {}", id, reason))?;
+    Ok(target)
+}
+
+/// layout: magic, u64 count, then per target the fields in declaration order.
+/// strings are a u32 length + utf-8 bytes, optionals a 0/1 tag byte, all little-endian;
+/// `salted` is a 0/1 byte after its tag and `hash_bytes` a u32.
+/// `expected_password` isn't stored - answer keys belong in small json datasets -
+/// and neither is `password_encoding`, so every `.bft` target is utf-8, or
+/// `salt_after`, which only `--hash-prefixes` lists set
+pub fn encode_binary(targets: &[Target]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(16 + targets.len() * 96);
+    out.extend_from_slice(BFT_MAGIC);
+    out.extend_from_slice(&(targets.len() as u64).to_le_bytes());
+
+    for target in targets {
+        put_str(&mut out, &target.id);
+        put_str(&mut out, &target.username);
+        put_str(&mut out, &target.algorithm.to_string());
+        put_str(&mut out, &target.hash);
+        put_str(&mut out, &target.salt);
+
+        match target.salted {
+            Some(salted) => out.extend_from_slice(&[1, salted as u8]),
+            None => out.push(0),
+        }
+        match target.verify_algo {
+            Some(algo) => {
+                out.push(1);
+                put_str(&mut out, &algo.to_string());
+            }
+            None => out.push(0),
+        }
+        match &target.verify_hash {
+            Some(hash) => {
+                out.push(1);
+                put_str(&mut out, hash);
+            }
+            None => out.push(0),
+        }
+        match target.priority {
+            Some(priority) => {
+                out.push(1);
+                out.extend_from_slice(&priority.to_le_bytes());
+            }
+            None => out.push(0),
+        }
+        match target.hash_bytes {
+            Some(bytes) => {
+                out.push(1);
+                out.extend_from_slice(&(bytes as u32).to_le_bytes());
+            }
+            None => out.push(0),
+        }
+    }
+
+    out
+}
+
+pub fn decode_binary(bytes: &[u8]) -> Result<Vec<Target>> {
+    let mut reader = Reader { bytes, pos: 0 };
+
+    let version = match reader.take(BFT_MAGIC.len())? {
+        magic if magic == BFT_MAGIC => 2,
+        magic if magic == BFT_V1_MAGIC => 1,
+        _ => bail!("not a .bft targets file"),
+    };
+
+    let count = u64::from_le_bytes(reader.take(8)?.try_into().unwrap());
+    // don't trust the header for the allocation - a corrupt count would abort
+    let mut targets = Vec::with_capacity(count.min(1 << 20) as usize);
+
+    for _ in 0..count {
+        let id = reader.string()?;
+        let username = reader.string()?;
+        let algorithm = reader.algorithm()?;
+        let hash = reader.string()?;
+        let salt = reader.string()?;
+
+        let salted = match version >= 2 && reader.flag()? {
+            true => Some(reader.flag()?),
+            false => None,
+        };
+        let verify_algo = match reader.flag()? {
+            true => Some(reader.algorithm()?),
+            false => None,
+        };
+        let verify_hash = match reader.flag()? {
+            true => Some(reader.string()?),
+            false => None,
+        };
+        let priority = match reader.flag()? {
+            true => Some(u32::from_le_bytes(reader.take(4)?.try_into().unwrap())),
+            false => None,
+        };
+        let hash_bytes = match version >= 2 && reader.flag()? {
+            true => Some(u32::from_le_bytes(reader.take(4)?.try_into().unwrap()) as usize),
+            false => None,
+        };
+
+        targets.push(Target {
+            id,
+            username,
+            algorithm,
+            hash,
+            salt,
+            salted,
+            verify_algo,
+            verify_hash,
+            priority,
+            hash_bytes,
+            ..Default::default()
+        });
+    }
+
+    if reader.pos != bytes.len() {
+        bail!("trailing data after {} targets in .bft file", count);
+    }
+
+    Ok(targets)
+}
+
+fn put_str(out: &mut Vec<u8>, s: &str) {
+    out.extend_from_slice(&(s.len() as u32).to_le_bytes());
+    out.extend_from_slice(s.as_bytes());
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        let end = self.pos.checked_add(n).filter(|&end| end <= self.bytes.len());
+        let Some(end) = end else {
+            bail!("truncated .bft file at byte {}", self.pos);
+        };
+
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as usize;
+        let raw = self.take(len)?;
+        Ok(String::from_utf8(raw.to_vec())?)
+    }
+
+    fn algorithm(&mut self) -> Result<Algorithm> {
+        let name = self.string()?;
+        name.parse().map_err(|e: String| anyhow::anyhow!(e))
+    }
+
+    fn flag(&mut self) -> Result<bool> {
+        match self.take(1)?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => bail!("invalid option tag {} in .bft file", other),
+        }
+    }
+}
+
+/// what changed between two targets files, matched up by id
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TargetsDiff {
+    /// ids only in the new file, in its order
+    pub added: Vec<String>,
+    /// ids only in the old file, in its order
+    pub removed: Vec<String>,
+    pub modified: Vec<ModifiedTarget>,
+}
+
+/// a target in both files whose hash fields differ
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModifiedTarget {
+    pub id: String,
+    /// "algorithm", "hash" and/or "salt"
+    pub fields: Vec<&'static str>,
+}
+
+/// compare `old` and `new` target by target. hex case and the rest of a
+/// target's fields (username, priority, ...) aren't counted as changes;
+/// kdf hashes (bcrypt, argon2, ...) are base64 and compared exactly
+pub fn diff_targets(old: &[Target], new: &[Target]) -> TargetsDiff {
+    let old_by_id: HashMap<&str, &Target> = old.iter().map(|t| (t.id.as_str(), t)).collect();
+    let new_by_id: HashMap<&str, &Target> = new.iter().map(|t| (t.id.as_str(), t)).collect();
+
+    let mut diff = TargetsDiff::default();
+    for target in old {
+        let Some(updated) = new_by_id.get(target.id.as_str()) else {
+            diff.removed.push(target.id.clone());
+            continue;
+        };
+        let fields: Vec<&'static str> = [
+            ("algorithm", target.algorithm != updated.algorithm),
+            ("hash", match target.algorithm.is_kdf() || updated.algorithm.is_kdf() {
+                true => target.hash != updated.hash,
+                false => !target.hash.eq_ignore_ascii_case(&updated.hash),
+            }),
+            ("salt", target.salt != updated.salt || target.is_salt_after() != updated.is_salt_after()),
+        ]
+        .into_iter()
+        .filter_map(|(field, changed)| changed.then_some(field))
+        .collect();
+        if !fields.is_empty() {
+            diff.modified.push(ModifiedTarget { id: target.id.clone(), fields });
+        }
+    }
+    diff.added = new.iter()
+        .filter(|t| !old_by_id.contains_key(t.id.as_str()))
+        .map(|t| t.id.clone())
+        .collect();
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::hasher::create_hasher;
+
+    fn sample_targets() -> Vec<Target> {
+        let mut targets: Vec<Target> = [Algorithm::Md5, Algorithm::Sha256, Algorithm::BlitzHash]
+            .iter()
+            .enumerate()
+            .map(|(i, algo)| Target {
+                id: format!("t{}", i),
+                username: format!("user{}", i),
+                algorithm: *algo,
+                hash: hex::encode(create_hasher(*algo).hash(b"password")),
+                salt: if i == 1 { "pepper".to_string() } else { String::new() },
+                ..Default::default()
+            })
+            .collect();
+
+        targets[0].verify_algo = Some(Algorithm::Sha1);
+        targets[0].verify_hash = Some(hex::encode(create_hasher(Algorithm::Sha1).hash(b"password")));
+        targets[2].priority = Some(7);
+        targets[1].hash_bytes = Some(8);
+        targets[1].hash.truncate(16);
+        targets
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let targets = sample_targets();
+        let decoded = decode_binary(&encode_binary(&targets)).unwrap();
+        assert_eq!(decoded, targets);
+    }
+
+    #[test]
+    fn test_binary_and_json_load_identically() {
+        let targets = sample_targets();
+        let dir = std::env::temp_dir();
+        let json_path = dir.join(format!("blitzforge_targets_{}.json", std::process::id()));
+        let bft_path = dir.join(format!("blitzforge_targets_{}.bft", std::process::id()));
+
+        fs::write(&json_path, serde_json::to_string_pretty(&targets).unwrap()).unwrap();
+        fs::write(&bft_path, encode_binary(&targets)).unwrap();
+
+        let from_json = load_targets(&json_path).unwrap();
+        let from_bft = load_targets(&bft_path).unwrap();
+
+        fs::remove_file(&json_path).ok();
+        fs::remove_file(&bft_path).ok();
+
+        assert_eq!(from_json, targets);
+        assert_eq!(from_bft, from_json);
+    }
+
+    #[test]
+    fn test_binary_keeps_salted_and_hash_bytes() {
+        let md5 = create_hasher(Algorithm::Md5);
+        let targets = vec![
+            Target {
+                id: "empty_salt".to_string(),
+                algorithm: Algorithm::Md5,
+                hash: hex::encode(md5.hash(b"password")),
+                salted: Some(true),
+                ..Default::default()
+            },
+            Target {
+                id: "unsalted_prefix".to_string(),
+                algorithm: Algorithm::Md5,
+                hash: hex::encode(&md5.hash(b"password")[..6]),
+                salted: Some(false),
+                hash_bytes: Some(6),
+                ..Default::default()
+            },
+        ];
+
+        let decoded = decode_binary(&encode_binary(&targets)).unwrap();
+        assert_eq!(decoded, targets);
+        assert!(decoded.iter().all(|t| t.validate().is_ok()));
+    }
+
+    #[test]
+    fn test_version_1_binary_still_loads() {
+        let target = Target {
+            id: "old".to_string(),
+            username: "user".to_string(),
+            algorithm: Algorithm::Md5,
+            hash: hex::encode(create_hasher(Algorithm::Md5).hash(b"password")),
+            ..Default::default()
+        };
+        // version 1 is the same without the `salted` and `hash_bytes` tags,
+        // the fifth-last and last bytes here
+        let mut bytes = encode_binary(std::slice::from_ref(&target));
+        bytes[..4].copy_from_slice(BFT_V1_MAGIC);
+        bytes.pop();
+        bytes.remove(bytes.len() - 4);
+
+        assert_eq!(parse_targets(&bytes).unwrap(), vec![target]);
+    }
+
+    #[test]
+    fn test_truncated_binary_is_an_error() {
+        let bytes = encode_binary(&sample_targets());
+        let err = decode_binary(&bytes[..bytes.len() - 3]).unwrap_err();
+        assert!(err.to_string().contains("truncated"), "{}", err);
+    }
+
+    #[test]
+    fn test_hash_list_colon_separator() {
+        let md5 = hex::encode(create_hasher(Algorithm::Md5).hash(b"x"));
+        let text = format!("{}:nacl\n\n{}:pepper\n", md5, md5);
+
+        let targets = parse_hash_list(&text, Algorithm::Md5, Some(':')).unwrap();
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].hash, md5);
+        assert_eq!(targets[0].salt, "nacl");
+        assert_eq!(targets[1].id, "line3");
+        assert_eq!(targets[1].hash, md5);
+        assert_eq!(targets[1].salt, "pepper");
+    }
+
+    #[test]
+    fn test_hash_list_dollar_separator() {
+        let targets = parse_hash_list("aa$s1\nbb$s2\n", Algorithm::Md5, Some('$')).unwrap();
+        let pairs: Vec<(&str, &str)> = targets.iter().map(|t| (t.hash.as_str(), t.salt.as_str())).collect();
+        assert_eq!(pairs, vec![("aa", "s1"), ("bb", "s2")]);
+
+        // without a separator the whole line is the hash
+        let targets = parse_hash_list("aa$s1\n", Algorithm::Md5, None).unwrap();
+        assert_eq!(targets[0].hash, "aa$s1");
+        assert_eq!(targets[0].salt, "");
+    }
+
+    #[test]
+    fn test_hash_list_requires_exactly_two_parts() {
+        let err = parse_hash_list("aa:s1\nbb\n", Algorithm::Md5, Some(':')).unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{}", err);
+
+        let err = parse_hash_list("aa:s1:extra\n", Algorithm::Md5, Some(':')).unwrap_err();
+        assert!(err.to_string().contains("3 part(s)"), "{}", err);
+    }
+
+    #[test]
+    fn test_prefixed_hashes() {
+        let sha1 = create_hasher(Algorithm::Sha1).hash(b"password");
+        let b64 = base64::engine::general_purpose::STANDARD.encode(&sha1);
+        assert_eq!(b64, "W6ph5Mm5Pz8GgiULbPgzG37mj9g=");
+
+        assert_eq!(parse_prefixed_hash(&format!("{{SHA}}{}", b64)), Some((Algorithm::Sha1, DigestEncoding::Base64, b64.clone())));
+        assert_eq!(parse_prefixed_hash("{ssha}c2FsdGVk"), Some((Algorithm::Sha1, DigestEncoding::SaltedBase64, "c2FsdGVk".to_string())));
+        assert_eq!(
+            parse_prefixed_hash("sha256:5E884898da28"),
+            Some((Algorithm::Sha256, DigestEncoding::Hex, "5E884898da28".to_string())),
+        );
+        for unprefixed in ["5e884898da28", "{CRYPT}$1$salt$x", "sha256:not-hex", "user:5e88", "bcrypt:00"] {
+            assert_eq!(parse_prefixed_hash(unprefixed), None, "{}", unprefixed);
+        }
+
+        // the list decodes each digest to hex under the prefix's algorithm
+        let targets = parse_prefixed_list(&format!("{{SHA}}{}\n\nsha256:{}\n", b64, "AB".repeat(32))).unwrap();
+        assert_eq!(targets.len(), 2);
+        assert_eq!((targets[0].algorithm, targets[0].hash.as_str()), (Algorithm::Sha1, hex::encode(&sha1).as_str()));
+        assert!(targets[0].matches(&sha1));
+        assert_eq!((targets[1].id.as_str(), targets[1].hash.as_str()), ("line3", "ab".repeat(32).as_str()));
+
+        // {SSHA}: the digest then a binary salt, hashed after the password
+        let salt = [0x9a, 0x00, 0x3c, 0xf1];
+        let salted = create_hasher(Algorithm::Sha1).hash(&[&b"hunter2"[..], &salt].concat());
+        let ssha = base64::engine::general_purpose::STANDARD.encode([&salted[..], &salt].concat());
+        let targets = parse_prefixed_list(&format!("{{SSHA}}{}\n", ssha)).unwrap();
+        assert_eq!((targets[0].algorithm, targets[0].hash.as_str()), (Algorithm::Sha1, hex::encode(&salted).as_str()));
+        assert_eq!((targets[0].salt.as_str(), targets[0].salt_after), ("$HEX[9a003cf1]", Some(true)));
+        assert!(targets[0].validate().is_ok());
+        assert!(targets[0].hashes_to(b"hunter2"));
+        assert!(!targets[0].hashes_to(b"hunter3"));
+
+        let err = parse_prefixed_list("{SSHA}c2FsdGVk\n").unwrap_err();
+        assert!(err.to_string().contains("hold no salt"), "{}", err);
+        let err = parse_prefixed_list("{SHA}W6ph\n5e88\n").unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{}", err);
+    }
+
+    #[test]
+    fn test_inline_target_specs() {
+        let md5 = hex::encode(create_hasher(Algorithm::Md5).hash(b"x"));
+
+        let target = parse_inline_target(&format!("a:md5:{}", md5)).unwrap();
+        assert_eq!((target.id.as_str(), target.algorithm, target.salt.as_str()), ("a", Algorithm::Md5, ""));
+        assert_eq!(target.hash, md5);
+
+        // everything after the third colon is salt
+        let target = parse_inline_target(&format!("b:MD5:{}:s:a:l:t", md5)).unwrap();
+        assert_eq!(target.salt, "s:a:l:t");
+
+        let bcrypt = "$2b$04$abcdefghijklmnopqrstuuN1lLPcXVzOvh4o5VWsqIUgJHuBd.Q.W";
+        assert_eq!(parse_inline_target(&format!("c:bcrypt:{}", bcrypt)).unwrap().hash, bcrypt);
+
+        for (spec, error) in [
+            (format!("a:{}", md5), "expected id:algo:hash"),
+            (format!(":md5:{}", md5), "non-empty id"),
+            ("a:md5:".to_string(), "non-empty id and hash"),
+            (format!("a:md6:{}", md5), "unknown algorithm"),
+            (format!("a:sha256:{}", md5), "should be 32 bytes"),
+            ("a:md5:xyz".to_string(), "not valid hex"),
+        ] {
+            let err = parse_inline_target(&spec).unwrap_err();
+            assert!(err.to_string().contains(error), "{}: {}", spec, err);
+        }
+    }
+
+    #[test]
+    fn test_diff_targets() {
+        let old = sample_targets();
+        let mut new = sample_targets();
+
+        // t0 gets a new hash (upper-cased too), t1 a new algorithm and
+        // salt, t2 is dropped and t3 added; username edits don't count
+        new[0].hash = hex::encode(create_hasher(Algorithm::Md5).hash(b"changed")).to_uppercase();
+        new[1].algorithm = Algorithm::Sha1;
+        new[1].salt = "salt".to_string();
+        new[1].username = "renamed".to_string();
+        let mut added = new.remove(2);
+        added.id = "t3".to_string();
+        new.push(added);
+
+        let diff = diff_targets(&old, &new);
+        assert_eq!(diff.added, vec!["t3"]);
+        assert_eq!(diff.removed, vec!["t2"]);
+        assert_eq!(diff.modified, vec![
+            ModifiedTarget { id: "t0".to_string(), fields: vec!["hash"] },
+            ModifiedTarget { id: "t1".to_string(), fields: vec!["algorithm", "salt"] },
+        ]);
+
+        let mut same = sample_targets();
+        same[2].hash = same[2].hash.to_uppercase();
+        same[2].priority = None;
+        assert_eq!(diff_targets(&old, &same), TargetsDiff::default());
+
+        // bcrypt's base64 is case-sensitive: re-casing it is a new hash
+        let bcrypt = Target {
+            id: "b0".to_string(),
+            algorithm: Algorithm::Bcrypt,
+            hash: "$2b$04$BwcHBwcHBwcHBwcHBwcHB.pVKSWU6TbvhXdqn1E.wUTbBmz9xcSVa".to_string(),
+            ..Default::default()
+        };
+        let recased = Target { hash: bcrypt.hash.to_lowercase(), ..bcrypt.clone() };
+        assert_eq!(diff_targets(std::slice::from_ref(&bcrypt), std::slice::from_ref(&bcrypt)), TargetsDiff::default());
+        assert_eq!(diff_targets(&[bcrypt], &[recased]).modified, vec![
+            ModifiedTarget { id: "b0".to_string(), fields: vec!["hash"] },
+        ]);
+    }
+}
@@ -5,17 +5,21 @@ pub mod cli;
 pub mod tools;
 
 // re-exports for convenience
+pub use core::engine::{crack_one, MatchStream, PreparedTargets};
 pub use core::{
     Engine, 
+    CandidateTransform,
     CrackingResult,
     Algorithm,
     Hasher,
     Generator,
     DictionaryGenerator,
+    MultiDictionaryGenerator,
     MaskGenerator,
     BruteForceGenerator,
     Target,
     TargetMatch,
+    PasswordEncoding,
 };
 
 pub use cli::{
@@ -1,12 +1,8 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
 
-mod core;
-mod cli;
-mod tools;
-
-use cli::commands;
+use blitzforge::cli::{self, commands, exit_code};
+use blitzforge::core::{CrackingResult, TerminationReason};
 
 #[derive(Parser)]
 #[command(name = "blitzforge")]
@@ -19,117 +15,135 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Commands {
-    /// generate demo target hashes from known passwords
-    GenerateTargets {
-        /// output json file path
-        #[arg(short, long)]
-        out: PathBuf,
-        
-        /// input passwords file (one per line)
-        #[arg(short, long)]
-        passwords: PathBuf,
-        
-        /// comma-separated algorithms (md5,sha1,sha256,md4)
-        #[arg(short, long, default_value = "md5,sha256")]
-        algorithms: String,
-    },
+    /// generate demo target hashes from known (or made-up) passwords
+    GenerateTargets(commands::GenerateTargetsArgs),
     
     /// run cracking job with live terminal ui
-    Run {
-        /// targets json file
-        #[arg(short, long)]
-        targets: PathBuf,
-        
-        /// attack strategy
-        #[arg(short, long, value_enum)]
-        strategy: cli::commands::Strategy,
-        
-        /// wordlist path (for dictionary/hybrid modes)
-        #[arg(short, long)]
-        wordlist: Option<PathBuf>,
-        
-        /// mask pattern (for mask/hybrid modes) e.g. ?l?l?l?d?d
-        #[arg(short, long)]
-        mask: Option<String>,
-        
-        /// charset (for brute force mode) e.g. "abc123"
-        #[arg(short, long)]
-        charset: Option<String>,
-        
-        /// minimum length (for brute force)
-        #[arg(long, default_value = "1")]
-        min_len: usize,
-        
-        /// maximum length (for brute force)
-        #[arg(long, default_value = "8")]
-        max_len: usize,
-        
-        /// number of worker threads (default: cpu count)
-        #[arg(long)]
-        workers: Option<usize>,
-        
-        /// batch size for candidate processing
-        #[arg(long, default_value = "4096")]
-        batch_size: usize,
-        
-        /// number of repeat runs for benchmarking
-        #[arg(short, long, default_value = "1")]
-        repeat: usize,
-        
-        /// csv log file for benchmark results
-        #[arg(short, long)]
-        log: Option<PathBuf>,
-    },
+    Run(Box<commands::RunArgs>),
+    
+    /// build and run an attack step by step from prompts
+    Interactive,
+    
+    /// show the first and last candidates a strategy would produce, without hashing
+    Preview(commands::PreviewArgs),
+    
+    /// print the mask (?l ?u ?d ?s) matching each sample password
+    MaskOf(commands::MaskOfArgs),
+    
+    /// print the digest of a string under one algorithm, optionally salted
+    Hash(commands::HashArgs),
+    
+    /// print `run` commands that divide a keyspace into shards for distributed runs
+    Split(commands::SplitArgs),
+    
+    /// combine result files and potfiles from sharded runs, deduplicated
+    Merge(commands::MergeArgs),
+    
+    /// list targets added, removed or changed between two targets files
+    DiffTargets(commands::DiffTargetsArgs),
+    
+    /// hash a keyspace into a `hash<TAB>password` lookup table
+    GenerateTable(commands::GenerateTableArgs),
+    
+    /// time cracking one known password hashed under each algorithm
+    CompareAlgorithms(commands::CompareAlgorithmsArgs),
     
     /// generate summary report from benchmark csv
-    Report {
-        /// csv file to analyze
-        #[arg(short, long)]
-        csv: PathBuf,
-    },
+    Report(commands::ReportArgs),
     
     /// run internal tests and micro-benchmarks
     Selftest,
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+fn main() {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) => {
+            // --help and --version come through here too
+            let code = if err.use_stderr() { exit_code::INVALID_ARGS } else { exit_code::ALL_FOUND };
+            err.print().ok();
+            std::process::exit(code);
+        }
+    };
+    
+    // default sigint would kill us with 130; scripts get the documented code,
+    // and a running attack gets to report what it found first
+    ctrlc::set_handler(cli::interrupt::on_ctrl_c).ok();
+    #[cfg(unix)]
+    cli::interrupt::watch_sigterm().ok();
+    
+    let code = match run(cli) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("Error: {:?}", err);
+            exit_code::for_error(&err)
+        }
+    };
     
+    std::process::exit(code);
+}
+
+/// cracking results decide between 0, 2, 4 and 5; other commands succeed with 0
+fn outcome(result: &CrackingResult) -> i32 {
+    if cli::interrupt::terminated() {
+        exit_code::TERMINATED
+    } else if result.termination == TerminationReason::Interrupted {
+        exit_code::INTERRUPTED
+    } else if result.matches.len() < result.statistics.targets_total {
+        exit_code::SOME_UNFOUND
+    } else {
+        exit_code::ALL_FOUND
+    }
+}
+
+fn run(cli: Cli) -> Result<i32> {
     match cli.command {
-        Commands::GenerateTargets { out, passwords, algorithms } => {
-            commands::generate_targets(out, passwords, algorithms)?;
+        Commands::GenerateTargets(args) => {
+            commands::generate_targets(args)?;
         }
         
-        Commands::Run {
-            targets,
-            strategy,
-            wordlist,
-            mask,
-            charset,
-            min_len,
-            max_len,
-            workers,
-            batch_size,
-            repeat,
-            log,
-        } => {
-            commands::run_cracking(
-                targets,
-                strategy,
-                wordlist,
-                mask,
-                charset,
-                min_len,
-                max_len,
-                workers,
-                batch_size,
-                repeat,
-                log,
-            )?;
+        Commands::Run(args) => {
+            return Ok(outcome(&commands::run_cracking(*args)?));
         }
         
-        Commands::Report { csv } => {
-            commands::generate_report(csv)?;
+        Commands::Interactive => {
+            return Ok(outcome(&cli::interactive::run_interactive()?));
+        }
+        
+        Commands::Preview(args) => {
+            commands::preview(args)?;
+        }
+        
+        Commands::MaskOf(args) => {
+            commands::mask_of(args)?;
+        }
+        
+        Commands::Hash(args) => {
+            commands::hash(args)?;
+        }
+        
+        Commands::Split(args) => {
+            commands::split(args)?;
+        }
+        
+        Commands::Merge(args) => {
+            commands::merge(args)?;
+        }
+        
+        Commands::DiffTargets(args) => {
+            commands::diff_targets(args)?;
+        }
+        
+        Commands::GenerateTable(args) => {
+            commands::generate_table(args)?;
+        }
+        
+        Commands::CompareAlgorithms(args) => {
+            commands::compare_algorithms(args)?;
+        }
+        
+        Commands::Report(args) => {
+            commands::generate_report(args)?;
         }
         
         Commands::Selftest => {
@@ -137,5 +151,5 @@ fn main() -> Result<()> {
         }
     }
     
-    Ok(())
-}
\ No newline at end of file
+    Ok(exit_code::ALL_FOUND)
+}
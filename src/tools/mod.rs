@@ -1,12 +1,53 @@
-use crate::core::{Algorithm, Target, hasher::*};
-use rand::Rng;
+use crate::core::{Algorithm, Generator, PasswordEncoding, Target, hasher::*};
+use crate::core::generator::CharSet;
+use anyhow::Result;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::io::Write;
 
-/// generate demo targets from known passwords
-pub fn generate_demo_targets(passwords: &[String], algorithms: &[Algorithm]) -> Vec<Target> {
+/// id `generate_demo_targets` gives the `idx`th password's `algorithm` target
+fn demo_target_id(idx: usize, algorithm: Algorithm) -> String {
+    format!("demo{}_{}", idx, algorithm)
+}
+
+/// whether `target` looks like it came from `generate_demo_targets`
+pub fn is_demo_target(target: &Target) -> bool {
+    let idx = target.id.strip_prefix("demo")
+        .and_then(|rest| rest.split_once('_'))
+        .and_then(|(idx, _)| idx.parse::<usize>().ok());
+    
+    idx.is_some_and(|idx| target.id == demo_target_id(idx, target.algorithm)
+        && target.username == format!("user{}", idx))
+}
+
+/// generate demo targets from known passwords, hashing each one as
+/// `encoding` bytes and recording that on the target
+pub fn generate_demo_targets(
+    passwords: &[String],
+    algorithms: &[Algorithm],
+    encoding: PasswordEncoding,
+) -> Result<Vec<Target>> {
+    generate_peppered_targets(passwords, algorithms, encoding, &[])
+}
+
+/// `generate_demo_targets` with `pepper` appended to each password's bytes
+/// before hashing. the pepper isn't recorded, so only a run given the same
+/// pepper can crack them
+pub fn generate_peppered_targets(
+    passwords: &[String],
+    algorithms: &[Algorithm],
+    encoding: PasswordEncoding,
+    pepper: &[u8],
+) -> Result<Vec<Target>> {
     let mut targets = Vec::new();
     let mut rng = rand::thread_rng();
+    let recorded = (encoding != PasswordEncoding::Utf8).then_some(encoding);
     
     for (idx, password) in passwords.iter().enumerate() {
+        let mut bytes = encoding.encode(password)
+            .ok_or_else(|| anyhow::anyhow!("password {} can't be encoded as {}", idx + 1, encoding))?;
+        bytes.extend_from_slice(pepper);
+        
         for algo in algorithms {
             let hasher = create_hasher(*algo);
             
@@ -18,21 +59,298 @@ pub fn generate_demo_targets(passwords: &[String], algorithms: &[Algorithm]) ->
                 String::new()
             };
             
+            // kdfs embed a random salt and their cost parameters in a phc string
+            if algo.is_kdf() {
+                let kdf_salt: [u8; 16] = rng.gen();
+                let phc = hash_phc(*algo, &bytes, &kdf_salt)
+                    .expect("demo kdf parameters are valid");
+                
+                targets.push(Target {
+                    id: demo_target_id(idx, *algo),
+                    username: format!("user{}", idx),
+                    algorithm: *algo,
+                    hash: phc,
+                    password_encoding: recorded,
+                    ..Default::default()
+                });
+                continue;
+            }
+            
             let hash = if salt.is_empty() {
-                hasher.hash(password.as_bytes())
+                hasher.hash(&bytes)
             } else {
-                hasher.hash_with_salt(password.as_bytes(), salt.as_bytes())
+                hasher.hash_with_salt(&bytes, salt.as_bytes())
             };
             
             targets.push(Target {
-                id: format!("demo{}_{}", idx, algo),
+                id: demo_target_id(idx, *algo),
                 username: format!("user{}", idx),
                 algorithm: *algo,
                 hash: hex::encode(hash),
                 salt,
+                password_encoding: recorded,
+                ..Default::default()
             });
         }
     }
     
-    targets
-}
\ No newline at end of file
+    Ok(targets)
+}
+
+/// the digest `hash` prints: lowercase hex of the salt then the password for
+/// fast hashes, the same as a target's `salt`, or a phc string for kdfs, with
+/// `salt` as the kdf salt (16 random bytes without one)
+pub fn hash_password(algorithm: Algorithm, password: &[u8], salt: Option<&[u8]>) -> Result<String> {
+    if algorithm.is_kdf() {
+        let random: [u8; 16] = rand::thread_rng().gen();
+        return hash_phc(algorithm, password, salt.unwrap_or(&random));
+    }
+    
+    let hasher = create_hasher(algorithm);
+    let hash = match salt {
+        Some(salt) => hasher.hash_with_salt(password, salt),
+        None => hasher.hash(password),
+    };
+    Ok(hex::encode(hash))
+}
+
+/// `count` random passwords drawn from `charset`, each of a length picked
+/// uniformly from `min_len..=max_len`. the same seed always gives the same list
+pub fn synthetic_passwords(count: usize, charset: &str, min_len: usize, max_len: usize, seed: u64) -> Vec<String> {
+    let chars: Vec<char> = charset.chars().collect();
+    let mut rng = StdRng::seed_from_u64(seed);
+    
+    (0..count)
+        .map(|_| {
+            let len = rng.gen_range(min_len..=max_len);
+            (0..len).map(|_| chars[rng.gen_range(0..chars.len())]).collect()
+        })
+        .collect()
+}
+
+/// `id<TAB>password` for every target `generate_demo_targets` makes from
+/// these passwords and algorithms, in the same order
+pub fn answer_key(passwords: &[String], algorithms: &[Algorithm]) -> String {
+    let mut key = String::new();
+    for (idx, password) in passwords.iter().enumerate() {
+        for algo in algorithms {
+            key.push_str(&format!("{}\t{}\n", demo_target_id(idx, *algo), password));
+        }
+    }
+    key
+}
+
+/// the mask that produces `password`: `?l`, `?u`, `?d` or `?s` per
+/// character, and the character itself where it's in none of those classes -
+/// spaces, quotes, non-ascii letters. '?' is a special, so it becomes `?s`
+pub fn mask_of(password: &str) -> String {
+    let special = CharSet::special();
+    password.chars()
+        .map(|c| match c {
+            'a'..='z' => "?l".to_string(),
+            'A'..='Z' => "?u".to_string(),
+            '0'..='9' => "?d".to_string(),
+            c if c.is_ascii() && special.chars.contains(&(c as u8)) => "?s".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// a brute-force charset and length bounds for passwords shaped like
+/// `password`: every class (`?l`, `?u`, `?d`, `?s`) it draws a character
+/// from, plus any other characters (once each) as they are. brute force
+/// works on bytes, so a non-ascii sample is refused rather than split
+pub fn infer_charset(password: &str) -> Result<(String, usize, usize)> {
+    if let Some(c) = password.chars().find(|c| !c.is_ascii()) {
+        anyhow::bail!("sample password has a non-ascii character {:?}; brute force tries single bytes - use --charset", c);
+    }
+    
+    let classes = [CharSet::lowercase(), CharSet::uppercase(), CharSet::digits(), CharSet::special()];
+    let mut charset = Vec::new();
+    
+    for class in &classes {
+        if password.bytes().any(|b| class.chars.contains(&b)) {
+            charset.extend_from_slice(&class.chars);
+        }
+    }
+    let mut seen = std::collections::HashSet::new();
+    for b in password.bytes() {
+        if !classes.iter().any(|class| class.chars.contains(&b)) && seen.insert(b) {
+            charset.push(b);
+        }
+    }
+    
+    let charset = String::from_utf8(charset).expect("ascii only");
+    Ok((charset, password.len(), password.len()))
+}
+
+/// write `hex digest<TAB>password` for every candidate `generator` yields -
+/// a precomputed lookup table for unsalted `algorithm` hashes. a password
+/// that would break its row (a tab or line break in it) is written in the
+/// potfile's `$HEX[..]` form. returns the number of entries written
+pub fn write_lookup_table(
+    generator: &mut dyn Generator,
+    algorithm: Algorithm,
+    out: &mut impl Write,
+) -> Result<u64> {
+    let hasher = create_hasher(algorithm);
+    let mut entries = 0u64;
+    
+    while let Some(batch) = generator.next_batch(4096) {
+        for candidate in batch {
+            out.write_all(hex::encode(hasher.hash(&candidate)).as_bytes())?;
+            out.write_all(b"\t")?;
+            let breaks_row = candidate.iter().any(|b| matches!(b, b'\t' | b'\n' | b'\r'));
+            if breaks_row || candidate.starts_with(b"$HEX[") {
+                write!(out, "$HEX[{}]", hex::encode(&candidate))?;
+            } else {
+                out.write_all(&candidate)?;
+            }
+            out.write_all(b"\n")?;
+            entries += 1;
+        }
+    }
+    generator.finish()?;
+    out.flush()?;
+    
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{MaskGenerator, RangeGenerator};
+
+    #[test]
+    fn test_hash_password_vectors() {
+        assert_eq!(hash_password(Algorithm::Md5, b"abc", None).unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+        // salted is md5("xyz" + "abc")
+        assert_eq!(hash_password(Algorithm::Md5, b"abc", Some(b"xyz")).unwrap(), "472dc90ed439d118ad3a205dda96eaf0");
+        assert_eq!(
+            hash_password(Algorithm::Sha256, b"abc", Some(b"xyz")).unwrap(),
+            "591243fe79a31b8535a77a50f8abe4a0a183d6deac6a5fd82595a82030f9dcee",
+        );
+        assert!(hash_password(Algorithm::Bcrypt, b"abc", Some(b"short")).is_err());
+    }
+
+    #[test]
+    fn test_hash_password_cracks_back_for_every_algorithm() {
+        let salt = b"0123456789abcdef";
+        for algorithm in Algorithm::ALL {
+            for salted in [false, true] {
+                let hash = hash_password(algorithm, b"abc", salted.then_some(&salt[..])).unwrap();
+                // a kdf's salt travels inside the phc string
+                let target_salt = (salted && !algorithm.is_kdf()).then(|| std::str::from_utf8(salt).unwrap());
+                let words = crate::core::MemoryGenerator::new(vec![b"abd".to_vec(), b"abc".to_vec()]);
+                let found = crate::core::engine::crack_one(&hash, algorithm, target_salt, Box::new(words));
+                assert_eq!(found.as_deref(), Some(&b"abc"[..]), "{} salted={}: {}", algorithm, salted, hash);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lookup_table_contents() {
+        let mut generator = RangeGenerator::new(Box::new(MaskGenerator::new("a?d").unwrap()), 0, Some(3));
+        let mut table = Vec::new();
+        let entries = write_lookup_table(&mut generator, Algorithm::Md5, &mut table).unwrap();
+
+        assert_eq!(entries, 3);
+        let text = String::from_utf8(table).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], format!("{}\ta0", hex::encode(create_hasher(Algorithm::Md5).hash(b"a0"))));
+        assert!(lines[2].ends_with("\ta2"));
+
+        // passwords that would split a row are hex-encoded
+        let mut words = crate::core::MemoryGenerator::new(vec![b"a\tb".to_vec(), b"c\nd".to_vec(), b"$HEX[00]".to_vec()]);
+        let mut table = Vec::new();
+        write_lookup_table(&mut words, Algorithm::Md5, &mut table).unwrap();
+        let text = String::from_utf8(table).unwrap();
+        let passwords: Vec<&str> = text.lines().map(|line| line.split_once('\t').unwrap().1).collect();
+        assert_eq!(passwords, ["$HEX[610962]", "$HEX[630a64]", "$HEX[244845585b30305d]"]);
+    }
+
+    #[test]
+    fn test_answer_key_matches_synthetic_targets() {
+        let passwords = synthetic_passwords(20, "abc123", 3, 5, 7);
+        assert_eq!(passwords, synthetic_passwords(20, "abc123", 3, 5, 7));
+        assert_ne!(passwords, synthetic_passwords(20, "abc123", 3, 5, 8));
+        assert!(passwords.iter().all(|p| (3..=5).contains(&p.len()) && p.chars().all(|c| "abc123".contains(c))));
+
+        let algorithms = [Algorithm::Md5, Algorithm::Sha1];
+        let targets = generate_demo_targets(&passwords, &algorithms, PasswordEncoding::Utf8).unwrap();
+        let key = answer_key(&passwords, &algorithms);
+        let lines: Vec<&str> = key.lines().collect();
+        assert_eq!(lines.len(), targets.len());
+
+        for (line, target) in lines.iter().zip(&targets) {
+            let (id, password) = line.split_once('\t').unwrap();
+            assert_eq!(id, target.id);
+            let hasher = create_hasher(target.algorithm);
+            let hash = match target.salt.is_empty() {
+                true => hasher.hash(password.as_bytes()),
+                false => hasher.hash_with_salt(password.as_bytes(), target.salt.as_bytes()),
+            };
+            assert!(target.matches(&hash), "{} doesn't hash to {}", password, target.id);
+        }
+    }
+
+    #[test]
+    fn test_mask_of() {
+        assert_eq!(mask_of("Pass1!"), "?u?l?l?l?d?s");
+        assert_eq!(mask_of("hi there?"), "?l?l ?l?l?l?l?l?s");
+        assert_eq!(mask_of("~café"), "~?l?l?lé");
+        assert_eq!(mask_of(""), "");
+
+        // every mask produces the password it came from
+        for password in ["A1!", "a b\"c", "~cé"] {
+            let mut generator = crate::core::MaskGenerator::new(&mask_of(password)).unwrap();
+            let mut found = false;
+            while let Some(batch) = generator.next_batch(4096) {
+                found |= batch.iter().any(|c| c == password.as_bytes());
+            }
+            assert!(found, "{}", password);
+        }
+    }
+
+    #[test]
+    fn test_infer_charset() {
+        assert_eq!(infer_charset("4821").unwrap(), ("0123456789".to_string(), 4, 4));
+        assert_eq!(infer_charset("hello").unwrap(), ("abcdefghijklmnopqrstuvwxyz".to_string(), 5, 5));
+
+        let (charset, min, max) = infer_charset("Abc123").unwrap();
+        assert_eq!(charset, "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789");
+        assert_eq!((min, max), (6, 6));
+
+        // a special character brings in its whole class; a space only itself
+        let (charset, ..) = infer_charset("a b!").unwrap();
+        assert_eq!(charset, format!("abcdefghijklmnopqrstuvwxyz{} ", String::from_utf8(CharSet::special().chars).unwrap()));
+        assert_eq!(infer_charset("").unwrap().0, "");
+        // other characters appear once however they're spread out
+        assert_eq!(infer_charset("a a\ta").unwrap().0, "abcdefghijklmnopqrstuvwxyz \t");
+        assert!(infer_charset("café").is_err());
+
+        // every inferred charset can brute force the sample back
+        for password in ["x9", "Q z"] {
+            let (charset, min, max) = infer_charset(password).unwrap();
+            let mut generator = crate::core::BruteForceGenerator::new(&charset, min, max);
+            let mut found = false;
+            while let Some(batch) = generator.next_batch(4096) {
+                found |= batch.iter().any(|c| c == password.as_bytes());
+            }
+            assert!(found, "{}", password);
+        }
+    }
+
+    #[test]
+    fn test_is_demo_target() {
+        let mut targets = generate_demo_targets(&["ab1".to_string(), "zz9".to_string()], &[Algorithm::Sha256], PasswordEncoding::Utf8).unwrap();
+        assert!(targets.iter().all(is_demo_target));
+
+        // a real dump renamed to look like one still has to match the algorithm and user
+        targets[0].algorithm = Algorithm::Md5;
+        assert!(!is_demo_target(&targets[0]));
+        targets[1].username = "root".to_string();
+        assert!(!is_demo_target(&targets[1]));
+    }
+}
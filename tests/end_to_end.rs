@@ -0,0 +1,242 @@
+// end to end: the compiled binary through the flows a user would take -
+// generate-targets, run with each strategy, report on the log, selftest
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const BIN: &str = env!("CARGO_BIN_EXE_blitzforge");
+
+/// a temp directory per test, removed when the test ends
+struct Workspace(PathBuf);
+
+impl Workspace {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("blitzforge_e2e_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        Workspace(dir)
+    }
+
+    fn path(&self, file: &str) -> PathBuf {
+        self.0.join(file)
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.0).ok();
+    }
+}
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+fn blitzforge(args: &[&str]) -> Output {
+    Command::new(BIN).args(args).output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+/// three made-up passwords of three letters from "abc", always the same three
+fn seeded_targets(ws: &Workspace) -> PathBuf {
+    let targets = ws.path("seeded.json");
+    let output = blitzforge(&[
+        "generate-targets", "--seed-passwords", "3", "--charset", "abc",
+        "--min-len", "3", "--max-len", "3", "--seed", "7",
+        "--algorithms", "md5,sha256", "--out", path_str(&targets),
+    ]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    assert!(stdout(&output).contains("Generated 6 targets"), "{}", stdout(&output));
+    targets
+}
+
+/// the fixture passwords, every one of them in the fixture wordlist
+fn dictionary_targets(ws: &Workspace) -> PathBuf {
+    let targets = ws.path("dictionary.json");
+    let output = blitzforge(&[
+        "generate-targets", "--algorithms", "md5,sha1",
+        "--passwords", path_str(&fixture("passwords.txt")), "--out", path_str(&targets),
+    ]);
+    assert!(output.status.success(), "{}", stdout(&output));
+    targets
+}
+
+/// `run` with `args`, expecting every target cracked
+fn run_cracks_all(targets: &Path, args: &[&str], total: usize) {
+    let mut argv = vec!["run", "--targets", path_str(targets), "--workers", "2"];
+    argv.extend(args);
+    let output = blitzforge(&argv);
+
+    let text = stdout(&output);
+    assert_eq!(output.status.code(), Some(0), "{:?}\n{}", args, text);
+    assert!(text.contains(&format!("Matches Found: {}/{}", total, total)), "{:?}\n{}", args, text);
+}
+
+#[test]
+fn test_dictionary_strategy() {
+    let ws = Workspace::new("dictionary");
+    let targets = dictionary_targets(&ws);
+    run_cracks_all(&targets, &["--strategy", "dictionary", "--wordlist", path_str(&fixture("words.txt"))], 6);
+}
+
+#[test]
+fn test_mask_strategy() {
+    let ws = Workspace::new("mask");
+    let targets = seeded_targets(&ws);
+    run_cracks_all(&targets, &["--strategy", "mask", "--mask", "?l?l?l"], 6);
+}
+
+#[test]
+fn test_brute_strategy() {
+    let ws = Workspace::new("brute");
+    let targets = seeded_targets(&ws);
+    run_cracks_all(&targets, &["--strategy", "brute", "--charset", "abc", "--min-len", "1", "--max-len", "3"], 6);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_command_strategy() {
+    let ws = Workspace::new("command");
+    let targets = dictionary_targets(&ws);
+    let cmd = format!("cat {}", path_str(&fixture("words.txt")));
+    run_cracks_all(&targets, &["--generator-cmd", &cmd], 6);
+}
+
+#[test]
+fn test_partial_crack_exits_2_and_lists_the_rest() {
+    let ws = Workspace::new("partial");
+    let targets = seeded_targets(&ws);
+    let unfound = ws.path("unfound.txt");
+
+    // "a" and "b" only: the password with a "c" in it survives
+    let output = blitzforge(&[
+        "run", "--targets", path_str(&targets), "--strategy", "brute",
+        "--charset", "ab", "--min-len", "3", "--max-len", "3",
+        "--output-unfound", path_str(&unfound),
+    ]);
+
+    assert_eq!(output.status.code(), Some(2), "{}", stdout(&output));
+    let unfound = std::fs::read_to_string(unfound).unwrap();
+    assert_eq!(unfound.lines().count(), 4, "{}", unfound);
+    assert!(unfound.contains("demo1_md5") && unfound.contains("demo2_sha256"), "{}", unfound);
+}
+
+#[test]
+fn test_potfile_preloads_earlier_finds() {
+    let ws = Workspace::new("potfile");
+    let targets = seeded_targets(&ws);
+    let results = ws.path("results.json");
+    let potfile = ws.path("found.pot");
+
+    let output = blitzforge(&[
+        "run", "--targets", path_str(&targets), "--strategy", "brute",
+        "--charset", "ab", "--min-len", "3", "--max-len", "3",
+        "--results", path_str(&results),
+    ]);
+    assert_eq!(output.status.code(), Some(2), "{}", stdout(&output));
+    let merged = ws.path("merged.json");
+    let output = blitzforge(&[
+        "merge", path_str(&results), "--potfile", path_str(&potfile), "--json", path_str(&merged),
+    ]);
+    assert!(output.status.success(), "{}", stdout(&output));
+
+    // an empty wordlist guesses nothing, so the finds all come from the potfile
+    let words = ws.path("empty.txt");
+    std::fs::write(&words, "").unwrap();
+    for preload in [&results, &potfile] {
+        let output = blitzforge(&[
+            "run", "--targets", path_str(&targets), "--strategy", "dictionary",
+            "--wordlist", path_str(&words), "--potfile", path_str(preload),
+        ]);
+        let text = stdout(&output);
+        assert_eq!(output.status.code(), Some(2), "{}", text);
+        assert!(text.contains("2 already cracked"), "{}", text);
+        assert!(text.contains("Matches Found: 2/6"), "{}", text);
+    }
+}
+
+#[test]
+fn test_logged_runs_feed_the_report() {
+    let ws = Workspace::new("report");
+    let targets = seeded_targets(&ws);
+    let log = ws.path("bench.csv");
+
+    run_cracks_all(&targets, &["--strategy", "mask", "--mask", "?l?l?l", "--repeat", "2", "--log", path_str(&log)], 6);
+    // one row per target per run, under a header
+    assert_eq!(std::fs::read_to_string(&log).unwrap().lines().count(), 13);
+
+    let output = blitzforge(&["report", "--csv", path_str(&log)]);
+    let text = stdout(&output);
+    assert!(output.status.success(), "{}", text);
+    assert!(text.contains("Total runs: 12"), "{}", text);
+    assert!(text.contains("MD5:") && text.contains("SHA256:"), "{}", text);
+
+    let output = blitzforge(&["report", "--csv", path_str(&log), "--format", "json"]);
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(json.is_array() || json.is_object(), "{}", json);
+}
+
+#[test]
+fn test_seeded_targets_are_reproducible() {
+    let first = Workspace::new("seed_a");
+    let second = Workspace::new("seed_b");
+    seeded_targets(&first);
+    seeded_targets(&second);
+
+    let key = |ws: &Workspace| std::fs::read_to_string(ws.path("seeded.answers.txt")).unwrap();
+    assert_eq!(key(&first), key(&second));
+    assert_eq!(key(&first).lines().count(), 6);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_sigterm_writes_the_report_and_exits_5() {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+
+    let ws = Workspace::new("sigterm");
+    let targets = seeded_targets(&ws);
+    let report = ws.path("report.json");
+
+    // eight characters from 36: far too many to finish before the signal
+    let mut child = Command::new(BIN)
+        .args([
+            "run", "--targets", path_str(&targets), "--strategy", "brute",
+            "--charset", "abcdefghijklmnopqrstuvwxyz0123456789", "--min-len", "8", "--max-len", "8",
+            "--workers", "2", "--report-out", path_str(&report),
+        ])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+    assert!(lines.any(|line| line.unwrap().contains("Starting cracking engine")));
+    std::thread::sleep(std::time::Duration::from_millis(500));
+
+    let killed = Command::new("kill").args(["-TERM", &child.id().to_string()]).status().unwrap();
+    assert!(killed.success());
+    // keep draining so the child never blocks on a full pipe
+    let rest: Vec<String> = lines.map(Result::unwrap).collect();
+    let status = child.wait().unwrap();
+
+    assert_eq!(status.code(), Some(5), "{}", rest.join("\n"));
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&report).unwrap()).unwrap();
+    assert_eq!(json["statistics"]["targets_found"], 0);
+    assert!(json["statistics"]["guesses_tried"].as_u64().unwrap() > 0, "{}", json);
+}
+
+#[test]
+fn test_selftest_passes() {
+    let output = blitzforge(&["selftest"]);
+    let text = stdout(&output);
+    assert_eq!(output.status.code(), Some(0), "{}", text);
+    assert!(text.contains("All self-tests passed"), "{}", text);
+}
@@ -0,0 +1,150 @@
+// exit-code contract: run the real binary and check what scripts would see
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+const BIN: &str = env!("CARGO_BIN_EXE_blitzforge");
+
+/// demo targets for `passwords`, written to a per-test temp file
+fn targets_file(name: &str, passwords: &[&str]) -> PathBuf {
+    let dir = std::env::temp_dir();
+    let pw_path = dir.join(format!("blitzforge_exit_{}_{}.txt", name, std::process::id()));
+    let targets_path = dir.join(format!("blitzforge_exit_{}_{}.json", name, std::process::id()));
+    std::fs::write(&pw_path, passwords.join("\n")).unwrap();
+
+    let output = Command::new(BIN)
+        .args(["generate-targets", "--algorithms", "md5"])
+        .arg("--passwords").arg(&pw_path)
+        .arg("--out").arg(&targets_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    std::fs::remove_file(pw_path).ok();
+    targets_path
+}
+
+fn run_mask(targets: &PathBuf, mask: &str) -> Output {
+    Command::new(BIN)
+        .arg("run")
+        .arg("--targets").arg(targets)
+        .args(["--strategy", "mask", "--mask", mask, "--workers", "2"])
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_all_found_exits_0() {
+    let targets = targets_file("all", &["ab1", "zz9"]);
+    assert_eq!(run_mask(&targets, "?l?l?d").status.code(), Some(0));
+    std::fs::remove_file(targets).ok();
+}
+
+#[test]
+fn test_some_unfound_exits_2() {
+    let targets = targets_file("some", &["ab1", "not-in-keyspace"]);
+    assert_eq!(run_mask(&targets, "?l?l?d").status.code(), Some(2));
+    std::fs::remove_file(targets).ok();
+}
+
+#[test]
+fn test_invalid_arguments_exit_3() {
+    // rejected by the parser
+    let output = Command::new(BIN).args(["run", "--no-such-flag"]).output().unwrap();
+    assert_eq!(output.status.code(), Some(3));
+
+    // parses, but the mask strategy has no mask
+    let targets = targets_file("args", &["ab1"]);
+    let output = Command::new(BIN)
+        .arg("run")
+        .arg("--targets").arg(&targets)
+        .args(["--strategy", "mask"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+
+    // a timeout that isn't a positive duration
+    for secs in ["-1", "NaN", "inf"] {
+        let output = Command::new(BIN)
+            .arg("run")
+            .arg("--targets").arg(&targets)
+            .args(["--strategy", "mask", "--mask", "?d", "--batch-timeout", secs])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(3), "--batch-timeout {}", secs);
+    }
+    std::fs::remove_file(targets).ok();
+
+    // an empty lookup table
+    let out = std::env::temp_dir().join(format!("blitzforge_exit_table_{}.tsv", std::process::id()));
+    let output = Command::new(BIN)
+        .args(["generate-table", "--algo", "md5", "--strategy", "mask", "--mask", "?d", "--limit", "0"])
+        .arg("--out").arg(&out)
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(3));
+    assert!(!out.exists());
+}
+
+#[test]
+fn test_help_exits_0() {
+    let output = Command::new(BIN).arg("--help").output().unwrap();
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn test_other_errors_exit_1() {
+    let output = Command::new(BIN)
+        .args(["run", "--targets", "/nonexistent/targets.json", "--strategy", "mask", "--mask", "?d"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_interrupted_exits_4() {
+    let targets = targets_file("interrupt", &["never-found"]);
+    let mut child = Command::new(BIN)
+        .arg("run")
+        .arg("--targets").arg(&targets)
+        .args(["--strategy", "brute", "--max-len", "12", "--workers", "1"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+
+    // give it time to install the handler and start cracking
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    let status = Command::new("kill").args(["-INT", &child.id().to_string()]).status().unwrap();
+    assert!(status.success());
+
+    assert_eq!(child.wait().unwrap().code(), Some(4));
+    std::fs::remove_file(targets).ok();
+}
+
+#[test]
+fn test_non_demo_targets_need_acknowledgment() {
+    // a bare hash list, as from a real dump: md5("ab1")
+    let hashes = std::env::temp_dir().join(format!("blitzforge_exit_ack_{}.txt", std::process::id()));
+    std::fs::write(&hashes, "68b6a776378decbb4a79cda89087c4ce\n").unwrap();
+
+    let run = |ack: bool| {
+        let mut command = Command::new(BIN);
+        command.arg("run")
+            .arg("--targets").arg(&hashes)
+            .args(["--hash-algo", "md5", "--strategy", "mask", "--mask", "?l?l?d"])
+            .env_remove("BLITZFORGE_ACK");
+        if ack {
+            command.env("BLITZFORGE_ACK", "1");
+        }
+        command.output().unwrap()
+    };
+
+    let refused = run(false);
+    assert_eq!(refused.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&refused.stderr).contains("--i-understand"));
+
+    assert_eq!(run(true).status.code(), Some(0));
+    std::fs::remove_file(hashes).ok();
+}
@@ -0,0 +1,41 @@
+// `run --json`: stdout carries only the report, progress goes to stderr
+
+use std::process::Command;
+
+const BIN: &str = env!("CARGO_BIN_EXE_blitzforge");
+
+#[test]
+fn test_json_stdout_is_parseable() {
+    let dir = std::env::temp_dir();
+    let id = std::process::id();
+    let passwords = dir.join(format!("blitzforge_json_pw_{}.txt", id));
+    let targets = dir.join(format!("blitzforge_json_{}.json", id));
+
+    std::fs::write(&passwords, "ab1\nzz9\n").unwrap();
+    let output = Command::new(BIN)
+        .args(["generate-targets", "--algorithms", "md5"])
+        .arg("--passwords").arg(&passwords)
+        .arg("--out").arg(&targets)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let output = Command::new(BIN)
+        .arg("run")
+        .arg("--targets").arg(&targets)
+        .args(["--strategy", "mask", "--mask", "?l?l?d", "--workers", "2", "--json"])
+        .output()
+        .unwrap();
+
+    std::fs::remove_file(&passwords).ok();
+    std::fs::remove_file(&targets).ok();
+
+    assert_eq!(output.status.code(), Some(0));
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .unwrap_or_else(|e| panic!("stdout isn't json ({}): {}", e, String::from_utf8_lossy(&output.stdout)));
+    assert_eq!(report["statistics"]["targets_found"], 2);
+    assert_eq!(report["targets"].as_array().unwrap().len(), 2);
+
+    // the ui still ran, just on the other stream
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Matches Found: 2/2"));
+}
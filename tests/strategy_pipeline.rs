@@ -0,0 +1,77 @@
+// --strategy-pipeline: later stages only see what earlier stages missed
+
+use std::process::Command;
+
+const BIN: &str = env!("CARGO_BIN_EXE_blitzforge");
+
+#[test]
+fn test_dictionary_then_mask_cracks_both() {
+    let dir = std::env::temp_dir();
+    let id = std::process::id();
+    let passwords = dir.join(format!("blitzforge_pipeline_pw_{}.txt", id));
+    let targets = dir.join(format!("blitzforge_pipeline_{}.json", id));
+    let wordlist = dir.join(format!("blitzforge_pipeline_words_{}.txt", id));
+
+    // "sunshine" only the dictionary finds, "ab1" only the mask
+    std::fs::write(&passwords, "sunshine\nab1\n").unwrap();
+    std::fs::write(&wordlist, "password\nsunshine\nletmein\n").unwrap();
+
+    let output = Command::new(BIN)
+        .args(["generate-targets", "--algorithms", "md5"])
+        .arg("--passwords").arg(&passwords)
+        .arg("--out").arg(&targets)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let output = Command::new(BIN)
+        .arg("run")
+        .arg("--targets").arg(&targets)
+        .arg("--wordlist").arg(&wordlist)
+        .args(["--strategy-pipeline", "dictionary,mask", "--mask", "?l?l?d", "--workers", "2"])
+        .output()
+        .unwrap();
+
+    for path in [&passwords, &targets, &wordlist] {
+        std::fs::remove_file(path).ok();
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(0), "{}", stdout);
+    assert!(stdout.contains("Stage 1/2: dictionary (2 targets left)"), "{}", stdout);
+    assert!(stdout.contains("Stage 2/2: mask (1 targets left)"), "{}", stdout);
+    assert!(stdout.contains("demo0_md5 cracked by stage 1 (dictionary)"), "{}", stdout);
+    assert!(stdout.contains("demo1_md5 cracked by stage 2 (mask)"), "{}", stdout);
+}
+
+#[test]
+fn test_pipeline_stops_once_everything_cracks() {
+    let dir = std::env::temp_dir();
+    let id = std::process::id();
+    let passwords = dir.join(format!("blitzforge_pipeline_early_pw_{}.txt", id));
+    let targets = dir.join(format!("blitzforge_pipeline_early_{}.json", id));
+
+    std::fs::write(&passwords, "ab1\n").unwrap();
+    let output = Command::new(BIN)
+        .args(["generate-targets", "--algorithms", "md5"])
+        .arg("--passwords").arg(&passwords)
+        .arg("--out").arg(&targets)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // brute force would take far longer than the test allows
+    let output = Command::new(BIN)
+        .arg("run")
+        .arg("--targets").arg(&targets)
+        .args(["--strategy-pipeline", "mask,brute", "--mask", "?l?l?d", "--max-len", "12"])
+        .output()
+        .unwrap();
+
+    std::fs::remove_file(&passwords).ok();
+    std::fs::remove_file(&targets).ok();
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(0), "{}", stdout);
+    assert!(!stdout.contains("Stage 2/2"), "{}", stdout);
+}